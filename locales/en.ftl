@@ -0,0 +1,141 @@
+## Snackbar
+error = Error
+success = Success
+close = Close
+could-not-process-request = Could not process request
+unauthorized = Unauthorized
+
+## Account
+user-created = Created user sucessfully
+user-missing = User does not exist
+details-invalid = Invalid personal details
+details-invalid-version = Invalid version
+details-changed-elsewhere = Your details were changed elsewhere, reload and try again
+details-updated = Updated personal details succesfully
+details-too-long = At most { $max } characters
+details-country-unknown = Unknown country code
+details-postal-code-invalid = Invalid postal code for { $country }
+account-deletion-pending = Account deletion in progress
+account-deleted = Succesfully deleted account
+email-already-verified = Already verified email
+email-sent = Succesfully send email
+email-resend-after = Already send email, try again after { $time }
+email-verified-title = Email verified
+email-verified = Your email address has been verified
+kyc-already-verified = Already verified
+kyc-submitted = Submitted document for review
+kyc-not-pending = Verification is not pending
+kyc-reviewed = Reviewed verification
+identity-verified-title = Identity verified
+identity-verified = Your identity has been verified
+identity-rejected-title = Identity verification rejected
+identity-rejected = Please submit your documents again
+limit-missing = No limit set
+limit-set = Limit set
+limit-decreased = Limit decreased
+limit-increases-on = Limit increases on { $time }
+limit-removed-on = Limit removed on { $time }
+limit-changed-title = Limit changed
+limit-pending-change = Changes to { $amount } on { $time }
+limit-pending-removal = Removed on { $time }
+limit-self-excluded = Self-excluded indefinitely
+limit-self-excluded-until = Self-excluded until { $time }
+limit-reached = { $period } { $kind } limit of { $limit } reached
+session-time-reminder = You have been playing for { $minutes } minutes
+session-time-title = Session time
+self-exclusion-longer = Already self-excluded for longer
+self-exclusion-active = Self-exclusion active
+export-expired = Download link has expired
+export-pending = Export is still being prepared
+export-failed = Export failed, please request a new one
+export-ready-title = Export ready
+export-ready = Your data export can be downloaded from { $link }
+deposit-credited-title = Deposit credited
+deposit-credited = { $amount } { $currency } was added to your balance
+locale-updated = Updated language
+locale-english = English
+locale-dutch = Nederlands
+locale-title = Language
+
+## Payment
+deposit-verification-required = Verify your identity to deposit more
+deposit-limit-exceeded = { $period } deposit limit of { $limit } exceeded
+deposit-limit-updated = Deposit limit updated
+balance-added = Succesfully added balance
+
+## Game catalog
+catalog-title = Games
+favourites-title = Favourites
+recent-title = Recently played
+catalog-search = Search
+catalog-tag = Tag
+catalog-all = All
+catalog-loading = Loading...
+catalog-by = by
+catalog-plays-week = plays this week
+category-Table = Table
+category-Card = Card
+category-Slots = Slots
+category-Live = Live
+sort-popularity = Most played
+sort-trending = Trending
+sort-name = Name
+sort-newest = Newest
+game-category = Category
+game-provider = Provider
+game-rtp = RTP
+game-bet = Bet
+game-currencies = Currencies
+game-released = Released
+game-plays-today = Plays today
+game-plays-week = Plays this week
+game-does-not-exist = Game does not exist
+game-slug-exists = Slug already exists
+game-created = Created game
+game-updated = Updated game
+game-enabled = Enabled game
+game-disabled = Disabled game
+game-countries-updated = Updated game countries
+game-translation-updated = Updated game translation
+game-translation-removed = Removed game translation
+game-translation-missing = Game has no such translation
+game-deleted = Deleted game
+machine-updated = Updated slot machine
+machine-game-missing = No slot game with this slug
+favourite = Favourite
+favourite-added = Added to favourites
+favourite-missing = Game is not a favourite
+favourite-removed = Removed from favourites
+
+## Blackjack
+blackjack = Blackjack
+blackjack-dealer = Dealer
+blackjack-hand = Hand
+blackjack-bet = Bet
+blackjack-deal = Deal
+blackjack-staked = Staked
+blackjack-payout = Paid out
+blackjack-action-Hit = Hit
+blackjack-action-Stand = Stand
+blackjack-action-Double = Double
+blackjack-action-Split = Split
+blackjack-action-Insurance = Insurance
+blackjack-action-DeclineInsurance = No insurance
+blackjack-outcome-Blackjack = Blackjack
+blackjack-outcome-Win = Win
+blackjack-outcome-Push = Push
+blackjack-outcome-Lose = Lose
+blackjack-round-open = Finish the round in play first
+blackjack-no-round = No round in play
+blackjack-action-not-allowed = Action is not allowed
+blackjack-round-changed = Round was played elsewhere, reload and try again
+insufficient-balance = Insufficient balance
+stake-pending = Your stake is being confirmed, please try again shortly
+
+## Slots
+slots-line = Line
+slots-scatter = Scatter
+slots-payout = Paid out
+slots-spin = Spin
+slots-bet-out-of-range = Bet must be between { $min } and { $max }
+slots-currency-unsupported = Game does not accept the currency of your wallet
@@ -0,0 +1,141 @@
+## Snackbar
+error = Fout
+success = Gelukt
+close = Sluiten
+could-not-process-request = Kon het verzoek niet verwerken
+unauthorized = Geen toegang
+
+## Account
+user-created = Gebruiker aangemaakt
+user-missing = Gebruiker bestaat niet
+details-invalid = Ongeldige persoonsgegevens
+details-invalid-version = Ongeldige versie
+details-changed-elsewhere = Je gegevens zijn elders gewijzigd, herlaad en probeer opnieuw
+details-updated = Persoonsgegevens bijgewerkt
+details-too-long = Maximaal { $max } tekens
+details-country-unknown = Onbekende landcode
+details-postal-code-invalid = Ongeldige postcode voor { $country }
+account-deletion-pending = Account wordt verwijderd
+account-deleted = Account verwijderd
+email-already-verified = E-mailadres is al bevestigd
+email-sent = E-mail verstuurd
+email-resend-after = E-mail is al verstuurd, probeer opnieuw na { $time }
+email-verified-title = E-mailadres bevestigd
+email-verified = Je e-mailadres is bevestigd
+kyc-already-verified = Al geverifieerd
+kyc-submitted = Document ingediend ter beoordeling
+kyc-not-pending = Verificatie wacht niet op beoordeling
+kyc-reviewed = Verificatie beoordeeld
+identity-verified-title = Identiteit geverifieerd
+identity-verified = Je identiteit is geverifieerd
+identity-rejected-title = Identiteitsverificatie afgewezen
+identity-rejected = Dien je documenten opnieuw in
+limit-missing = Geen limiet ingesteld
+limit-set = Limiet ingesteld
+limit-decreased = Limiet verlaagd
+limit-increases-on = Limiet wordt verhoogd op { $time }
+limit-removed-on = Limiet vervalt op { $time }
+limit-changed-title = Limiet gewijzigd
+limit-pending-change = Wordt { $amount } op { $time }
+limit-pending-removal = Vervalt op { $time }
+limit-self-excluded = Voor onbepaalde tijd uitgesloten
+limit-self-excluded-until = Uitgesloten tot { $time }
+limit-reached = { $period } { $kind } limiet van { $limit } bereikt
+session-time-reminder = Je speelt al { $minutes } minuten
+session-time-title = Speeltijd
+self-exclusion-longer = Al langer uitgesloten
+self-exclusion-active = Zelfuitsluiting actief
+export-expired = Downloadlink is verlopen
+export-pending = Export wordt nog voorbereid
+export-failed = Export mislukt, vraag een nieuwe aan
+export-ready-title = Export klaar
+export-ready = Je gegevensexport kan worden gedownload via { $link }
+deposit-credited-title = Storting bijgeschreven
+deposit-credited = { $amount } { $currency } is aan je saldo toegevoegd
+locale-updated = Taal bijgewerkt
+locale-english = English
+locale-dutch = Nederlands
+locale-title = Taal
+
+## Payment
+deposit-verification-required = Verifieer je identiteit om meer te storten
+deposit-limit-exceeded = { $period } stortingslimiet van { $limit } overschreden
+deposit-limit-updated = Stortingslimiet aangepast
+balance-added = Saldo toegevoegd
+
+## Game catalog
+catalog-title = Spellen
+favourites-title = Favorieten
+recent-title = Recent gespeeld
+catalog-search = Zoeken
+catalog-tag = Label
+catalog-all = Alle
+catalog-loading = Laden...
+catalog-by = door
+catalog-plays-week = keer gespeeld deze week
+category-Table = Tafel
+category-Card = Kaarten
+category-Slots = Gokkasten
+category-Live = Live
+sort-popularity = Meest gespeeld
+sort-trending = Populair
+sort-name = Naam
+sort-newest = Nieuwste
+game-category = Categorie
+game-provider = Aanbieder
+game-rtp = RTP
+game-bet = Inzet
+game-currencies = Valuta
+game-released = Uitgebracht
+game-plays-today = Vandaag gespeeld
+game-plays-week = Deze week gespeeld
+game-does-not-exist = Spel bestaat niet
+game-slug-exists = Slug bestaat al
+game-created = Spel aangemaakt
+game-updated = Spel bijgewerkt
+game-enabled = Spel ingeschakeld
+game-disabled = Spel uitgeschakeld
+game-countries-updated = Landen van spel bijgewerkt
+game-translation-updated = Vertaling van spel bijgewerkt
+game-translation-removed = Vertaling van spel verwijderd
+game-translation-missing = Spel heeft deze vertaling niet
+game-deleted = Spel verwijderd
+machine-updated = Gokkast bijgewerkt
+machine-game-missing = Geen gokkast met deze slug
+favourite = Favoriet
+favourite-added = Toegevoegd aan favorieten
+favourite-missing = Spel is geen favoriet
+favourite-removed = Verwijderd uit favorieten
+
+## Blackjack
+blackjack = Blackjack
+blackjack-dealer = Dealer
+blackjack-hand = Hand
+blackjack-bet = Inzet
+blackjack-deal = Delen
+blackjack-staked = Ingezet
+blackjack-payout = Uitbetaald
+blackjack-action-Hit = Kaart
+blackjack-action-Stand = Passen
+blackjack-action-Double = Verdubbelen
+blackjack-action-Split = Splitsen
+blackjack-action-Insurance = Verzekering
+blackjack-action-DeclineInsurance = Geen verzekering
+blackjack-outcome-Blackjack = Blackjack
+blackjack-outcome-Win = Gewonnen
+blackjack-outcome-Push = Gelijkspel
+blackjack-outcome-Lose = Verloren
+blackjack-round-open = Speel eerst de huidige ronde uit
+blackjack-no-round = Geen ronde bezig
+blackjack-action-not-allowed = Actie is niet toegestaan
+blackjack-round-changed = Ronde is elders gespeeld, herlaad en probeer opnieuw
+insufficient-balance = Onvoldoende saldo
+stake-pending = Je inzet wordt bevestigd, probeer het zo opnieuw
+
+## Slots
+slots-line = Lijn
+slots-scatter = Scatter
+slots-payout = Uitbetaald
+slots-spin = Draai
+slots-bet-out-of-range = Inzet moet tussen { $min } en { $max } liggen
+slots-currency-unsupported = Spel accepteert de valuta van je wallet niet
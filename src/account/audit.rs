@@ -0,0 +1,152 @@
+mod model;
+
+pub(crate) mod db;
+
+pub(crate) use model::{AuditAction, AuditContext, AuditEntry};
+
+use self::{
+    db::get_audit_entries,
+    model::{AuditQuery, AuditRecord},
+};
+
+use crate::{model::AdminToken, StateParams};
+
+use askama::Template;
+use axum::{extract::State, response::Html};
+use leprecon::{
+    template::{self, Snackbar},
+    utils::{
+        extract::{extract_conn_from_pool, ValidForm},
+        PostgresConn,
+    },
+};
+use reqwest::StatusCode;
+use tracing::error;
+
+/// Changes made to the account of a user, optionally within a time range.
+pub(super) async fn audit_log(
+    _: AdminToken,
+    State(state): State<StateParams>,
+    ValidForm(query): ValidForm<AuditQuery>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    let postgres_conn: PostgresConn = match extract_conn_from_pool(&state.2, &mut snackbar).await {
+        Ok(v) => v,
+        Err(e) => return e,
+    };
+
+    let entries: Vec<AuditRecord> = match get_audit_entries(&query, &postgres_conn).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Could not get audit log: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    let audit_template: template::AuditLog = template::AuditLog {
+        entries: entries
+            .into_iter()
+            .map(|e| template::AuditLogEntry {
+                sub: e.sub,
+                actor: e.actor,
+                action: e.action,
+                diff: e.diff.map(|v| v.to_string()),
+                request_id: e.request_id,
+                ip: e.ip,
+                created: e.created.format("%Y-%m-%d %H:%M:%S").to_string(),
+            })
+            .collect(),
+    };
+
+    (StatusCode::OK, Html(audit_template.render().unwrap()))
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{body::Body, extract::ConnectInfo, http::Request};
+    use reqwest::{header, Method, StatusCode};
+    use std::net::SocketAddr;
+    use tower::ServiceExt;
+
+    use crate::fixture::{admin_bearer, assert_body_contains, initialize, seed_database};
+
+    #[tokio::test]
+    async fn test_no_params_provided_audit_log() {
+        let app: axum::Router = initialize().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/admin/audit")
+                    .header(header::AUTHORIZATION, admin_bearer())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_invalid_range() {
+        let app: axum::Router = initialize().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/admin/audit?sub=auth0|0004&from=2024-02-01T00:00:00%2B00:00&to=2024-01-01T00:00:00%2B00:00")
+                    .header(header::AUTHORIZATION, admin_bearer())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_body_contains(response, &["from: must be before to"]).await;
+    }
+
+    #[tokio::test]
+    async fn test_update_is_audited() {
+        let app: axum::Router = initialize().await;
+        seed_database().await;
+
+        let params: String = String::from("sub=auth0|0005&first_name=Audited");
+
+        let response: axum::http::Response<Body> = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::PUT)
+                    .uri("/account/user/information")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .header("x-forwarded-for", "203.0.113.7")
+                    .extension(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 8080))))
+                    .body(params)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/admin/audit?sub=auth0|0005")
+                    .header(header::AUTHORIZATION, admin_bearer())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_body_contains(response, &["Audited", "203.0.113.7"]).await;
+    }
+}
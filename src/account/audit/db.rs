@@ -0,0 +1,33 @@
+use super::model::{AuditEntry, AuditQuery, AuditRecord};
+
+use chrono::Local;
+use leprecon::utils::PostgresConn;
+use tokio_postgres::{GenericClient, Row};
+
+/// Appends the entry, pass a transaction to log together with the change itself.
+pub(crate) async fn insert_audit_entry<C: GenericClient>(
+    entry: &AuditEntry<'_>,
+    db_client: &C,
+) -> Result<u64, tokio_postgres::Error> {
+    db_client
+        .execute(
+            "INSERT INTO audit_log(sub, actor, action, diff, request_id, ip, created) VALUES($1, $2, $3, $4, $5, $6, $7)",
+            &[&entry.sub, &entry.actor, &entry.action.to_string(), &entry.diff, &entry.context.request_id, &entry.context.ip, &Local::now()],
+        )
+        .await
+}
+
+/// Newest entries of the user first, at most 500.
+pub(super) async fn get_audit_entries(
+    query: &AuditQuery,
+    db_client: &PostgresConn<'_>,
+) -> Result<Vec<AuditRecord>, tokio_postgres::Error> {
+    let rows: Vec<Row> = db_client
+        .query(
+            "SELECT * FROM audit_log WHERE sub = $1 AND ($2::timestamptz IS NULL OR created >= $2) AND ($3::timestamptz IS NULL OR created <= $3) ORDER BY created DESC LIMIT 500",
+            &[&query.sub, &query.from, &query.to],
+        )
+        .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+}
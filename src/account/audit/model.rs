@@ -0,0 +1,203 @@
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts},
+    http::request::Parts,
+};
+use chrono::{DateTime, Local};
+use leprecon::utils::validate::{Validate, ValidationError};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{
+    convert::Infallible,
+    fmt,
+    net::{IpAddr, SocketAddr},
+};
+use tokio_postgres::Row;
+
+use crate::TRUSTED_PROXIES;
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum AuditAction {
+    UserCreated,
+    CustomerDetailsCreated,
+    CustomerDetailsUpdated,
+    AccountDeleted,
+    VerificationEmailSent,
+    EmailVerificationChanged,
+}
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Origin of the request, the ip is taken from `x-forwarded-for` only when a trusted proxy sent it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AuditContext {
+    pub request_id: Option<String>,
+    pub ip: Option<String>,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuditContext
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let header =
+            |name: &str| -> Option<&str> { parts.headers.get(name).and_then(|v| v.to_str().ok()) };
+
+        let request_id: Option<String> = header("x-request-id").map(str::to_owned);
+
+        let peer: Option<IpAddr> = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|v| v.0.ip());
+
+        let ip: Option<String> = client_ip(
+            peer,
+            header("x-forwarded-for"),
+            TRUSTED_PROXIES.get().unwrap(),
+        )
+        .map(|v| v.to_string());
+
+        Ok(AuditContext { request_id, ip })
+    }
+}
+
+/// Walks back from the peer through the trusted proxies, the first other address is the client.
+///
+/// Anyone can send `x-forwarded-for`, so only the addresses the trusted proxies appended count.
+fn client_ip(peer: Option<IpAddr>, forwarded: Option<&str>, trusted: &[IpAddr]) -> Option<IpAddr> {
+    let mut ip: IpAddr = peer?;
+    let mut hops = forwarded
+        .unwrap_or_default()
+        .rsplit(',')
+        .map(|v| v.trim().parse::<IpAddr>());
+
+    while trusted.contains(&ip) {
+        match hops.next() {
+            Some(Ok(v)) => ip = v,
+            _ => break,
+        }
+    }
+
+    Some(ip)
+}
+
+/// A change made to the account of `sub`, by `actor`.
+pub(crate) struct AuditEntry<'a> {
+    pub sub: &'a str,
+    pub actor: &'a str,
+    pub action: AuditAction,
+    pub diff: Option<Value>,
+    pub context: &'a AuditContext,
+}
+
+pub(super) struct AuditRecord {
+    pub sub: String,
+    pub actor: String,
+    pub action: String,
+    pub diff: Option<Value>,
+    pub request_id: Option<String>,
+    pub ip: Option<String>,
+    pub created: DateTime<Local>,
+}
+
+impl From<Row> for AuditRecord {
+    fn from(r: Row) -> Self {
+        AuditRecord {
+            sub: r.get("sub"),
+            actor: r.get("actor"),
+            action: r.get("action"),
+            diff: r.get("diff"),
+            request_id: r.get("request_id"),
+            ip: r.get("ip"),
+            created: r.get("created"),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct AuditQuery {
+    #[serde(default)]
+    pub sub: String,
+    pub from: Option<DateTime<Local>>,
+    pub to: Option<DateTime<Local>>,
+}
+
+impl Validate for AuditQuery {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.sub.is_empty() {
+            return Err(ValidationError::new("sub", "is required"));
+        }
+
+        if let (Some(from), Some(to)) = (self.from, self.to) {
+            if from > to {
+                return Err(ValidationError::new("from", "must be before to"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::client_ip;
+    use std::net::IpAddr;
+
+    fn ip(v: &str) -> IpAddr {
+        v.parse().unwrap()
+    }
+
+    #[test]
+    fn test_client_ip_untrusted_peer() {
+        let trusted: Vec<IpAddr> = vec![ip("10.0.0.1")];
+
+        assert_eq!(
+            client_ip(Some(ip("198.51.100.1")), Some("203.0.113.7"), &trusted),
+            Some(ip("198.51.100.1"))
+        );
+    }
+
+    #[test]
+    fn test_client_ip_trusted_peer() {
+        let trusted: Vec<IpAddr> = vec![ip("10.0.0.1"), ip("10.0.0.2")];
+
+        assert_eq!(
+            client_ip(Some(ip("10.0.0.1")), Some("203.0.113.7"), &trusted),
+            Some(ip("203.0.113.7"))
+        );
+        assert_eq!(
+            client_ip(
+                Some(ip("10.0.0.1")),
+                Some("192.0.2.1, 203.0.113.7, 10.0.0.2"),
+                &trusted
+            ),
+            Some(ip("203.0.113.7"))
+        );
+    }
+
+    #[test]
+    fn test_client_ip_malformed_header() {
+        let trusted: Vec<IpAddr> = vec![ip("10.0.0.1")];
+
+        assert_eq!(
+            client_ip(Some(ip("10.0.0.1")), Some("unknown"), &trusted),
+            Some(ip("10.0.0.1"))
+        );
+        assert_eq!(
+            client_ip(Some(ip("10.0.0.1")), None, &trusted),
+            Some(ip("10.0.0.1"))
+        );
+    }
+
+    #[test]
+    fn test_client_ip_without_peer() {
+        assert_eq!(client_ip(None, Some("203.0.113.7"), &[]), None);
+    }
+}
@@ -0,0 +1,203 @@
+pub(crate) mod db;
+
+use self::db::{get_balance, BALANCE_CHANNEL};
+
+use crate::{StateParams, VALKEY_CONN};
+
+use askama::Template;
+use axum::{
+    extract::State,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Html, IntoResponse, Response,
+    },
+    Form,
+};
+use bb8_postgres::{bb8::Pool, PostgresConnectionManager};
+use futures::{stream, Stream, StreamExt};
+use leprecon::{
+    auth::AuthParam,
+    template::{self, Snackbar},
+    utils::PostgresConn,
+};
+use redis::aio::PubSub;
+use reqwest::StatusCode;
+use std::{convert::Infallible, time::Duration};
+use tokio::sync::broadcast::{self, error::RecvError, Receiver, Sender};
+use tokio_postgres::NoTls;
+use tracing::{error, warn};
+
+/// Streams the `#balance` fragment of the user as `balance` events, whenever the balance changes.
+///
+/// Compatible with the htmx sse extension, through `sse-swap="balance"`.
+pub(super) async fn balance_events(
+    State(state): State<StateParams>,
+    Form(auth_param): Form<AuthParam>,
+) -> Response {
+    let snackbar: Snackbar<'_> = Snackbar::default();
+
+    if auth_param.sub.is_empty() {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Html(snackbar.render().unwrap()),
+        )
+            .into_response();
+    };
+
+    let events = balance_stream(auth_param.sub, state.2.clone(), state.6.subscribe());
+
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// Current balance first, then a new one for every update of the user.
+fn balance_stream(
+    sub: String,
+    postgres_pool: Pool<PostgresConnectionManager<NoTls>>,
+    receiver: Receiver<String>,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    stream::unfold(
+        (sub, postgres_pool, receiver, true),
+        |(sub, postgres_pool, mut receiver, first)| async move {
+            if !first {
+                loop {
+                    match receiver.recv().await {
+                        Ok(v) if v == sub => break,
+                        Ok(_) => continue,
+                        // Missed updates might include ours
+                        Err(RecvError::Lagged(_)) => break,
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            }
+
+            let event: Event = match render_balance(&sub, &postgres_pool).await {
+                Some(v) => Event::default().event("balance").data(v),
+                None => Event::default().comment("balance unavailable"),
+            };
+
+            Some((Ok(event), (sub, postgres_pool, receiver, false)))
+        },
+    )
+}
+
+async fn render_balance(
+    sub: &str,
+    postgres_pool: &Pool<PostgresConnectionManager<NoTls>>,
+) -> Option<String> {
+    let postgres_conn: PostgresConn = match postgres_pool.get().await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Cannot get connection from pool: {:?}", e);
+            return None;
+        }
+    };
+
+    let (amount, currency): (f64, String) = match get_balance(sub, &postgres_conn).await {
+        Ok(Some(v)) => v,
+        Ok(None) => return None,
+        Err(e) => {
+            error!("Could not fetch balance: {:?}", e);
+            return None;
+        }
+    };
+
+    let balance: template::Balance<'_> = template::Balance {
+        amount: &amount.to_string(),
+        currency: &currency,
+    };
+
+    Some(balance.render().unwrap())
+}
+
+/// Forwards the balance updates published by any replica to the local event streams.
+pub(super) async fn subscribe_balance_updates(sender: Sender<String>) {
+    loop {
+        if let Err(e) = forward_balance_updates(&sender).await {
+            warn!("Lost balance update subscription, reconnecting: {:?}", e);
+        }
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+async fn forward_balance_updates(sender: &Sender<String>) -> redis::RedisResult<()> {
+    let client: redis::Client = redis::Client::open(VALKEY_CONN.get().unwrap().as_str())?;
+    let mut pubsub: PubSub = client.get_async_pubsub().await?;
+    pubsub.subscribe(BALANCE_CHANNEL).await?;
+
+    let mut messages = pubsub.on_message();
+    while let Some(msg) = messages.next().await {
+        match msg.get_payload::<String>() {
+            // No receivers means no open streams on this replica
+            Ok(sub) => {
+                let _ = sender.send(sub);
+            }
+            Err(e) => error!("Skipping malformed balance update: {:?}", e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Channel between the subscription and the event streams.
+pub(super) fn balance_channel() -> Sender<String> {
+    broadcast::channel(1024).0
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{body::Body, http::Request};
+    use futures::StreamExt;
+    use reqwest::{header, StatusCode};
+    use tower::ServiceExt;
+
+    use crate::fixture::{assert_body_contains, initialize, seed_database};
+
+    #[tokio::test]
+    async fn test_no_params_provided_balance_events() {
+        let app: axum::Router = initialize().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/user/balance/events")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_body_contains(response, &["Could not process request"]).await;
+    }
+
+    #[tokio::test]
+    async fn test_balance_events_start_with_balance() {
+        let app: axum::Router = initialize().await;
+        seed_database().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/user/balance/events?sub=auth0|0002")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/event-stream"
+        );
+
+        let first = response.into_body().into_data_stream().next().await;
+        let event: String = String::from_utf8(first.unwrap().unwrap().to_vec()).unwrap();
+
+        assert!(event.contains("event: balance"));
+        assert!(event.contains("id=\"balance\""));
+    }
+}
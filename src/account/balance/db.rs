@@ -0,0 +1,25 @@
+use leprecon::utils::{PostgresConn, RedisConn};
+use redis::{AsyncCommands, RedisResult};
+use tokio_postgres::Row;
+
+/// Valkey channel on which the subs of users whose balance changed are published.
+pub(super) const BALANCE_CHANNEL: &str = "balance_updates";
+
+/// Balance and currency of the user, `None` for unknown users.
+pub(super) async fn get_balance(
+    sub: &str,
+    db_client: &PostgresConn<'_>,
+) -> Result<Option<(f64, String)>, tokio_postgres::Error> {
+    let r: Option<Row> = db_client
+        .query_opt(
+            "SELECT balance, acronym FROM users INNER JOIN currencies ON currencies.id = users.currency_id WHERE sub = $1 AND deleted IS NULL",
+            &[&sub],
+        )
+        .await?;
+
+    Ok(r.map(|r| (r.get("balance"), r.get("acronym"))))
+}
+
+pub(crate) async fn publish_balance_update(sub: &str, conn: &mut RedisConn<'_>) -> RedisResult<()> {
+    conn.publish(BALANCE_CHANNEL, sub).await
+}
@@ -0,0 +1,154 @@
+mod db;
+mod model;
+mod request;
+
+pub(crate) use db::create_deletion_job;
+
+use self::{
+    db::{
+        claim_due_deletion_jobs, delete_deletion_job, get_deletion_job, mark_provider_deleted,
+        reschedule_deletion_job,
+    },
+    model::DeletionJob,
+    request::delete_user_from_auth_provider,
+};
+
+use crate::{
+    notification::db::delete_notifications, StateParams, AUTH_HOST, CLIENT_ID, CLIENT_SECRET,
+};
+
+use leprecon::{
+    auth::get_valid_jwt,
+    broker::AccountDeleted,
+    utils::{PostgresConn, RedisConn},
+};
+use rabbitmq_stream_client::types::Message;
+use reqwest::StatusCode;
+use std::time::Duration;
+use tracing::{error, info};
+
+/// Retries the deletion jobs which are due, until the service shuts down.
+pub(super) async fn run_deletion_jobs(state: StateParams) {
+    let mut interval: tokio::time::Interval = tokio::time::interval(Duration::from_secs(30));
+
+    loop {
+        interval.tick().await;
+
+        let jobs: Vec<DeletionJob> = match state.2.get().await {
+            Ok(conn) => match claim_due_deletion_jobs(&conn).await {
+                Ok(v) => v,
+                Err(e) => {
+                    error!("Could not claim deletion jobs: {:?}", e);
+                    continue;
+                }
+            },
+            Err(e) => {
+                error!("Cannot get connection from pool: {:?}", e);
+                continue;
+            }
+        };
+
+        for job in jobs {
+            if let Err(e) = process_deletion_job(&state, job.id).await {
+                error!("Deletion job {} failed: {}", job.id, e);
+            }
+        }
+    }
+}
+
+/// Deletes the user at the auth provider and announces the deletion.
+///
+/// On failure the job is rescheduled, and the error is returned.
+pub(crate) async fn process_deletion_job(state: &StateParams, id: i32) -> Result<(), String> {
+    let postgres_conn: PostgresConn = state.2.get().await.map_err(|e| e.to_string())?;
+
+    let job: DeletionJob = get_deletion_job(id, &postgres_conn)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Err(e) = run_job(state, &job, &postgres_conn).await {
+        if let Err(e) = reschedule_deletion_job(&job, &e, &postgres_conn).await {
+            error!("Could not reschedule deletion job: {:?}", e);
+        }
+
+        return Err(e);
+    }
+
+    info!("Deleted account: {}", job.sub);
+
+    Ok(())
+}
+
+async fn run_job(
+    state: &StateParams,
+    job: &DeletionJob,
+    postgres_conn: &PostgresConn<'_>,
+) -> Result<(), String> {
+    if !job.provider_deleted {
+        delete_from_auth_provider(state, &job.sub).await?;
+        mark_provider_deleted(job.id, postgres_conn)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    let mut redis_conn: RedisConn = state.3.get().await.map_err(|e| e.to_string())?;
+    delete_notifications(&job.sub, &mut redis_conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    drop(redis_conn);
+
+    let event: AccountDeleted = AccountDeleted {
+        sub: job.sub.clone(),
+    };
+
+    state
+        .5
+        .send_with_confirm(
+            Message::builder()
+                .body(serde_json::to_string(&event).unwrap())
+                .build(),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    delete_deletion_job(job.id, postgres_conn)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+async fn delete_from_auth_provider(state: &StateParams, sub: &str) -> Result<(), String> {
+    let mut lock: tokio::sync::MutexGuard<'_, leprecon::auth::JWT> = state.0.lock().await;
+    let req_client: &reqwest::Client = &state.1;
+
+    let redis_conn: RedisConn = state.3.get().await.map_err(|e| e.to_string())?;
+
+    *lock = match get_valid_jwt(
+        redis_conn,
+        req_client,
+        AUTH_HOST.get().unwrap(),
+        CLIENT_ID.get().unwrap(),
+        CLIENT_SECRET.get().unwrap(),
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(e) => return Err(format!("Could not get valid jwt: {e}")),
+    };
+
+    let res: reqwest::Response = delete_user_from_auth_provider(
+        sub,
+        req_client,
+        AUTH_HOST.get().unwrap(),
+        &lock.access_token,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Not found means an earlier attempt already deleted the user
+    match res.status() {
+        StatusCode::NO_CONTENT | StatusCode::NOT_FOUND => Ok(()),
+        v => Err(format!("Auth provider responded with {v}")),
+    }
+}
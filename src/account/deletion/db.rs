@@ -0,0 +1,84 @@
+use super::model::DeletionJob;
+
+use chrono::{Duration, Local};
+use leprecon::utils::PostgresConn;
+use tokio_postgres::{Row, Transaction};
+
+/// Time a job is reserved for the replica that picked it up.
+const LEASE_MINUTES: i64 = 5;
+
+pub(crate) async fn create_deletion_job(
+    sub: &str,
+    transaction: &Transaction<'_>,
+) -> Result<i32, tokio_postgres::Error> {
+    let r: Row = transaction
+        .query_one(
+            "INSERT INTO deletion_jobs(sub, next_attempt, created) VALUES($1, $2, now()) RETURNING id",
+            &[&sub, &(Local::now() + Duration::minutes(LEASE_MINUTES))],
+        )
+        .await?;
+
+    Ok(r.get("id"))
+}
+
+pub(super) async fn get_deletion_job(
+    id: i32,
+    db_client: &PostgresConn<'_>,
+) -> Result<DeletionJob, tokio_postgres::Error> {
+    let r: Row = db_client
+        .query_one("SELECT * FROM deletion_jobs WHERE id = $1", &[&id])
+        .await?;
+
+    Ok(DeletionJob::from(r))
+}
+
+/// Reserves the jobs which are due, so other replicas skip them.
+pub(super) async fn claim_due_deletion_jobs(
+    db_client: &PostgresConn<'_>,
+) -> Result<Vec<DeletionJob>, tokio_postgres::Error> {
+    let rows: Vec<Row> = db_client
+        .query(
+            "UPDATE deletion_jobs SET next_attempt = $1 WHERE id IN (SELECT id FROM deletion_jobs WHERE next_attempt <= now() ORDER BY next_attempt LIMIT 10 FOR UPDATE SKIP LOCKED) RETURNING *",
+            &[&(Local::now() + Duration::minutes(LEASE_MINUTES))],
+        )
+        .await?;
+
+    Ok(rows.into_iter().map(DeletionJob::from).collect())
+}
+
+pub(super) async fn mark_provider_deleted(
+    id: i32,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    db_client
+        .execute(
+            "UPDATE deletion_jobs SET provider_deleted = true WHERE id = $1",
+            &[&id],
+        )
+        .await
+}
+
+/// Retries the job later, backing off exponentially up to a day.
+pub(super) async fn reschedule_deletion_job(
+    job: &DeletionJob,
+    error: &str,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    let backoff: i64 = 2_i64.saturating_pow(job.attempts as u32).min(24 * 60);
+
+    db_client
+        .execute(
+            "UPDATE deletion_jobs SET attempts = attempts + 1, last_error = $2, next_attempt = $3 WHERE id = $1",
+            &[&job.id, &error, &(Local::now() + Duration::minutes(backoff))],
+        )
+        .await
+}
+
+pub(super) async fn delete_deletion_job(
+    id: i32,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    db_client
+        .execute("DELETE FROM deletion_jobs WHERE id = $1", &[&id])
+        .await
+}
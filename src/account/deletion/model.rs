@@ -0,0 +1,19 @@
+use tokio_postgres::Row;
+
+pub(super) struct DeletionJob {
+    pub id: i32,
+    pub sub: String,
+    pub provider_deleted: bool,
+    pub attempts: i32,
+}
+
+impl From<Row> for DeletionJob {
+    fn from(r: Row) -> Self {
+        DeletionJob {
+            id: r.get("id"),
+            sub: r.get("sub"),
+            provider_deleted: r.get("provider_deleted"),
+            attempts: r.get("attempts"),
+        }
+    }
+}
@@ -4,25 +4,45 @@ mod request;
 pub(crate) mod db;
 
 use self::{
-    db::{create_verification_session, verification_already_send},
-    model::EmailParams,
-    request::send_email_verification,
+    db::{
+        cache_email_verified, create_verification_session, delete_expired_sessions,
+        get_cached_email_verified, get_email_verified, get_verification_sends, set_email_verified,
+    },
+    model::{
+        EmailParams, EmailVerifiedCallback, ProviderEmail, ProviderUser, ResendStatus,
+        VerificationTicket,
+    },
+    request::{create_verification_ticket, get_user_from_auth_provider},
 };
 
-use crate::{StateParams, AUTH_HOST, CLIENT_ID, CLIENT_SECRET};
+use crate::{
+    audit::{db::insert_audit_entry, AuditAction, AuditContext, AuditEntry},
+    ledger::db::lock_user,
+    mail::{db::set_email, queue_user_mail},
+    notification::{notify, Notification},
+    StateParams, AUTH_CALLBACK_SECRET, AUTH_HOST, CLIENT_ID, CLIENT_SECRET,
+    EMAIL_RESEND_COOLDOWN_SECONDS, EMAIL_RESEND_DAILY_CAP, EMAIL_SESSION_EXPIRY_MINUTES,
+    EMAIL_VERIFIED_CACHE_SECONDS,
+};
 
 use askama::Template;
-use axum::{extract::State, response::Html, Form};
+use axum::{extract::State, http::HeaderMap, response::Html, Form, Json};
+use bb8_postgres::{bb8::Pool, PostgresConnectionManager};
+use chrono::{DateTime, Duration, Local};
 use leprecon::{
-    auth::get_valid_jwt,
-    template::Snackbar,
+    auth::{get_valid_jwt, valid_bearer, AuthParam},
+    i18n::{translate_with, FluentArgs},
+    template::{Snackbar, VerificationMail},
     utils::{extract::extract_conn_from_pool, PostgresConn, RedisConn},
 };
 use reqwest::StatusCode;
-use tracing::error;
+use std::error::Error;
+use tokio_postgres::{GenericClient, NoTls, Transaction};
+use tracing::{error, info};
 
 pub(super) async fn email_verification(
     State(state): State<StateParams>,
+    context: AuditContext,
     Form(params): Form<EmailParams>,
 ) -> (StatusCode, Html<String>) {
     let mut snackbar: Snackbar<'_> = Snackbar::default();
@@ -34,27 +54,40 @@ pub(super) async fn email_verification(
         );
     };
 
-    if params.email_verified.is_empty() {
-        return (
-            StatusCode::UNPROCESSABLE_ENTITY,
-            Html(snackbar.render().unwrap()),
-        );
-    };
+    let mut postgres_conn: PostgresConn =
+        match extract_conn_from_pool(&state.2, &mut snackbar).await {
+            Ok(v) => v,
+            Err(e) => return e,
+        };
 
-    // Already verified token
-    if params.email_verified == "true" {
-        snackbar.message = "Already verified email";
-        return (StatusCode::BAD_REQUEST, Html(snackbar.render().unwrap()));
+    match get_email_verified(&params.sub, &postgres_conn).await {
+        Ok(Some(true)) => {
+            snackbar.message = "email-already-verified";
+            return (StatusCode::BAD_REQUEST, Html(snackbar.render().unwrap()));
+        }
+        Ok(_) => (),
+        Err(e) => {
+            error!("Could not get email verification status: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
     }
 
-    let postgres_conn: PostgresConn = match extract_conn_from_pool(&state.2, &mut snackbar).await {
+    let status: ResendStatus = match resend_status(&params.sub, &*postgres_conn).await {
         Ok(v) => v,
-        Err(e) => return e,
+        Err(e) => {
+            error!("Could not get verification sends: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
     };
 
-    if verification_already_send(&postgres_conn, &params.sub).await {
-        snackbar.message = "Already send email";
-        return (StatusCode::BAD_REQUEST, Html(snackbar.render().unwrap()));
+    if let (false, Some(next_resend)) = (status.allowed, status.next_resend) {
+        return resend_refused(next_resend);
     };
 
     let mut lock: tokio::sync::MutexGuard<'_, leprecon::auth::JWT> = state.0.lock().await;
@@ -65,7 +98,7 @@ pub(super) async fn email_verification(
         Err(e) => return e,
     };
 
-    snackbar.message = "Could not process request";
+    snackbar.message = "could-not-process-request";
 
     *lock = match get_valid_jwt(
         redis_conn,
@@ -86,43 +119,351 @@ pub(super) async fn email_verification(
         }
     };
 
-    // Send verification email
-    let response: reqwest::Response = match send_email_verification(
-        req_client,
+    let mut redis_conn: RedisConn = match extract_conn_from_pool(&state.3, &mut snackbar).await {
+        Ok(v) => v,
+        Err(e) => return e,
+    };
+
+    // The local flag may be behind, the auth provider decides
+    let email_verified: bool =
+        match provider_email_verified(&params.sub, req_client, &lock.access_token, &mut redis_conn)
+            .await
+        {
+            Ok(v) => v,
+            Err(e) => {
+                error!(
+                    "Could not get email verification status from auth provider: {:?}",
+                    e
+                );
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Html(snackbar.render().unwrap()),
+                );
+            }
+        };
+
+    if email_verified {
+        if let Err(e) = set_email_verified(&params.sub, true, &postgres_conn).await {
+            error!("Could not update email verification status: {:?}", e);
+        }
+
+        snackbar.message = "email-already-verified";
+        return (StatusCode::BAD_REQUEST, Html(snackbar.render().unwrap()));
+    }
+
+    // Send verification email ourselves, the auth provider only hands out the link
+    let expires: DateTime<Local> =
+        Local::now() + Duration::minutes(*EMAIL_SESSION_EXPIRY_MINUTES.get().unwrap());
+
+    let mail: VerificationMail = match verification_mail(
         &params.sub,
-        CLIENT_ID.get().unwrap(),
-        AUTH_HOST.get().unwrap(),
+        req_client,
         &lock.access_token,
+        expires,
+        &postgres_conn,
     )
     .await
     {
         Ok(v) => v,
         Err(e) => {
-            error!("Cannot process verification email request: {:?}", e);
+            error!("Cannot create verification email: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    let entry: AuditEntry = AuditEntry {
+        sub: &params.sub,
+        actor: &params.sub,
+        action: AuditAction::VerificationEmailSent,
+        diff: None,
+        context: &context,
+    };
+
+    // Concurrent requests wait for the lock, and see the session of the first one
+    match send_verification_mail(&params.sub, &mail, &entry, &mut postgres_conn).await {
+        Ok(None) => (),
+        Ok(Some(next_resend)) => return resend_refused(next_resend),
+        Err(e) => {
+            error!("Cannot queue verification email: {:?}", e);
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Html(snackbar.render().unwrap()),
             );
         }
+    }
+
+    snackbar.title = "success";
+    snackbar.message = "email-sent";
+    snackbar.color = "green";
+
+    (StatusCode::OK, Html(snackbar.render().unwrap()))
+}
+
+/// Keeps the local verification status in sync, called by the auth provider.
+pub(super) async fn email_verified_callback(
+    State(state): State<StateParams>,
+    context: AuditContext,
+    headers: HeaderMap,
+    Json(callback): Json<EmailVerifiedCallback>,
+) -> StatusCode {
+    if !valid_bearer(&headers, AUTH_CALLBACK_SECRET.get().unwrap()) {
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    let postgres_conn: PostgresConn = match state.2.get().await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Cannot get connection from pool: {:?}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR;
+        }
+    };
+
+    match set_email_verified(&callback.sub, callback.email_verified, &postgres_conn).await {
+        Ok(0) => return StatusCode::NOT_FOUND,
+        Ok(_) => (),
+        Err(e) => {
+            error!("Could not update email verification status: {:?}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR;
+        }
+    }
+
+    match state.3.get().await {
+        Ok(mut v) => {
+            if let Err(e) = cache_email_verified(
+                &callback.sub,
+                callback.email_verified,
+                *EMAIL_VERIFIED_CACHE_SECONDS.get().unwrap(),
+                &mut v,
+            )
+            .await
+            {
+                error!("Could not cache email verification status: {:?}", e);
+            }
+        }
+        Err(e) => error!("Cannot get connection from pool: {:?}", e),
+    }
+
+    if callback.email_verified {
+        notify(
+            &callback.sub,
+            Notification::new("email-verified-title", "email-verified"),
+            &state.3,
+        )
+        .await;
+    }
+
+    let entry: AuditEntry = AuditEntry {
+        sub: &callback.sub,
+        actor: "auth_provider",
+        action: AuditAction::EmailVerificationChanged,
+        diff: Some(serde_json::json!({ "email_verified": callback.email_verified })),
+        context: &context,
     };
 
-    if response.status() != StatusCode::CREATED {
-        error!("Verification email not send");
+    if let Err(e) = insert_audit_entry(&entry, &*postgres_conn).await {
+        error!("Could not write audit log: {:?}", e);
+    }
+
+    StatusCode::NO_CONTENT
+}
+
+/// Tells when the next verification email can be sent.
+pub(super) async fn email_verification_status(
+    State(state): State<StateParams>,
+    Form(auth_param): Form<AuthParam>,
+) -> (StatusCode, Json<ResendStatus>) {
+    if auth_param.sub.is_empty() {
         return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Html(snackbar.render().unwrap()),
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ResendStatus::default()),
         );
     }
 
-    if let Err(e) = create_verification_session(&postgres_conn, &params.sub).await {
-        error!("Cannot create verification session: {:?}", e)
+    let postgres_conn: PostgresConn = match state.2.get().await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Cannot get connection from pool: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ResendStatus::default()),
+            );
+        }
+    };
+
+    match resend_status(&auth_param.sub, &*postgres_conn).await {
+        Ok(v) => (StatusCode::OK, Json(v)),
+        Err(e) => {
+            error!("Could not get verification sends: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ResendStatus::default()),
+            )
+        }
     }
+}
 
-    snackbar.title = "Succes";
-    snackbar.message = "Succesfully send email";
-    snackbar.color = "green";
+/// Deletes expired verification sessions, until the service shuts down.
+pub(super) async fn sweep_expired_sessions(postgres_pool: Pool<PostgresConnectionManager<NoTls>>) {
+    let mut interval: tokio::time::Interval =
+        tokio::time::interval(std::time::Duration::from_secs(600));
+
+    loop {
+        interval.tick().await;
+
+        let postgres_conn: PostgresConn = match postgres_pool.get().await {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Cannot get connection from pool: {:?}", e);
+                continue;
+            }
+        };
+
+        match delete_expired_sessions(&postgres_conn).await {
+            Ok(0) => (),
+            Ok(v) => info!("Deleted {} expired sessions", v),
+            Err(e) => error!("Could not delete expired sessions: {:?}", e),
+        }
+    }
+}
 
-    (StatusCode::OK, Html(snackbar.render().unwrap()))
+/// Verification link from the auth provider, the email address is stored for later mails.
+async fn verification_mail(
+    sub: &str,
+    req_client: &reqwest::Client,
+    access_token: &str,
+    expires: DateTime<Local>,
+    postgres_conn: &PostgresConn<'_>,
+) -> Result<VerificationMail, Box<dyn Error + Send + Sync>> {
+    let user: ProviderEmail = get_user_from_auth_provider(
+        req_client,
+        sub,
+        AUTH_HOST.get().unwrap(),
+        access_token,
+        "email",
+    )
+    .await?
+    .error_for_status()?
+    .json()
+    .await?;
+
+    set_email(sub, &user.email, postgres_conn).await?;
+
+    let ticket: VerificationTicket = create_verification_ticket(
+        req_client,
+        sub,
+        CLIENT_ID.get().unwrap(),
+        AUTH_HOST.get().unwrap(),
+        access_token,
+        (expires - Local::now()).num_seconds(),
+    )
+    .await?
+    .error_for_status()?
+    .json()
+    .await?;
+
+    Ok(VerificationMail {
+        link: ticket.ticket,
+        expires: expires.format("%Y-%m-%d %H:%M").to_string(),
+    })
+}
+
+/// Verification status at the auth provider, cached for a short while.
+async fn provider_email_verified(
+    sub: &str,
+    req_client: &reqwest::Client,
+    access_token: &str,
+    redis_conn: &mut RedisConn<'_>,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    match get_cached_email_verified(sub, redis_conn).await {
+        Ok(Some(v)) => return Ok(v),
+        Ok(None) => (),
+        Err(e) => error!("Could not get cached email verification status: {:?}", e),
+    }
+
+    let response: reqwest::Response = get_user_from_auth_provider(
+        req_client,
+        sub,
+        AUTH_HOST.get().unwrap(),
+        access_token,
+        "email_verified",
+    )
+    .await?
+    .error_for_status()?;
+    let user: ProviderUser = response.json().await?;
+
+    if let Err(e) = cache_email_verified(
+        sub,
+        user.email_verified,
+        *EMAIL_VERIFIED_CACHE_SECONDS.get().unwrap(),
+        redis_conn,
+    )
+    .await
+    {
+        error!("Could not cache email verification status: {:?}", e);
+    }
+
+    Ok(user.email_verified)
+}
+
+/// Checks the resend limits with the user locked, and queues the mail with its session and audit
+/// entry. Returns the time of the next resend when another request sent a mail first.
+async fn send_verification_mail(
+    sub: &str,
+    mail: &VerificationMail,
+    entry: &AuditEntry<'_>,
+    postgres_conn: &mut PostgresConn<'_>,
+) -> Result<Option<DateTime<Local>>, Box<dyn Error + Send + Sync>> {
+    let transaction: Transaction = postgres_conn.transaction().await?;
+
+    if !lock_user(sub, &transaction).await? {
+        return Err("User not found".into());
+    }
+
+    let status: ResendStatus = resend_status(sub, &transaction).await?;
+    if let (false, Some(next_resend)) = (status.allowed, status.next_resend) {
+        return Ok(Some(next_resend));
+    }
+
+    create_verification_session(sub, &transaction).await?;
+    queue_user_mail(sub, mail, &transaction).await?;
+    insert_audit_entry(entry, &transaction).await?;
+
+    transaction.commit().await?;
+
+    Ok(None)
+}
+
+/// Tells when the resend limits allow the next mail.
+fn resend_refused(next_resend: DateTime<Local>) -> (StatusCode, Html<String>) {
+    let mut args: FluentArgs = FluentArgs::new();
+    args.set("time", next_resend.format("%Y-%m-%d %H:%M").to_string());
+    let message: String = translate_with("email-resend-after", &args);
+    let snackbar: Snackbar<'_> = Snackbar {
+        message: &message,
+        ..Default::default()
+    };
+
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        Html(snackbar.render().unwrap()),
+    )
+}
+
+async fn resend_status<C: GenericClient>(
+    sub: &str,
+    db_client: &C,
+) -> Result<ResendStatus, tokio_postgres::Error> {
+    let sends: Vec<DateTime<Local>> = get_verification_sends(sub, db_client).await?;
+
+    Ok(ResendStatus::new(
+        &sends,
+        Duration::seconds(*EMAIL_RESEND_COOLDOWN_SECONDS.get().unwrap()),
+        *EMAIL_RESEND_DAILY_CAP.get().unwrap(),
+        Local::now(),
+    ))
 }
 
 #[cfg(test)]
@@ -158,7 +499,8 @@ mod test {
     #[tokio::test]
     async fn test_already_verified() {
         let app: axum::Router = initialize().await;
-        let params: String = format!("sub=123&email_verified=true");
+        seed_database().await;
+        let params: String = format!("sub=auth0|0006");
 
         let response: axum::http::Response<Body> = app
             .oneshot(
@@ -180,7 +522,7 @@ mod test {
     async fn test_already_send_verification_email() {
         let app = initialize().await;
         seed_database().await;
-        let params: String = format!("sub=auth0|0000&email_verified=false");
+        let params: String = format!("sub=auth0|0000");
 
         let response: axum::http::Response<Body> = app
             .oneshot(
@@ -194,14 +536,14 @@ mod test {
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
-        assert_body_contains(response, &["Already send email"]).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_body_contains(response, &["Already send email, try again after"]).await;
     }
 
     #[tokio::test]
     async fn test_send_email_invalid_sub() {
         let app: axum::Router = initialize().await;
-        let params: String = String::from("sub=123&email_verified=false");
+        let params: String = String::from("sub=123");
 
         let response: axum::http::Response<Body> = app
             .oneshot(
@@ -225,7 +567,7 @@ mod test {
         seed_database().await;
 
         let sub: String = env::var("SUB_NOT_VERIFIED").unwrap();
-        let params: String = format!("sub={sub}&email_verified=false");
+        let params: String = format!("sub={sub}");
 
         let response = app
             .oneshot(
@@ -242,4 +584,62 @@ mod test {
         assert_eq!(response.status(), StatusCode::OK);
         assert_body_contains(response, &["Succesfully send email"]).await;
     }
+
+    // Verification status
+    #[tokio::test]
+    async fn test_no_params_provided_verification_status() {
+        let app: axum::Router = initialize().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/email/verification")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_verification_status_after_send() {
+        let app: axum::Router = initialize().await;
+        seed_database().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/email/verification?sub=auth0|0000")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_body_contains(response, &["\"allowed\":false", "\"next_resend\":\""]).await;
+    }
+
+    // Verified callback
+    #[tokio::test]
+    async fn test_verified_callback_invalid_secret() {
+        let app: axum::Router = initialize().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/account/email/verified")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .header(header::AUTHORIZATION, "Bearer invalid")
+                    .body(Body::from(r#"{"sub":"auth0|0000","email_verified":true}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
 }
@@ -1,45 +1,106 @@
-use chrono::{Duration, Local};
-use leprecon::utils::PostgresConn;
-use tokio_postgres::Row;
-use tracing::debug;
+use crate::{model::SessionType, EMAIL_SESSION_EXPIRY_MINUTES};
 
-use crate::model::SessionType;
+use chrono::{DateTime, Duration, Local};
+use leprecon::utils::{PostgresConn, RedisConn};
+use redis::{AsyncCommands, RedisResult};
+use tokio_postgres::{GenericClient, Row, Transaction};
 
-pub(super) async fn verification_already_send(db_client: &PostgresConn<'_>, sub: &str) -> bool {
-    match db_client
-        .query_one(
-            "SELECT * FROM sessions INNER JOIN users ON users.id = sessions.user_id WHERE expires > now() AND sub=$1 AND type=$2 ORDER BY expires DESC LIMIT 1",
-            &[&sub, &SessionType::Verification.to_string()],
+/// Locally known verification status, `None` for unknown users.
+pub(super) async fn get_email_verified(
+    sub: &str,
+    db_client: &PostgresConn<'_>,
+) -> Result<Option<bool>, tokio_postgres::Error> {
+    let r: Option<Row> = db_client
+        .query_opt(
+            "SELECT email_verified FROM users WHERE sub = $1 AND deleted IS NULL",
+            &[&sub],
         )
+        .await?;
+
+    Ok(r.map(|r| r.get("email_verified")))
+}
+
+pub(super) async fn set_email_verified(
+    sub: &str,
+    email_verified: bool,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    db_client
+        .execute(
+            "UPDATE users SET email_verified = $2 WHERE sub = $1",
+            &[&sub, &email_verified],
+        )
+        .await
+}
+
+fn email_verified_key(sub: &str) -> String {
+    format!("email_verified:{sub}")
+}
+
+pub(super) async fn get_cached_email_verified(
+    sub: &str,
+    conn: &mut RedisConn<'_>,
+) -> RedisResult<Option<bool>> {
+    conn.get(email_verified_key(sub)).await
+}
+
+pub(super) async fn cache_email_verified(
+    sub: &str,
+    email_verified: bool,
+    expires: u64,
+    conn: &mut RedisConn<'_>,
+) -> RedisResult<()> {
+    conn.set_ex(email_verified_key(sub), email_verified, expires)
         .await
-    {
-        Err(e) => {
-            debug!("No email verification session in db: {:?}", e);
-            false
-        }
-        _ => true,
-    }
+}
+
+/// Creation times of the verification sessions of the last day, oldest first.
+pub(super) async fn get_verification_sends<C: GenericClient>(
+    sub: &str,
+    db_client: &C,
+) -> Result<Vec<DateTime<Local>>, tokio_postgres::Error> {
+    let rows: Vec<Row> = db_client
+        .query(
+            "SELECT sessions.created FROM sessions INNER JOIN users ON users.id = sessions.user_id WHERE sub = $1 AND type = $2 AND sessions.created > now() - interval '1 day' ORDER BY sessions.created",
+            &[&sub, &SessionType::Verification.to_string()],
+        )
+        .await?;
+
+    Ok(rows.into_iter().map(|r| r.get("created")).collect())
 }
 
 pub(super) async fn create_verification_session(
-    db_client: &PostgresConn<'_>,
     sub: &str,
-) -> Result<Vec<Row>, tokio_postgres::Error> {
-    let expires = 3600; // 60 minutes
+    transaction: &Transaction<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    let expires: DateTime<Local> =
+        Local::now() + Duration::minutes(*EMAIL_SESSION_EXPIRY_MINUTES.get().unwrap());
+
+    transaction
+        .execute(
+            "WITH userId AS (SELECT id FROM users WHERE sub = $1) INSERT INTO sessions(expires, type, created, user_id) VALUES($2, $3, now(), (SELECT id FROM userId))",
+            &[&sub, &expires, &SessionType::Verification.to_string()],
+        )
+        .await
+}
 
+/// Deletes expired sessions, those of the last day are kept to enforce the daily cap.
+pub(super) async fn delete_expired_sessions(
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
     db_client
-        .query(
-            "WITH userId AS (SELECT id FROM users WHERE sub = $1) INSERT INTO sessions(expires, type, user_id) VALUES($2, $3, (SELECT id FROM userId))",
-            &[&sub, &(Local::now() + Duration::seconds(expires)), &SessionType::Verification.to_string()],
+        .execute(
+            "DELETE FROM sessions WHERE expires < now() AND created < now() - interval '1 day'",
+            &[],
         )
         .await
 }
 
 pub(crate) async fn delete_email_sessions(
     sub: &str,
-    db_client: &PostgresConn<'_>,
+    transaction: &Transaction<'_>,
 ) -> Result<Option<Row>, tokio_postgres::Error> {
-    db_client.query_opt(
+    transaction.query_opt(
         "WITH userId AS (SELECT id FROM users WHERE sub = $1) DELETE FROM sessions WHERE user_id = (SELECT id FROM userId)",
         &[&sub],
     )
@@ -1,10 +1,123 @@
-use serde::Deserialize;
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Debug)]
 pub(crate) struct EmailParams {
     #[serde(default)]
     pub sub: String,
+}
 
-    #[serde(default)]
-    pub email_verified: String,
+/// User as returned by the auth provider, only the fields we ask for.
+#[derive(Deserialize, Debug)]
+pub(super) struct ProviderUser {
+    pub email_verified: bool,
+}
+
+/// Email address of the user at the auth provider.
+#[derive(Deserialize, Debug)]
+pub(super) struct ProviderEmail {
+    pub email: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub(super) struct VerificationTicket {
+    pub ticket: String,
+}
+
+/// Sent by the auth provider once the user has verified their email.
+#[derive(Deserialize, Debug)]
+pub(crate) struct EmailVerifiedCallback {
+    pub sub: String,
+    pub email_verified: bool,
+}
+
+/// When the next verification email may be sent.
+#[derive(Serialize, Debug, Default)]
+pub(crate) struct ResendStatus {
+    pub allowed: bool,
+    pub sent_today: usize,
+    pub daily_cap: usize,
+    pub next_resend: Option<DateTime<Local>>,
+}
+
+impl ResendStatus {
+    /// Each resend waits twice as long as the one before, starting at the cooldown.
+    ///
+    /// Once the cap is reached the next send is allowed when the oldest send of the last day
+    /// leaves the window. `sends` are the sends of the last day, oldest first.
+    pub(super) fn new(
+        sends: &[DateTime<Local>],
+        cooldown: Duration,
+        daily_cap: usize,
+        now: DateTime<Local>,
+    ) -> ResendStatus {
+        let daily_cap: usize = daily_cap.max(1);
+
+        let next_resend: Option<DateTime<Local>> = match sends.len() {
+            0 => None,
+            n if n >= daily_cap => Some(sends[n - daily_cap] + Duration::days(1)),
+            n => Some(sends[n - 1] + cooldown * 2_i32.pow((n as u32 - 1).min(16))),
+        };
+
+        ResendStatus {
+            allowed: next_resend.map_or(true, |v| v <= now),
+            sent_today: sends.len(),
+            daily_cap,
+            next_resend,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use chrono::TimeZone;
+
+    fn at(minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 1, 1, 12, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_first_send_allowed() {
+        let status: ResendStatus = ResendStatus::new(&[], Duration::minutes(1), 3, at(0));
+
+        assert!(status.allowed);
+        assert_eq!(status.sent_today, 0);
+        assert_eq!(status.next_resend, None);
+    }
+
+    #[test]
+    fn test_cooldown_doubles() {
+        let cooldown: Duration = Duration::minutes(1);
+
+        let status: ResendStatus = ResendStatus::new(&[at(0)], cooldown, 5, at(0));
+        assert_eq!(status.next_resend, Some(at(1)));
+
+        let status: ResendStatus = ResendStatus::new(&[at(0), at(1)], cooldown, 5, at(1));
+        assert_eq!(status.next_resend, Some(at(3)));
+
+        let status: ResendStatus = ResendStatus::new(&[at(0), at(1), at(3)], cooldown, 5, at(3));
+        assert_eq!(status.next_resend, Some(at(7)));
+    }
+
+    #[test]
+    fn test_daily_cap() {
+        let sends: [DateTime<Local>; 3] = [at(0), at(1), at(3)];
+
+        let status: ResendStatus = ResendStatus::new(&sends, Duration::minutes(1), 3, at(30));
+        assert!(!status.allowed);
+        assert_eq!(status.sent_today, 3);
+        assert_eq!(status.daily_cap, 3);
+        assert_eq!(status.next_resend, Some(at(0) + Duration::days(1)));
+    }
+
+    #[test]
+    fn test_allowed_from_next_resend() {
+        let cooldown: Duration = Duration::minutes(1);
+
+        assert!(!ResendStatus::new(&[at(0), at(1)], cooldown, 5, at(2)).allowed);
+        assert!(ResendStatus::new(&[at(0), at(1)], cooldown, 5, at(3)).allowed);
+        assert!(ResendStatus::new(&[at(0), at(1)], cooldown, 5, at(4)).allowed);
+    }
 }
@@ -1,14 +1,15 @@
-use std::collections::HashMap;
-
 use axum::http::HeaderValue;
 use reqwest::Response;
+use serde_json::{json, Value};
 
-pub(super) async fn send_email_verification(
+/// Verification link for the user, which is mailed by us.
+pub(super) async fn create_verification_ticket(
     req_client: &reqwest::Client,
     sub: &str,
     client_id: &str,
     auth_host: &str,
     access_token: &str,
+    ttl_sec: i64,
 ) -> Result<Response, reqwest::Error> {
     // Set headers
     let mut headers = reqwest::header::HeaderMap::new();
@@ -17,12 +18,28 @@ pub(super) async fn send_email_verification(
     headers.insert("Accept", content_type);
 
     // Setup
-    let map: HashMap<&str, &str> = HashMap::from([("user_id", sub), ("client_id", client_id)]);
+    let body: Value = json!({ "user_id": sub, "client_id": client_id, "ttl_sec": ttl_sec });
 
     // Send request
     req_client
-        .post(format!("{auth_host}/api/v2/jobs/verification-email"))
-        .json(&map)
+        .post(format!("{auth_host}/api/v2/tickets/email-verification"))
+        .headers(headers)
+        .json(&body)
+        .bearer_auth(access_token)
+        .send()
+        .await
+}
+
+pub(super) async fn get_user_from_auth_provider(
+    req_client: &reqwest::Client,
+    sub: &str,
+    auth_host: &str,
+    access_token: &str,
+    fields: &str,
+) -> Result<Response, reqwest::Error> {
+    req_client
+        .get(format!("{auth_host}/api/v2/users/{sub}"))
+        .query(&[("fields", fields)])
         .bearer_auth(access_token)
         .send()
         .await
@@ -0,0 +1,411 @@
+mod db;
+mod model;
+
+use self::{
+    db::{
+        count_transactions, get_export, get_stored_export, get_stored_export_data,
+        mark_export_failed, store_export, store_pending_export,
+    },
+    model::{Export, ExportFormat, ExportParams, ExportStatus, StoredExport},
+};
+
+use crate::{
+    notification::{notify, user_notifications, Notification},
+    StateParams, EXPORT_ASYNC_TRANSACTIONS, EXPORT_EXPIRES_MINUTES,
+};
+
+use askama::Template;
+use axum::{
+    extract::{Path, State},
+    http::header,
+    response::{Html, IntoResponse, Response},
+    Form,
+};
+use bb8_postgres::{bb8::Pool, PostgresConnectionManager};
+use bb8_redis::RedisConnectionManager;
+use chrono::{Duration, Local};
+use leprecon::{
+    auth::AuthParam,
+    template::{self, Snackbar},
+    utils::{
+        extract::{extract_conn_from_pool, ValidForm},
+        PostgresConn, RedisConn,
+    },
+};
+use redis::RedisResult;
+use reqwest::StatusCode;
+use std::{error::Error, str::FromStr};
+use tokio::task;
+use tokio_postgres::NoTls;
+use tracing::{error, info};
+use uuid::Uuid;
+
+/// Exports all data of the user.
+///
+/// Small exports are returned right away, larger ones are generated in the background and
+/// can be downloaded from a link which expires.
+pub(super) async fn user_export(
+    State(state): State<StateParams>,
+    ValidForm(params): ValidForm<ExportParams>,
+) -> Response {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    let mut postgres_conn: PostgresConn =
+        match extract_conn_from_pool(&state.2, &mut snackbar).await {
+            Ok(v) => v,
+            Err(e) => return e.into_response(),
+        };
+
+    let transactions: i64 = match count_transactions(&params.sub, &postgres_conn).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Could not count transactions: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            )
+                .into_response();
+        }
+    };
+
+    let mut redis_conn: RedisConn = match extract_conn_from_pool(&state.3, &mut snackbar).await {
+        Ok(v) => v,
+        Err(e) => return e.into_response(),
+    };
+
+    if transactions <= *EXPORT_ASYNC_TRANSACTIONS.get().unwrap() {
+        let data: Vec<u8> = match build_export(
+            &params.sub,
+            params.format,
+            &mut postgres_conn,
+            &mut redis_conn,
+        )
+        .await
+        {
+            Ok(Some(v)) => v,
+            Ok(None) => {
+                snackbar.message = "user-missing";
+                return (StatusCode::NOT_FOUND, Html(snackbar.render().unwrap())).into_response();
+            }
+            Err(e) => {
+                error!("Could not build export: {:?}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Html(snackbar.render().unwrap()),
+                )
+                    .into_response();
+            }
+        };
+
+        return download(params.format, data);
+    }
+
+    drop(postgres_conn);
+
+    let token: String = Uuid::new_v4().to_string();
+
+    if let Err(e) = store_pending_export(
+        &token,
+        &params.sub,
+        params.format,
+        expires_in_seconds(),
+        &mut redis_conn,
+    )
+    .await
+    {
+        error!("Could not register export: {:?}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Html(snackbar.render().unwrap()),
+        )
+            .into_response();
+    }
+
+    drop(redis_conn);
+
+    task::spawn(generate_export(
+        state.2.clone(),
+        state.3.clone(),
+        params.sub,
+        params.format,
+        token.clone(),
+    ));
+
+    let export_template: template::Export = template::Export {
+        token,
+        expires: (Local::now() + Duration::minutes(*EXPORT_EXPIRES_MINUTES.get().unwrap()))
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+    };
+
+    (
+        StatusCode::ACCEPTED,
+        Html(export_template.render().unwrap()),
+    )
+        .into_response()
+}
+
+/// Downloads an export which was generated in the background.
+pub(super) async fn download_export(
+    State(state): State<StateParams>,
+    Path(token): Path<String>,
+    Form(auth_param): Form<AuthParam>,
+) -> Response {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    if auth_param.sub.is_empty() {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Html(snackbar.render().unwrap()),
+        )
+            .into_response();
+    };
+
+    let mut redis_conn: RedisConn = match extract_conn_from_pool(&state.3, &mut snackbar).await {
+        Ok(v) => v,
+        Err(e) => return e.into_response(),
+    };
+
+    let stored: StoredExport = match get_stored_export(&token, &mut redis_conn).await {
+        Ok(Some(v)) if v.sub == auth_param.sub => v,
+        Ok(_) => {
+            snackbar.message = "export-expired";
+            return (StatusCode::GONE, Html(snackbar.render().unwrap())).into_response();
+        }
+        Err(e) => {
+            error!("Could not get export: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            )
+                .into_response();
+        }
+    };
+
+    if stored.status == ExportStatus::Pending.to_string() {
+        snackbar.title = "success";
+        snackbar.message = "export-pending";
+        snackbar.color = "green";
+        return (StatusCode::ACCEPTED, Html(snackbar.render().unwrap())).into_response();
+    }
+
+    if stored.status == ExportStatus::Failed.to_string() {
+        snackbar.message = "export-failed";
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Html(snackbar.render().unwrap()),
+        )
+            .into_response();
+    }
+
+    let format: ExportFormat = match ExportFormat::from_str(&stored.format) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Stored export has an unknown format: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            )
+                .into_response();
+        }
+    };
+
+    match get_stored_export_data(&token, &mut redis_conn).await {
+        Ok(v) => download(format, v),
+        Err(e) => {
+            error!("Could not get export data: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Generates the export, and stores it for download under the token.
+async fn generate_export(
+    postgres_pool: Pool<PostgresConnectionManager<NoTls>>,
+    redis_pool: Pool<RedisConnectionManager>,
+    sub: String,
+    format: ExportFormat,
+    token: String,
+) {
+    let mut redis_conn: RedisConn = match redis_pool.get().await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Cannot get connection from pool: {:?}", e);
+            return;
+        }
+    };
+
+    let data: Result<Option<Vec<u8>>, Box<dyn Error + Send + Sync>> =
+        match postgres_pool.get().await {
+            Ok(mut v) => build_export(&sub, format, &mut v, &mut redis_conn).await,
+            Err(e) => Err(e.into()),
+        };
+
+    let ready: bool = matches!(data, Ok(Some(_)));
+    let stored: RedisResult<()> = match data {
+        Ok(Some(v)) => store_export(&token, &v, expires_in_seconds(), &mut redis_conn).await,
+        Ok(None) => {
+            error!("Could not export {}: user does not exist", sub);
+            mark_export_failed(&token, expires_in_seconds(), &mut redis_conn).await
+        }
+        Err(e) => {
+            error!("Could not build export of {}: {:?}", sub, e);
+            mark_export_failed(&token, expires_in_seconds(), &mut redis_conn).await
+        }
+    };
+
+    match stored {
+        Ok(_) if ready => {
+            info!("Stored export of {}", sub);
+            notify(
+                &sub,
+                Notification::new("export-ready-title", "export-ready")
+                    .arg("link", format!("/user/export/{token}")),
+                &redis_pool,
+            )
+            .await;
+        }
+        Ok(_) => (),
+        Err(e) => error!("Could not store export of {}: {:?}", sub, e),
+    }
+}
+
+async fn build_export(
+    sub: &str,
+    format: ExportFormat,
+    postgres_conn: &mut PostgresConn<'_>,
+    redis_conn: &mut RedisConn<'_>,
+) -> Result<Option<Vec<u8>>, Box<dyn Error + Send + Sync>> {
+    let mut export: Export = match get_export(sub, postgres_conn).await? {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+
+    export.notifications = user_notifications(sub, redis_conn)
+        .await?
+        .notifications
+        .into_iter()
+        .map(Into::into)
+        .collect();
+
+    Ok(Some(export.to_bytes(format)?))
+}
+
+fn download(format: ExportFormat, data: Vec<u8>) -> Response {
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, format.content_type().to_owned()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", format.file_name()),
+            ),
+        ],
+        data,
+    )
+        .into_response()
+}
+
+fn expires_in_seconds() -> i64 {
+    EXPORT_EXPIRES_MINUTES.get().unwrap() * 60
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{body::Body, http::Request};
+    use reqwest::{header, StatusCode};
+    use tower::ServiceExt;
+
+    use crate::fixture::{assert_body_contains, initialize, seed_database};
+
+    #[tokio::test]
+    async fn test_no_params_provided_export() {
+        let app: axum::Router = initialize().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/user/export")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_export_json() {
+        let app: axum::Router = initialize().await;
+        seed_database().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/user/export?sub=auth0|0003&format=Json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+        assert_body_contains(
+            response,
+            &[
+                "auth0|0003",
+                "transactions",
+                "email_verified",
+                "audit_log",
+                "notifications",
+            ],
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_export_unknown_user() {
+        let app: axum::Router = initialize().await;
+        seed_database().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/user/export?sub=auth0|9999")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_body_contains(response, &["User does not exist"]).await;
+    }
+
+    #[tokio::test]
+    async fn test_download_expired_export() {
+        let app: axum::Router = initialize().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/user/export/unknown-token?sub=auth0|0003")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::GONE);
+        assert_body_contains(response, &["Download link has expired"]).await;
+    }
+}
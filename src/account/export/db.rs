@@ -0,0 +1,211 @@
+use super::model::{Export, ExportFormat, ExportStatus, StoredExport};
+
+use chrono::Local;
+use leprecon::utils::{PostgresConn, RedisConn};
+use redis::{AsyncCommands, RedisResult};
+use tokio_postgres::{IsolationLevel, Row, Transaction};
+
+pub(super) async fn count_transactions(
+    sub: &str,
+    db_client: &PostgresConn<'_>,
+) -> Result<i64, tokio_postgres::Error> {
+    let r: Row = db_client
+        .query_one(
+            "SELECT COUNT(*) AS count FROM transactions INNER JOIN users ON users.id = transactions.user_id WHERE sub = $1",
+            &[&sub],
+        )
+        .await?;
+
+    Ok(r.get("count"))
+}
+
+/// Gathers all data of the user from a single snapshot, unknown users have no export.
+pub(super) async fn get_export(
+    sub: &str,
+    db_client: &mut PostgresConn<'_>,
+) -> Result<Option<Export>, tokio_postgres::Error> {
+    let transaction: Transaction = db_client
+        .build_transaction()
+        .isolation_level(IsolationLevel::RepeatableRead)
+        .read_only(true)
+        .start()
+        .await?;
+
+    let user: Row = match transaction
+        .query_opt(
+            "SELECT sub, email, email_verified, locale, balance, acronym FROM users INNER JOIN currencies ON currencies.id = users.currency_id WHERE sub = $1 AND deleted IS NULL",
+            &[&sub],
+        )
+        .await?
+    {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+
+    let customer_details: Option<Row> = transaction
+        .query_opt(
+            "SELECT customer_details.* FROM customer_details INNER JOIN users ON users.id = customer_details.user_id WHERE sub = $1",
+            &[&sub],
+        )
+        .await?;
+
+    let sessions: Vec<Row> = transaction
+        .query(
+            "SELECT sessions.* FROM sessions INNER JOIN users ON users.id = sessions.user_id WHERE sub = $1 ORDER BY expires",
+            &[&sub],
+        )
+        .await?;
+
+    let transactions: Vec<Row> = transaction
+        .query(
+            "SELECT transactions.* FROM transactions INNER JOIN users ON users.id = transactions.user_id WHERE sub = $1 ORDER BY created",
+            &[&sub],
+        )
+        .await?;
+
+    let limits: Vec<Row> = transaction
+        .query(
+            "SELECT limits.* FROM limits INNER JOIN users ON users.id = limits.user_id WHERE sub = $1 ORDER BY kind, period",
+            &[&sub],
+        )
+        .await?;
+
+    let self_exclusions: Vec<Row> = transaction
+        .query(
+            "SELECT self_exclusions.* FROM self_exclusions INNER JOIN users ON users.id = self_exclusions.user_id WHERE sub = $1 ORDER BY starts",
+            &[&sub],
+        )
+        .await?;
+
+    let kyc_verification: Option<Row> = transaction
+        .query_opt(
+            "SELECT kyc_verifications.* FROM kyc_verifications INNER JOIN users ON users.id = kyc_verifications.user_id WHERE sub = $1",
+            &[&sub],
+        )
+        .await?;
+
+    let kyc_documents: Vec<Row> = transaction
+        .query(
+            "SELECT kyc_documents.* FROM kyc_documents INNER JOIN users ON users.id = kyc_documents.user_id WHERE sub = $1 ORDER BY uploaded",
+            &[&sub],
+        )
+        .await?;
+
+    let audit_log: Vec<Row> = transaction
+        .query(
+            "SELECT actor, action, diff::text AS diff, request_id, ip, created FROM audit_log WHERE sub = $1 ORDER BY created",
+            &[&sub],
+        )
+        .await?;
+
+    transaction.commit().await?;
+
+    Ok(Some(Export {
+        generated: Local::now(),
+        user: user.into(),
+        customer_details: customer_details.map(Into::into),
+        sessions: sessions.into_iter().map(Into::into).collect(),
+        transactions: transactions.into_iter().map(Into::into).collect(),
+        limits: limits.into_iter().map(Into::into).collect(),
+        self_exclusions: self_exclusions.into_iter().map(Into::into).collect(),
+        kyc_verification: kyc_verification.map(Into::into),
+        kyc_documents: kyc_documents.into_iter().map(Into::into).collect(),
+        audit_log: audit_log.into_iter().map(Into::into).collect(),
+        notifications: Vec::new(),
+    }))
+}
+
+fn export_key(token: &str) -> String {
+    format!("export:{token}")
+}
+
+/// Registers an export which is still being generated, it expires after the given seconds.
+pub(super) async fn store_pending_export(
+    token: &str,
+    sub: &str,
+    format: ExportFormat,
+    expires: i64,
+    conn: &mut RedisConn<'_>,
+) -> RedisResult<()> {
+    let key: String = export_key(token);
+
+    redis::pipe()
+        .atomic()
+        .hset_multiple(
+            &key,
+            &[
+                ("sub", sub.to_owned()),
+                ("format", format.to_string()),
+                ("status", ExportStatus::Pending.to_string()),
+            ],
+        )
+        .ignore()
+        .expire(&key, expires)
+        .ignore()
+        .query_async(&mut **conn)
+        .await
+}
+
+/// Stores the generated export, and restarts its expiry.
+pub(super) async fn store_export(
+    token: &str,
+    data: &[u8],
+    expires: i64,
+    conn: &mut RedisConn<'_>,
+) -> RedisResult<()> {
+    let key: String = export_key(token);
+
+    redis::pipe()
+        .atomic()
+        .hset(&key, "data", data)
+        .ignore()
+        .hset(&key, "status", ExportStatus::Ready.to_string())
+        .ignore()
+        .expire(&key, expires)
+        .ignore()
+        .query_async(&mut **conn)
+        .await
+}
+
+pub(super) async fn mark_export_failed(
+    token: &str,
+    expires: i64,
+    conn: &mut RedisConn<'_>,
+) -> RedisResult<()> {
+    let key: String = export_key(token);
+
+    redis::pipe()
+        .atomic()
+        .hset(&key, "status", ExportStatus::Failed.to_string())
+        .ignore()
+        .expire(&key, expires)
+        .ignore()
+        .query_async(&mut **conn)
+        .await
+}
+
+/// The export belonging to the token, expired exports are gone.
+pub(super) async fn get_stored_export(
+    token: &str,
+    conn: &mut RedisConn<'_>,
+) -> RedisResult<Option<StoredExport>> {
+    let (sub, format, status): (Option<String>, Option<String>, Option<String>) = conn
+        .hget(export_key(token), &["sub", "format", "status"])
+        .await?;
+
+    Ok(match (sub, format, status) {
+        (Some(sub), Some(format), Some(status)) => Some(StoredExport {
+            sub,
+            format,
+            status,
+        }),
+        _ => None,
+    })
+}
+
+pub(super) async fn get_stored_export_data(
+    token: &str,
+    conn: &mut RedisConn<'_>,
+) -> RedisResult<Vec<u8>> {
+    conn.hget(export_key(token), "data").await
+}
@@ -0,0 +1,385 @@
+use chrono::{DateTime, Local};
+use leprecon::{
+    template,
+    utils::validate::{Validate, ValidationError},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    io::{Cursor, Write},
+    str::FromStr,
+};
+use tokio_postgres::Row;
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) enum ExportFormat {
+    #[default]
+    Json,
+    Zip,
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = ParseExportFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Json" => Ok(ExportFormat::Json),
+            "Zip" => Ok(ExportFormat::Zip),
+            _ => Err(ParseExportFormatError),
+        }
+    }
+}
+
+impl ExportFormat {
+    pub(super) fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "application/json",
+            ExportFormat::Zip => "application/zip",
+        }
+    }
+
+    pub(super) fn file_name(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "export.json",
+            ExportFormat::Zip => "export.zip",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct ParseExportFormatError;
+
+impl Display for ParseExportFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "provided string was not `Json` or `Zip`".fmt(f)
+    }
+}
+
+impl Error for ParseExportFormatError {}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct ExportParams {
+    #[serde(default)]
+    pub sub: String,
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+impl Validate for ExportParams {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.sub.is_empty() {
+            return Err(ValidationError::new("sub", "is required"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Progress of an export which is generated in the background.
+#[derive(Debug)]
+pub(super) enum ExportStatus {
+    Pending,
+    Ready,
+    Failed,
+}
+
+impl fmt::Display for ExportStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Export generated in the background, as kept in the session store.
+pub(super) struct StoredExport {
+    pub sub: String,
+    pub format: String,
+    pub status: String,
+}
+
+/// Everything stored about a user.
+#[derive(Serialize)]
+pub(super) struct Export {
+    pub generated: DateTime<Local>,
+    pub user: ExportUser,
+    pub customer_details: Option<ExportCustomerDetails>,
+    pub sessions: Vec<ExportSession>,
+    pub transactions: Vec<ExportTransaction>,
+    pub limits: Vec<ExportLimit>,
+    pub self_exclusions: Vec<ExportSelfExclusion>,
+    pub kyc_verification: Option<ExportKycVerification>,
+    pub kyc_documents: Vec<ExportKycDocument>,
+    pub audit_log: Vec<ExportAuditEntry>,
+    pub notifications: Vec<ExportNotification>,
+}
+
+impl Export {
+    pub(super) fn to_bytes(
+        &self,
+        format: ExportFormat,
+    ) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        match format {
+            ExportFormat::Json => Ok(serde_json::to_vec_pretty(self)?),
+            ExportFormat::Zip => self.to_zip(),
+        }
+    }
+
+    /// One csv file per table.
+    fn to_zip(&self) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        let mut zip: ZipWriter<Cursor<Vec<u8>>> = ZipWriter::new(Cursor::new(Vec::new()));
+        let options: SimpleFileOptions = SimpleFileOptions::default();
+
+        let files: [(&str, Vec<u8>); 10] = [
+            ("user.csv", to_csv(std::slice::from_ref(&self.user))?),
+            (
+                "customer_details.csv",
+                to_csv(self.customer_details.as_slice())?,
+            ),
+            ("sessions.csv", to_csv(&self.sessions)?),
+            ("transactions.csv", to_csv(&self.transactions)?),
+            ("limits.csv", to_csv(&self.limits)?),
+            ("self_exclusions.csv", to_csv(&self.self_exclusions)?),
+            (
+                "kyc_verification.csv",
+                to_csv(self.kyc_verification.as_slice())?,
+            ),
+            ("kyc_documents.csv", to_csv(&self.kyc_documents)?),
+            ("audit_log.csv", to_csv(&self.audit_log)?),
+            ("notifications.csv", to_csv(&self.notifications)?),
+        ];
+
+        for (name, content) in files {
+            zip.start_file(name, options)?;
+            zip.write_all(&content)?;
+        }
+
+        Ok(zip.finish()?.into_inner())
+    }
+}
+
+fn to_csv<T: Serialize>(records: &[T]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    let mut writer: csv::Writer<Vec<u8>> = csv::Writer::from_writer(Vec::new());
+
+    for record in records {
+        writer.serialize(record)?;
+    }
+
+    Ok(writer.into_inner()?)
+}
+
+#[derive(Serialize)]
+pub(super) struct ExportUser {
+    pub sub: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub locale: Option<String>,
+    pub balance: f64,
+    pub currency: String,
+}
+
+impl From<Row> for ExportUser {
+    fn from(r: Row) -> Self {
+        ExportUser {
+            sub: r.get("sub"),
+            email: r.get("email"),
+            email_verified: r.get("email_verified"),
+            locale: r.get("locale"),
+            balance: r.get("balance"),
+            currency: r.get("acronym"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(super) struct ExportCustomerDetails {
+    pub first_name: Option<String>,
+    pub middle_name: Option<String>,
+    pub last_name: Option<String>,
+    pub postal_code: Option<String>,
+    pub street_name: Option<String>,
+    pub street_nr: Option<String>,
+    pub premise: Option<String>,
+    pub settlement: Option<String>,
+    pub country: Option<String>,
+    pub country_code: Option<String>,
+}
+
+impl From<Row> for ExportCustomerDetails {
+    fn from(r: Row) -> Self {
+        ExportCustomerDetails {
+            first_name: r.get("first_name"),
+            middle_name: r.get("middle_name"),
+            last_name: r.get("last_name"),
+            postal_code: r.get("postal_code"),
+            street_name: r.get("street_name"),
+            street_nr: r.get("street_nr"),
+            premise: r.get("premise"),
+            settlement: r.get("settlement"),
+            country: r.get("country"),
+            country_code: r.get("country_code"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(super) struct ExportSession {
+    #[serde(rename = "type")]
+    pub session_type: String,
+    pub expires: DateTime<Local>,
+}
+
+impl From<Row> for ExportSession {
+    fn from(r: Row) -> Self {
+        ExportSession {
+            session_type: r.get("type"),
+            expires: r.get("expires"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(super) struct ExportTransaction {
+    pub kind: String,
+    pub amount: f64,
+    pub currency: String,
+    pub reference: String,
+    pub created: DateTime<Local>,
+}
+
+impl From<Row> for ExportTransaction {
+    fn from(r: Row) -> Self {
+        ExportTransaction {
+            kind: r.get("kind"),
+            amount: r.get("amount"),
+            currency: r.get("currency"),
+            reference: r.get("reference"),
+            created: r.get("created"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(super) struct ExportLimit {
+    pub kind: String,
+    pub period: String,
+    pub amount: f64,
+    pub pending_amount: Option<f64>,
+    pub pending_from: Option<DateTime<Local>>,
+    pub updated: DateTime<Local>,
+}
+
+impl From<Row> for ExportLimit {
+    fn from(r: Row) -> Self {
+        ExportLimit {
+            kind: r.get("kind"),
+            period: r.get("period"),
+            amount: r.get("amount"),
+            pending_amount: r.get("pending_amount"),
+            pending_from: r.get("pending_from"),
+            updated: r.get("updated"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(super) struct ExportSelfExclusion {
+    pub starts: DateTime<Local>,
+    pub ends: Option<DateTime<Local>>,
+}
+
+impl From<Row> for ExportSelfExclusion {
+    fn from(r: Row) -> Self {
+        ExportSelfExclusion {
+            starts: r.get("starts"),
+            ends: r.get("ends"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(super) struct ExportKycVerification {
+    pub status: String,
+    pub reason: Option<String>,
+    pub updated: DateTime<Local>,
+}
+
+impl From<Row> for ExportKycVerification {
+    fn from(r: Row) -> Self {
+        ExportKycVerification {
+            status: r.get("status"),
+            reason: r.get("reason"),
+            updated: r.get("updated"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(super) struct ExportKycDocument {
+    pub document_type: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: i32,
+    pub uploaded: DateTime<Local>,
+}
+
+impl From<Row> for ExportKycDocument {
+    fn from(r: Row) -> Self {
+        ExportKycDocument {
+            document_type: r.get("document_type"),
+            file_name: r.get("file_name"),
+            content_type: r.get("content_type"),
+            size: r.get("size"),
+            uploaded: r.get("uploaded"),
+        }
+    }
+}
+
+/// Audit entry of a change to the user, the diff only names the changed fields.
+#[derive(Serialize)]
+pub(super) struct ExportAuditEntry {
+    pub actor: String,
+    pub action: String,
+    pub diff: Option<String>,
+    pub request_id: Option<String>,
+    pub ip: Option<String>,
+    pub created: DateTime<Local>,
+}
+
+impl From<Row> for ExportAuditEntry {
+    fn from(r: Row) -> Self {
+        ExportAuditEntry {
+            actor: r.get("actor"),
+            action: r.get("action"),
+            diff: r.get("diff"),
+            request_id: r.get("request_id"),
+            ip: r.get("ip"),
+            created: r.get("created"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(super) struct ExportNotification {
+    pub title: String,
+    pub message: String,
+    pub read: bool,
+    pub created: String,
+}
+
+impl From<template::Notification> for ExportNotification {
+    fn from(n: template::Notification) -> Self {
+        ExportNotification {
+            title: n.title,
+            message: n.message,
+            read: n.read,
+            created: n.created,
+        }
+    }
+}
@@ -9,6 +9,7 @@ use bb8_redis::{bb8::Pool, RedisConnectionManager};
 use chrono::{DateTime, Local};
 use leprecon::{
     auth::{get_valid_jwt, JWT},
+    broker::init_broker,
     utils::create_conn_pool,
 };
 use tokio::sync::Mutex;
@@ -16,7 +17,8 @@ use tokio_postgres::NoTls;
 use tracing::error;
 
 use crate::{
-    build_app, embedded, init_env, ACCOUNT_CONN, AUTH_HOST, CLIENT_ID, CLIENT_SECRET, VALKEY_CONN,
+    balance::balance_channel, build_app, embedded, init_env, ACCOUNT_ADMIN_TOKEN, ACCOUNT_CONN,
+    AUTH_HOST, CLIENT_ID, CLIENT_SECRET, SERVICE_TOKEN, VALKEY_CONN,
 };
 
 #[allow(dead_code)]
@@ -56,11 +58,24 @@ pub(crate) async fn initialize() -> Router {
     .await
     .unwrap();
 
+    let environment = init_broker().await;
+    let producer: rabbitmq_stream_client::Producer<rabbitmq_stream_client::NoDedup> =
+        environment.producer().build("kyc_status").await.unwrap();
+    let deleted_producer: rabbitmq_stream_client::Producer<rabbitmq_stream_client::NoDedup> =
+        environment
+            .producer()
+            .build("account_deleted")
+            .await
+            .unwrap();
+
     build_app(
         Arc::new(Mutex::new(jwt)),
         req_client,
         postgres_pool,
         redis_pool,
+        producer,
+        deleted_producer,
+        balance_channel(),
     )
 }
 
@@ -93,11 +108,20 @@ pub(crate) async fn seed_database() {
         .unwrap();
 
     let sub: String = env::var("SUB_NOT_VERIFIED").unwrap();
-    let subs: Vec<&str> = vec!["auth0|0000", "auth0|0002", "auth0|0003", "auth0|0004", &sub];
+    let subs: Vec<&str> = vec![
+        "auth0|0000",
+        "auth0|0002",
+        "auth0|0003",
+        "auth0|0004",
+        "auth0|0005",
+        "auth0|0006",
+        &sub,
+    ];
 
     add_currency(&db_client).await;
     add_users(&db_client, &subs).await;
     add_email_session(&db_client, subs[0]).await;
+    verify_email(&db_client, "auth0|0006").await;
 
     *initialised = true;
 }
@@ -150,6 +174,15 @@ async fn add_email_session(conn: &tokio_postgres::Client, sub: &str) {
     .unwrap();
 }
 
+async fn verify_email(conn: &tokio_postgres::Client, sub: &str) {
+    conn.query(
+        "UPDATE users SET email_verified = true WHERE sub = $1",
+        &[&sub],
+    )
+    .await
+    .unwrap();
+}
+
 #[allow(dead_code)]
 pub(crate) async fn assert_body_contains(response: axum::http::Response<Body>, body: &[&str]) {
     let bytes: body::Bytes = body::to_bytes(response.into_body(), usize::MAX)
@@ -161,3 +194,15 @@ pub(crate) async fn assert_body_contains(response: axum::http::Response<Body>, b
         assert!(body_str.contains(s));
     }
 }
+
+/// Authorization header value of the admin endpoints.
+#[allow(dead_code)]
+pub(crate) fn admin_bearer() -> String {
+    format!("Bearer {}", ACCOUNT_ADMIN_TOKEN.get().unwrap())
+}
+
+/// Authorization header value of the endpoints for the other services.
+#[allow(dead_code)]
+pub(crate) fn service_bearer() -> String {
+    format!("Bearer {}", SERVICE_TOKEN.get().unwrap())
+}
@@ -0,0 +1,458 @@
+mod model;
+
+pub(crate) mod db;
+
+use self::{
+    db::{
+        claim_unpublished_kyc, get_kyc, get_kyc_documents, insert_kyc_document, mark_kyc_published,
+        set_kyc_status,
+    },
+    model::{transition, DocumentParams, Kyc, KycDocument, KycEvent, ReviewParams},
+};
+
+use crate::{
+    model::AdminToken,
+    notification::{notify, Notification},
+    StateParams,
+};
+
+use askama::Template;
+use axum::{extract::State, response::Html, Form};
+use bb8_postgres::{bb8::Pool, PostgresConnectionManager};
+use leprecon::{
+    auth::AuthParam,
+    broker::{KycStatus, KycStatusChanged},
+    template::{self, Snackbar},
+    utils::{
+        extract::{extract_conn_from_pool, ValidForm},
+        PostgresConn,
+    },
+};
+use rabbitmq_stream_client::{types::Message, NoDedup, Producer};
+use reqwest::StatusCode;
+use std::error::Error;
+use tokio_postgres::{NoTls, Transaction};
+use tracing::{error, info};
+
+pub(super) async fn user_kyc(
+    State(state): State<StateParams>,
+    Form(auth_param): Form<AuthParam>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    if auth_param.sub.is_empty() {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Html(snackbar.render().unwrap()),
+        );
+    };
+
+    let postgres_conn: PostgresConn = match extract_conn_from_pool(&state.2, &mut snackbar).await {
+        Ok(v) => v,
+        Err(e) => return e,
+    };
+
+    let kyc: Kyc = match get_kyc(&auth_param.sub, &postgres_conn).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Could not get kyc status: {:?}", e);
+            return (StatusCode::BAD_GATEWAY, Html(snackbar.render().unwrap()));
+        }
+    };
+
+    let documents: Vec<KycDocument> = match get_kyc_documents(&auth_param.sub, &postgres_conn).await
+    {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Could not get kyc documents: {:?}", e);
+            return (StatusCode::BAD_GATEWAY, Html(snackbar.render().unwrap()));
+        }
+    };
+
+    let kyc_template: template::Kyc = template::Kyc {
+        status: kyc.status.to_string(),
+        reason: kyc.reason,
+        documents: documents
+            .into_iter()
+            .map(|d| template::KycDocument {
+                document_type: d.document_type.to_string(),
+                file_name: d.file_name,
+                uploaded: d.uploaded.format("%Y-%m-%d %H:%M").to_string(),
+            })
+            .collect(),
+    };
+
+    (StatusCode::OK, Html(kyc_template.render().unwrap()))
+}
+
+/// Stores the metadata of an uploaded identity document, and marks the verification as pending.
+pub(super) async fn submit_kyc_document(
+    State(state): State<StateParams>,
+    ValidForm(params): ValidForm<DocumentParams>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    let mut postgres_conn: PostgresConn =
+        match extract_conn_from_pool(&state.2, &mut snackbar).await {
+            Ok(v) => v,
+            Err(e) => return e,
+        };
+
+    let kyc: Kyc = match get_kyc(&params.sub, &postgres_conn).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Could not get kyc status: {:?}", e);
+            return (StatusCode::BAD_GATEWAY, Html(snackbar.render().unwrap()));
+        }
+    };
+
+    let status: KycStatus = match transition(kyc.status, KycEvent::DocumentSubmitted) {
+        Some(v) => v,
+        None => {
+            snackbar.message = "kyc-already-verified";
+            return (StatusCode::BAD_REQUEST, Html(snackbar.render().unwrap()));
+        }
+    };
+
+    let transaction: Transaction = match postgres_conn.transaction().await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Could not start transaction: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    if let Err(e) = insert_kyc_document(&params, &transaction).await {
+        error!("Cannot insert kyc document: {:?}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Html(snackbar.render().unwrap()),
+        );
+    }
+
+    if let Err(e) = set_kyc_status(
+        &params.sub,
+        status,
+        None,
+        kyc.status != status,
+        &transaction,
+    )
+    .await
+    {
+        error!("Cannot change kyc status: {:?}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Html(snackbar.render().unwrap()),
+        );
+    }
+
+    if let Err(e) = transaction.commit().await {
+        error!("Could not commit kyc document: {:?}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Html(snackbar.render().unwrap()),
+        );
+    }
+
+    // Published once committed, what fails here is retried by `run_kyc_outbox`
+    if let Err(e) = publish_kyc_statuses(&mut postgres_conn, &state.4).await {
+        error!("Could not publish kyc status: {:?}", e);
+    }
+
+    snackbar.title = "success";
+    snackbar.message = "kyc-submitted";
+    snackbar.color = "green";
+
+    (StatusCode::OK, Html(snackbar.render().unwrap()))
+}
+
+/// Approves or rejects a pending verification.
+pub(super) async fn review_kyc(
+    _: AdminToken,
+    State(state): State<StateParams>,
+    ValidForm(params): ValidForm<ReviewParams>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    let mut postgres_conn: PostgresConn =
+        match extract_conn_from_pool(&state.2, &mut snackbar).await {
+            Ok(v) => v,
+            Err(e) => return e,
+        };
+
+    let kyc: Kyc = match get_kyc(&params.sub, &postgres_conn).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Could not get kyc status: {:?}", e);
+            return (StatusCode::BAD_GATEWAY, Html(snackbar.render().unwrap()));
+        }
+    };
+
+    let event: KycEvent = match params.status {
+        KycStatus::Verified => KycEvent::Approved,
+        _ => KycEvent::Rejected,
+    };
+
+    let status: KycStatus = match transition(kyc.status, event) {
+        Some(v) => v,
+        None => {
+            snackbar.message = "kyc-not-pending";
+            return (StatusCode::BAD_REQUEST, Html(snackbar.render().unwrap()));
+        }
+    };
+
+    let transaction: Transaction = match postgres_conn.transaction().await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Could not start transaction: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    if let Err(e) = set_kyc_status(
+        &params.sub,
+        status,
+        params.reason.as_deref(),
+        kyc.status != status,
+        &transaction,
+    )
+    .await
+    {
+        error!("Cannot change kyc status: {:?}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Html(snackbar.render().unwrap()),
+        );
+    }
+
+    if let Err(e) = transaction.commit().await {
+        error!("Could not commit kyc review: {:?}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Html(snackbar.render().unwrap()),
+        );
+    }
+
+    // Published once committed, what fails here is retried by `run_kyc_outbox`
+    if let Err(e) = publish_kyc_statuses(&mut postgres_conn, &state.4).await {
+        error!("Could not publish kyc status: {:?}", e);
+    }
+
+    // The reason of the reviewer is shown as it is
+    let notification: Notification = match status {
+        KycStatus::Verified => Notification::new("identity-verified-title", "identity-verified"),
+        _ => Notification::new(
+            "identity-rejected-title",
+            params.reason.as_deref().unwrap_or("identity-rejected"),
+        ),
+    };
+    notify(&params.sub, notification, &state.3).await;
+
+    snackbar.title = "success";
+    snackbar.message = "kyc-reviewed";
+    snackbar.color = "green";
+
+    (StatusCode::OK, Html(snackbar.render().unwrap()))
+}
+
+/// Publishes the changed statuses which were not published yet.
+///
+/// A status is marked published only after the broker confirmed it, and may be sent twice when
+/// marking fails, consumers store the latest status so this is harmless.
+async fn publish_kyc_statuses(
+    postgres_conn: &mut PostgresConn<'_>,
+    producer: &Producer<NoDedup>,
+) -> Result<usize, Box<dyn Error + Send + Sync>> {
+    let transaction: Transaction = postgres_conn.transaction().await?;
+    let changes: Vec<KycStatusChanged> = claim_unpublished_kyc(50, &transaction).await?;
+
+    for event in &changes {
+        producer
+            .send_with_confirm(
+                Message::builder()
+                    .body(serde_json::to_string(event)?)
+                    .build(),
+            )
+            .await?;
+
+        mark_kyc_published(&event.sub, &transaction).await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(changes.len())
+}
+
+/// Retries publishing the status changes which could not be published with their commit.
+pub(super) async fn run_kyc_outbox(
+    postgres_pool: Pool<PostgresConnectionManager<NoTls>>,
+    producer: Producer<NoDedup>,
+) {
+    let mut interval: tokio::time::Interval =
+        tokio::time::interval(std::time::Duration::from_secs(30));
+
+    loop {
+        interval.tick().await;
+
+        let mut postgres_conn: PostgresConn = match postgres_pool.get().await {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Cannot get connection from pool: {:?}", e);
+                continue;
+            }
+        };
+
+        match publish_kyc_statuses(&mut postgres_conn, &producer).await {
+            Ok(0) => {}
+            Ok(v) => info!("Published {} kyc statuses", v),
+            Err(e) => error!("Could not publish kyc statuses: {:?}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{body::Body, http::Request};
+    use reqwest::{header, Method, StatusCode};
+    use tower::ServiceExt;
+
+    use crate::fixture::{admin_bearer, assert_body_contains, initialize, seed_database};
+
+    // Get kyc
+    #[tokio::test]
+    async fn test_no_params_provided_get_kyc() {
+        let app: axum::Router = initialize().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/user/kyc")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_body_contains(response, &["Could not process request"]).await;
+    }
+
+    #[tokio::test]
+    async fn test_unverified_kyc() {
+        let app: axum::Router = initialize().await;
+        seed_database().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/user/kyc?sub=auth0|0002")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_body_contains(response, &["status: Unverified"]).await;
+    }
+
+    // Submit document
+    #[tokio::test]
+    async fn test_submit_document_invalid_content_type() {
+        let app: axum::Router = initialize().await;
+
+        let params: String = String::from(
+            "sub=auth0|0003&document_type=Passport&file_name=passport.gif&content_type=image/gif&size=100",
+        );
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/account/user/kyc/documents")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(params)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_body_contains(response, &["content_type: must be a pdf, jpeg or png"]).await;
+    }
+
+    // Review
+    #[tokio::test]
+    async fn test_review_without_token() {
+        let app: axum::Router = initialize().await;
+
+        let params: String = String::from("sub=auth0|0002&status=Verified");
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::PUT)
+                    .uri("/account/admin/kyc")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(params)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_reject_without_reason() {
+        let app: axum::Router = initialize().await;
+
+        let params: String = String::from("sub=auth0|0002&status=Rejected");
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::PUT)
+                    .uri("/account/admin/kyc")
+                    .header(header::AUTHORIZATION, admin_bearer())
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(params)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_body_contains(response, &["reason: is required when rejecting"]).await;
+    }
+
+    #[tokio::test]
+    async fn test_review_not_pending() {
+        let app: axum::Router = initialize().await;
+        seed_database().await;
+
+        let params: String = String::from("sub=auth0|0002&status=Verified");
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::PUT)
+                    .uri("/account/admin/kyc")
+                    .header(header::AUTHORIZATION, admin_bearer())
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(params)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_body_contains(response, &["Verification is not pending"]).await;
+    }
+}
@@ -0,0 +1,135 @@
+use super::model::{DocumentParams, DocumentType, Kyc, KycDocument};
+
+use leprecon::{
+    broker::{KycStatus, KycStatusChanged},
+    utils::PostgresConn,
+};
+use std::{error::Error, str::FromStr};
+use tokio_postgres::{Row, Transaction};
+
+/// Verification of the user, users without a verification entry are unverified.
+pub(super) async fn get_kyc(
+    sub: &str,
+    db_client: &PostgresConn<'_>,
+) -> Result<Kyc, Box<dyn Error>> {
+    let r: Row = db_client
+        .query_one(
+            "SELECT kyc_verifications.status, kyc_verifications.reason FROM users LEFT JOIN kyc_verifications ON users.id = kyc_verifications.user_id WHERE sub = $1 LIMIT 1",
+            &[&sub],
+        )
+        .await?;
+
+    let status: Option<&str> = r.get("status");
+
+    Ok(Kyc {
+        status: match status {
+            Some(v) => KycStatus::from_str(v)?,
+            None => KycStatus::Unverified,
+        },
+        reason: r.get("reason"),
+    })
+}
+
+/// Stores the status, a changed status is left unpublished until [`mark_kyc_published`].
+pub(super) async fn set_kyc_status(
+    sub: &str,
+    status: KycStatus,
+    reason: Option<&str>,
+    changed: bool,
+    transaction: &Transaction<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    transaction
+        .execute(
+            "WITH userId AS (SELECT id FROM users WHERE sub = $1) INSERT INTO kyc_verifications(status, reason, updated, published, user_id) VALUES($2, $3, now(), CASE WHEN $4 THEN NULL ELSE now() END, (SELECT id FROM userId)) ON CONFLICT (user_id) DO UPDATE SET status = EXCLUDED.status, reason = EXCLUDED.reason, updated = EXCLUDED.updated, published = CASE WHEN $4 THEN NULL ELSE kyc_verifications.published END",
+            &[&sub, &status.to_string(), &reason, &changed],
+        )
+        .await
+}
+
+/// Claims the changed statuses which were not published yet, until the transaction ends.
+pub(super) async fn claim_unpublished_kyc(
+    limit: i64,
+    transaction: &Transaction<'_>,
+) -> Result<Vec<KycStatusChanged>, Box<dyn Error + Send + Sync>> {
+    let rows: Vec<Row> = transaction
+        .query(
+            "SELECT users.sub, kyc_verifications.status, kyc_verifications.reason FROM kyc_verifications INNER JOIN users ON users.id = kyc_verifications.user_id WHERE kyc_verifications.published IS NULL ORDER BY kyc_verifications.updated LIMIT $1 FOR UPDATE OF kyc_verifications SKIP LOCKED",
+            &[&limit],
+        )
+        .await?;
+
+    let mut changes: Vec<KycStatusChanged> = vec![];
+    for r in rows {
+        changes.push(KycStatusChanged {
+            sub: r.get("sub"),
+            status: KycStatus::from_str(r.get("status"))?,
+            reason: r.get("reason"),
+        });
+    }
+
+    Ok(changes)
+}
+
+pub(super) async fn mark_kyc_published(
+    sub: &str,
+    transaction: &Transaction<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    transaction
+        .execute(
+            "WITH userId AS (SELECT id FROM users WHERE sub = $1) UPDATE kyc_verifications SET published = now() WHERE user_id = (SELECT id FROM userId)",
+            &[&sub],
+        )
+        .await
+}
+
+pub(super) async fn insert_kyc_document(
+    document: &DocumentParams,
+    transaction: &Transaction<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    transaction
+        .execute(
+            "WITH userId AS (SELECT id FROM users WHERE sub = $1) INSERT INTO kyc_documents(document_type, file_name, content_type, size, uploaded, user_id) VALUES($2, $3, $4, $5, now(), (SELECT id FROM userId))",
+            &[&document.sub, &document.document_type.to_string(), &document.file_name.trim(), &document.content_type, &document.size],
+        )
+        .await
+}
+
+pub(super) async fn get_kyc_documents(
+    sub: &str,
+    db_client: &PostgresConn<'_>,
+) -> Result<Vec<KycDocument>, Box<dyn Error>> {
+    let rows: Vec<Row> = db_client
+        .query(
+            "SELECT kyc_documents.* FROM kyc_documents INNER JOIN users ON users.id = kyc_documents.user_id WHERE sub = $1 ORDER BY uploaded DESC",
+            &[&sub],
+        )
+        .await?;
+
+    let mut documents: Vec<KycDocument> = vec![];
+    for r in rows {
+        documents.push(KycDocument {
+            document_type: DocumentType::from_str(r.get("document_type"))?,
+            file_name: r.get("file_name"),
+            uploaded: r.get("uploaded"),
+        });
+    }
+
+    Ok(documents)
+}
+
+pub(crate) async fn delete_kyc(
+    sub: &str,
+    transaction: &Transaction<'_>,
+) -> Result<Option<Row>, tokio_postgres::Error> {
+    transaction.query_opt(
+        "WITH userId AS (SELECT id FROM users WHERE sub = $1) DELETE FROM kyc_documents WHERE user_id = (SELECT id FROM userId)",
+        &[&sub],
+    )
+    .await?;
+
+    transaction.query_opt(
+        "WITH userId AS (SELECT id FROM users WHERE sub = $1) DELETE FROM kyc_verifications WHERE user_id = (SELECT id FROM userId)",
+        &[&sub],
+    )
+    .await
+}
@@ -0,0 +1,149 @@
+use chrono::{DateTime, Local};
+use leprecon::{
+    broker::KycStatus,
+    utils::validate::{Validate, ValidationError},
+};
+use serde::Deserialize;
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+const MAX_DOCUMENT_SIZE: i32 = 10 * 1024 * 1024; // 10 MiB
+const DOCUMENT_CONTENT_TYPES: [&str; 3] = ["application/pdf", "image/jpeg", "image/png"];
+
+/// Something that happens to a verification, moving it to another status.
+#[derive(Debug, Clone, Copy)]
+pub(super) enum KycEvent {
+    DocumentSubmitted,
+    Approved,
+    Rejected,
+}
+
+/// Returns the status after the event, or `None` when the event is not allowed in the current status.
+pub(super) fn transition(status: KycStatus, event: KycEvent) -> Option<KycStatus> {
+    match (status, event) {
+        (KycStatus::Unverified, KycEvent::DocumentSubmitted) => Some(KycStatus::Pending),
+        (KycStatus::Pending, KycEvent::DocumentSubmitted) => Some(KycStatus::Pending),
+        (KycStatus::Rejected, KycEvent::DocumentSubmitted) => Some(KycStatus::Pending),
+        (KycStatus::Pending, KycEvent::Approved) => Some(KycStatus::Verified),
+        (KycStatus::Pending, KycEvent::Rejected) => Some(KycStatus::Rejected),
+        _ => None,
+    }
+}
+
+pub(super) struct Kyc {
+    pub status: KycStatus,
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub(crate) enum DocumentType {
+    Passport,
+    IdCard,
+    DriversLicense,
+    ProofOfAddress,
+}
+
+impl fmt::Display for DocumentType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+pub(super) struct KycDocument {
+    pub document_type: DocumentType,
+    pub file_name: String,
+    pub uploaded: DateTime<Local>,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct DocumentParams {
+    #[serde(default)]
+    pub sub: String,
+    pub document_type: DocumentType,
+    #[serde(default)]
+    pub file_name: String,
+    #[serde(default)]
+    pub content_type: String,
+    pub size: i32,
+}
+
+impl Validate for DocumentParams {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.sub.is_empty() {
+            return Err(ValidationError::new("sub", "is required"));
+        }
+
+        if self.file_name.trim().is_empty() {
+            return Err(ValidationError::new("file_name", "is required"));
+        }
+
+        if !DOCUMENT_CONTENT_TYPES.contains(&self.content_type.as_str()) {
+            return Err(ValidationError::new(
+                "content_type",
+                "must be a pdf, jpeg or png",
+            ));
+        }
+
+        if self.size <= 0 || self.size > MAX_DOCUMENT_SIZE {
+            return Err(ValidationError::new("size", "must be at most 10 MiB"));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct ReviewParams {
+    #[serde(default)]
+    pub sub: String,
+    pub status: KycStatus,
+    pub reason: Option<String>,
+}
+
+impl Validate for ReviewParams {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.sub.is_empty() {
+            return Err(ValidationError::new("sub", "is required"));
+        }
+
+        match self.status {
+            KycStatus::Verified => Ok(()),
+            KycStatus::Rejected if self.reason.as_deref().is_some_and(|r| !r.is_empty()) => Ok(()),
+            KycStatus::Rejected => {
+                Err(ValidationError::new("reason", "is required when rejecting"))
+            }
+            _ => Err(ValidationError::new(
+                "status",
+                "must be Verified or Rejected",
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct ParseDocumentTypeError;
+
+impl Error for ParseDocumentTypeError {}
+
+impl Display for ParseDocumentTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ParseDocumentTypeError")
+    }
+}
+
+impl FromStr for DocumentType {
+    type Err = ParseDocumentTypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Passport" => Ok(DocumentType::Passport),
+            "IdCard" => Ok(DocumentType::IdCard),
+            "DriversLicense" => Ok(DocumentType::DriversLicense),
+            "ProofOfAddress" => Ok(DocumentType::ProofOfAddress),
+            _ => Err(ParseDocumentTypeError),
+        }
+    }
+}
@@ -0,0 +1,358 @@
+pub(crate) mod db;
+
+use self::db::{apply_balance_updates, credit_win, debit_wager, get_wallet, is_booked, lock_user};
+
+use crate::{
+    balance::db::publish_balance_update,
+    limit::limit_reached,
+    mail::queue_user_mail,
+    model::ServiceToken,
+    notification::{notify, Notification},
+    StateParams,
+};
+
+use axum::{
+    extract::State,
+    response::{IntoResponse, Response},
+    Form, Json,
+};
+use bb8_postgres::{bb8::Pool, PostgresConnectionManager};
+use bb8_redis::RedisConnectionManager;
+use chrono::Local;
+use futures::StreamExt;
+use leprecon::{
+    broker::{BalanceApplied, BalanceMessage, BalanceUpdate, BalanceUpdateKind},
+    limit::{LimitCheck, LimitCheckKind, LimitCheckParams},
+    template::DepositReceiptMail,
+    utils::{validate::is_whole_cents, PostgresConn},
+    wallet::{WagerParams, Wallet},
+};
+use rabbitmq_stream_client::{types::Message, Consumer, NoDedup, Producer};
+use reqwest::StatusCode;
+use std::error::Error;
+use tokio_postgres::{NoTls, Transaction};
+use tracing::{debug, error, info};
+
+/// Applies the balance updates from the stream to the ledger and the user balance.
+///
+/// Every applied update is confirmed on the `balance_applied` stream.
+pub(super) async fn consume_balance_updates(
+    mut consumer: Consumer,
+    producer: Producer<NoDedup>,
+    postgres_pool: Pool<PostgresConnectionManager<NoTls>>,
+    redis_pool: Pool<RedisConnectionManager>,
+) {
+    while let Some(delivery) = consumer.next().await {
+        let d = match delivery {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Could not receive balance update: {:?}", e);
+                continue;
+            }
+        };
+
+        let updates: Vec<BalanceUpdate> = match d
+            .message()
+            .data()
+            .map(serde_json::from_slice::<BalanceMessage>)
+        {
+            Some(Ok(v)) => v.into_updates(),
+            Some(Err(e)) => {
+                debug!(
+                    "Skipping malformed balance update at {}: {:?}",
+                    d.offset(),
+                    e
+                );
+                continue;
+            }
+            None => continue,
+        };
+
+        let mut postgres_conn: PostgresConn = match postgres_pool.get().await {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Cannot get connection from pool: {:?}", e);
+                continue;
+            }
+        };
+
+        let applied: Vec<bool> = match apply_balance_updates(&updates, &mut postgres_conn).await {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Could not apply balance updates {:?}: {:?}", updates, e);
+                continue;
+            }
+        };
+
+        for (update, applied) in updates.into_iter().zip(applied) {
+            if !applied {
+                debug!("Balance update already applied: {}", update.reference);
+                continue;
+            }
+
+            info!("Applied balance update: {}", update.reference);
+            publish_update(&update.sub, &redis_pool).await;
+            confirm_update(&update, &producer).await;
+
+            if update.kind == BalanceUpdateKind::Deposit {
+                notify_deposit(update, &redis_pool, &postgres_conn).await;
+            }
+        }
+    }
+}
+
+/// Checks the limits of the user and debits the stake of a game, which deals only once this
+/// succeeded. A win sent along is credited in the same transaction.
+///
+/// Answers `409 Conflict` with the reason when the limits or the balance do not allow the stake. A
+/// stake which was booked before is not checked again, so a game can retry it safely.
+pub(super) async fn wager(
+    _: ServiceToken,
+    State(state): State<StateParams>,
+    Form(params): Form<WagerParams>,
+) -> Response {
+    if params.sub.is_empty()
+        || params.reference.is_empty()
+        || !params.amount.is_finite()
+        || params.amount <= 0.0
+        || !is_whole_cents(params.amount)
+        || !params.win.is_finite()
+        || params.win < 0.0
+        || !is_whole_cents(params.win)
+        || (params.win > 0.0 && params.win_reference.is_empty())
+    {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(Wallet::default())).into_response();
+    }
+
+    let mut postgres_conn: PostgresConn = match state.2.get().await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Cannot get connection from pool: {:?}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(Wallet::default())).into_response();
+        }
+    };
+
+    // The error is not Send, so it must not be held across the await below
+    let taken: Result<Result<Wallet, LimitCheck>, String> = take_wager(&params, &mut postgres_conn)
+        .await
+        .map_err(|e| e.to_string());
+
+    match taken {
+        Ok(Ok(wallet)) => {
+            publish_update(&params.sub, &state.3).await;
+            (StatusCode::OK, Json(wallet)).into_response()
+        }
+        Ok(Err(check)) => (StatusCode::CONFLICT, Json(check)).into_response(),
+        Err(e) => {
+            error!("Could not debit wager {:?}: {:?}", params, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(Wallet::default())).into_response()
+        }
+    }
+}
+
+/// The wallet after the wager, or the reason why it was refused.
+///
+/// The limits are checked and the wager is debited while the user is locked, so concurrent wagers
+/// cannot all pass the same limit.
+async fn take_wager(
+    params: &WagerParams,
+    postgres_conn: &mut PostgresConn<'_>,
+) -> Result<Result<Wallet, LimitCheck>, Box<dyn Error>> {
+    let transaction: Transaction = postgres_conn.transaction().await?;
+
+    if !lock_user(&params.sub, &transaction).await? {
+        return Ok(Err(LimitCheck::refused("insufficient-balance")));
+    }
+
+    if is_booked(&params.reference, &transaction).await? {
+        debug!("Wager already debited: {}", params.reference);
+        return get_wallet(&params.sub, &transaction)
+            .await?
+            .ok_or_else(|| "User does not exist".into())
+            .map(Ok);
+    }
+
+    let check: LimitCheckParams = LimitCheckParams {
+        sub: params.sub.clone(),
+        kind: LimitCheckKind::Wager,
+        amount: params.amount,
+    };
+    let limit_check: LimitCheck = limit_reached(&check, &transaction).await?;
+    if !limit_check.allowed {
+        return Ok(Err(limit_check));
+    }
+
+    let mut wallet: Option<Wallet> =
+        debit_wager(&params.sub, params.amount, &params.reference, &transaction).await?;
+    if wallet.is_none() {
+        return Ok(Err(LimitCheck::refused("insufficient-balance")));
+    }
+
+    // Booked with the stake, so a win is never credited without its stake or lost after it
+    if params.win > 0.0 {
+        wallet = credit_win(&params.sub, params.win, &params.win_reference, &transaction).await?;
+    }
+    transaction.commit().await?;
+
+    info!("Debited wager: {}", params.reference);
+    wallet.ok_or_else(|| "User does not exist".into()).map(Ok)
+}
+
+/// Tells the user the deposit was credited, in the app and by mail.
+async fn notify_deposit(
+    update: BalanceUpdate,
+    redis_pool: &Pool<RedisConnectionManager>,
+    postgres_conn: &PostgresConn<'_>,
+) {
+    notify(
+        &update.sub,
+        Notification::new("deposit-credited-title", "deposit-credited")
+            .arg("amount", format!("{:.2}", update.amount))
+            .arg("currency", &update.currency),
+        redis_pool,
+    )
+    .await;
+
+    let mail: DepositReceiptMail = DepositReceiptMail {
+        amount: update.amount,
+        currency: update.currency,
+        reference: update.reference,
+        created: Local::now().format("%Y-%m-%d %H:%M").to_string(),
+    };
+
+    if let Err(e) = queue_user_mail(&update.sub, &mail, &**postgres_conn).await {
+        error!("Could not queue deposit receipt: {:?}", e);
+    }
+}
+
+/// Lets the service which published the update know it was booked.
+async fn confirm_update(update: &BalanceUpdate, producer: &Producer<NoDedup>) {
+    let applied: BalanceApplied = BalanceApplied {
+        sub: update.sub.clone(),
+        kind: update.kind,
+        reference: update.reference.clone(),
+    };
+
+    if let Err(e) = producer
+        .send_with_confirm(
+            Message::builder()
+                .body(serde_json::to_string(&applied).unwrap())
+                .build(),
+        )
+        .await
+    {
+        error!(
+            "Could not confirm balance update {}: {:?}",
+            update.reference, e
+        );
+    }
+}
+
+/// Lets the event streams of every replica know the balance of the user changed.
+async fn publish_update(sub: &str, redis_pool: &Pool<RedisConnectionManager>) {
+    match redis_pool.get().await {
+        Ok(mut v) => {
+            if let Err(e) = publish_balance_update(sub, &mut v).await {
+                error!("Could not publish balance update: {:?}", e);
+            }
+        }
+        Err(e) => error!("Cannot get connection from pool: {:?}", e),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{body::Body, http::Request};
+    use reqwest::{header, Method, StatusCode};
+    use tower::ServiceExt;
+
+    use crate::fixture::{initialize, seed_database, service_bearer};
+
+    #[tokio::test]
+    async fn test_wager_without_token() {
+        let app: axum::Router = initialize().await;
+
+        let params: String = String::from("sub=auth0|0002&amount=10&reference=test:1:wager");
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/account/user/wager")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(params)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_no_reference_wager() {
+        let app: axum::Router = initialize().await;
+
+        let params: String = String::from("sub=auth0|0002&amount=10");
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/account/user/wager")
+                    .header(header::AUTHORIZATION, service_bearer())
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(params)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_win_without_reference_wager() {
+        let app: axum::Router = initialize().await;
+
+        let params: String = String::from("sub=auth0|0002&amount=10&reference=test:1:wager&win=20");
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/account/user/wager")
+                    .header(header::AUTHORIZATION, service_bearer())
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(params)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_wager_exceeds_balance() {
+        let app: axum::Router = initialize().await;
+        seed_database().await;
+
+        let params: String = String::from("sub=auth0|0002&amount=10&reference=test:1:wager");
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/account/user/wager")
+                    .header(header::AUTHORIZATION, service_bearer())
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(params)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+}
@@ -0,0 +1,172 @@
+use chrono::{DateTime, Local};
+use leprecon::{
+    broker::{BalanceUpdate, BalanceUpdateKind},
+    utils::PostgresConn,
+    wallet::Wallet,
+};
+use tokio_postgres::{GenericClient, Row, Transaction};
+
+/// Stores the updates in the ledger and applies them to the balance, in one transaction.
+///
+/// Returns per update whether it was applied, false when the reference has already been applied.
+pub(super) async fn apply_balance_updates(
+    updates: &[BalanceUpdate],
+    db_client: &mut PostgresConn<'_>,
+) -> Result<Vec<bool>, tokio_postgres::Error> {
+    let transaction: Transaction = db_client.transaction().await?;
+
+    let mut applied: Vec<bool> = vec![];
+    for update in updates {
+        let inserted: u64 = transaction
+            .execute(
+                "WITH userId AS (SELECT id FROM users WHERE sub = $1) INSERT INTO transactions(kind, amount, currency, reference, created, user_id) VALUES($2, $3, $4, $5, $6, (SELECT id FROM userId)) ON CONFLICT (reference) DO NOTHING",
+                &[&update.sub, &update.kind.to_string(), &update.amount, &update.currency, &update.reference, &Local::now()],
+            )
+            .await?;
+
+        if inserted == 1 {
+            transaction
+                .execute(
+                    "UPDATE users SET balance = balance + $2 WHERE sub = $1",
+                    &[&update.sub, &update.amount],
+                )
+                .await?;
+        }
+
+        applied.push(inserted == 1);
+    }
+
+    transaction.commit().await?;
+
+    Ok(applied)
+}
+
+/// Whether a transaction with the reference was booked already.
+pub(super) async fn is_booked<C: GenericClient>(
+    reference: &str,
+    db_client: &C,
+) -> Result<bool, tokio_postgres::Error> {
+    let r: Option<Row> = db_client
+        .query_opt(
+            "SELECT id FROM transactions WHERE reference = $1",
+            &[&reference],
+        )
+        .await?;
+
+    Ok(r.is_some())
+}
+
+/// Locks the user until the transaction ends, so concurrent wagers or sends of the user are checked
+/// one by one. Returns false when there is no such user.
+pub(crate) async fn lock_user(
+    sub: &str,
+    transaction: &Transaction<'_>,
+) -> Result<bool, tokio_postgres::Error> {
+    let r: Option<Row> = transaction
+        .query_opt(
+            "SELECT id FROM users WHERE sub = $1 AND deleted IS NULL FOR UPDATE",
+            &[&sub],
+        )
+        .await?;
+
+    Ok(r.is_some())
+}
+
+/// Debits the wager when the balance covers it, and books it in the ledger.
+///
+/// Returns the wallet after the debit, or `None` when the balance is too low.
+pub(super) async fn debit_wager(
+    sub: &str,
+    amount: f64,
+    reference: &str,
+    transaction: &Transaction<'_>,
+) -> Result<Option<Wallet>, tokio_postgres::Error> {
+    let debited: Option<Row> = transaction
+        .query_opt(
+            "UPDATE users SET balance = balance - $2 WHERE sub = $1 AND deleted IS NULL AND balance >= $2 RETURNING id",
+            &[&sub, &amount],
+        )
+        .await?;
+
+    if debited.is_none() {
+        return Ok(None);
+    }
+
+    transaction
+        .execute(
+            "INSERT INTO transactions(kind, amount, currency, reference, created, user_id) SELECT $2, $3, currencies.acronym, $4, $5, users.id FROM users INNER JOIN currencies ON currencies.id = users.currency_id WHERE users.sub = $1",
+            &[&sub, &BalanceUpdateKind::Wager.to_string(), &-amount, &reference, &Local::now()],
+        )
+        .await?;
+
+    get_wallet(sub, transaction).await
+}
+
+/// Credits the win of a wager and books it in the ledger, returns the wallet after the credit.
+pub(super) async fn credit_win(
+    sub: &str,
+    amount: f64,
+    reference: &str,
+    transaction: &Transaction<'_>,
+) -> Result<Option<Wallet>, tokio_postgres::Error> {
+    transaction
+        .execute(
+            "UPDATE users SET balance = balance + $2 WHERE sub = $1",
+            &[&sub, &amount],
+        )
+        .await?;
+
+    transaction
+        .execute(
+            "INSERT INTO transactions(kind, amount, currency, reference, created, user_id) SELECT $2, $3, currencies.acronym, $4, $5, users.id FROM users INNER JOIN currencies ON currencies.id = users.currency_id WHERE users.sub = $1",
+            &[&sub, &BalanceUpdateKind::Win.to_string(), &amount, &reference, &Local::now()],
+        )
+        .await?;
+
+    get_wallet(sub, transaction).await
+}
+
+pub(super) async fn get_wallet<C: GenericClient>(
+    sub: &str,
+    db_client: &C,
+) -> Result<Option<Wallet>, tokio_postgres::Error> {
+    let r: Option<Row> = db_client
+        .query_opt(
+            "SELECT users.balance, currencies.acronym FROM users INNER JOIN currencies ON currencies.id = users.currency_id WHERE users.sub = $1",
+            &[&sub],
+        )
+        .await?;
+
+    Ok(r.map(|r| Wallet {
+        balance: r.get("balance"),
+        currency: r.get("acronym"),
+    }))
+}
+
+/// Sum of the signed transaction amounts of the given kinds since the given moment.
+pub(crate) async fn sum_transactions<C: GenericClient>(
+    sub: &str,
+    kinds: &[String],
+    since: DateTime<Local>,
+    db_client: &C,
+) -> Result<f64, tokio_postgres::Error> {
+    let r: Row = db_client
+        .query_one(
+            "SELECT COALESCE(SUM(amount), 0) AS total FROM transactions INNER JOIN users ON users.id = transactions.user_id WHERE sub = $1 AND kind = ANY($2) AND created > $3",
+            &[&sub, &kinds, &since],
+        )
+        .await?;
+
+    Ok(r.get("total"))
+}
+
+pub(crate) async fn delete_transactions(
+    sub: &str,
+    transaction: &Transaction<'_>,
+) -> Result<Option<Row>, tokio_postgres::Error> {
+    transaction.query_opt(
+        "WITH userId AS (SELECT id FROM users WHERE sub = $1) DELETE FROM transactions WHERE user_id = (SELECT id FROM userId)",
+        &[&sub],
+    )
+    .await
+}
@@ -0,0 +1,619 @@
+mod model;
+
+pub(crate) mod db;
+
+use self::{
+    db::{
+        due_session_reminders, get_limits, insert_limit, mark_session_reminded,
+        promote_pending_limits, schedule_limit, update_limit,
+    },
+    model::{Limit, LimitKind, LimitParams, LimitPeriod, RemoveLimitParams, SessionReminder},
+};
+
+use crate::{
+    ledger::db::sum_transactions,
+    mail::queue_user_mail,
+    model::ServiceToken,
+    notification::{notify, Notification},
+    self_exclusion::db::active_self_exclusion,
+    StateParams, LIMIT_COOLING_OFF_HOURS,
+};
+
+use askama::Template;
+use axum::{extract::State, response::Html, Form, Json};
+use bb8_postgres::{bb8::Pool, PostgresConnectionManager};
+use bb8_redis::RedisConnectionManager;
+use chrono::{DateTime, Duration, Local};
+use leprecon::{
+    auth::AuthParam,
+    broker::BalanceUpdateKind,
+    i18n::{translate_with, FluentArgs},
+    limit::{LimitCheck, LimitCheckKind, LimitCheckParams},
+    template::{self, LimitChangeMail, Snackbar},
+    utils::{
+        extract::{extract_conn_from_pool, ValidForm},
+        PostgresConn,
+    },
+};
+use reqwest::StatusCode;
+use std::error::Error;
+use tokio_postgres::{GenericClient, NoTls};
+use tracing::{error, info};
+
+pub(super) async fn user_limits(
+    State(state): State<StateParams>,
+    Form(auth_param): Form<AuthParam>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    if auth_param.sub.is_empty() {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Html(snackbar.render().unwrap()),
+        );
+    };
+
+    let postgres_conn: PostgresConn = match extract_conn_from_pool(&state.2, &mut snackbar).await {
+        Ok(v) => v,
+        Err(e) => return e,
+    };
+
+    let limits: Vec<Limit> = match current_limits(&auth_param.sub, &*postgres_conn).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Could not fetch limits: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    let limits_template: template::Limits = template::Limits {
+        limits: limits
+            .into_iter()
+            .map(|l| template::Limit {
+                kind: l.kind.to_string(),
+                period: l.period.to_string(),
+                amount: l.amount,
+                pending: l.pending_from.map(|from| {
+                    let mut args: FluentArgs = FluentArgs::new();
+                    args.set("time", from.format("%Y-%m-%d %H:%M").to_string());
+                    match l.pending_amount {
+                        Some(v) => {
+                            args.set("amount", v);
+                            translate_with("limit-pending-change", &args)
+                        }
+                        None => translate_with("limit-pending-removal", &args),
+                    }
+                }),
+            })
+            .collect(),
+    };
+
+    (StatusCode::OK, Html(limits_template.render().unwrap()))
+}
+
+/// Sets a limit, decreases apply right away while increases wait for the cooling-off period.
+pub(super) async fn set_limit(
+    State(state): State<StateParams>,
+    ValidForm(params): ValidForm<LimitParams>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    let postgres_conn: PostgresConn = match extract_conn_from_pool(&state.2, &mut snackbar).await {
+        Ok(v) => v,
+        Err(e) => return e,
+    };
+
+    let limits: Vec<Limit> = match current_limits(&params.sub, &*postgres_conn).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Could not fetch limits: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    let existing: Option<&Limit> = limits
+        .iter()
+        .find(|l| l.kind == params.kind && l.period == params.period);
+
+    let from: DateTime<Local> = cooling_off_end();
+    let time: String = from.format("%Y-%m-%d %H:%M").to_string();
+
+    let message_id: &str;
+    let result: Result<u64, tokio_postgres::Error> = match existing {
+        None => {
+            message_id = "limit-set";
+            insert_limit(
+                &params.sub,
+                params.kind,
+                params.period,
+                params.amount,
+                &postgres_conn,
+            )
+            .await
+        }
+        Some(l) if params.amount <= l.amount => {
+            message_id = "limit-decreased";
+            update_limit(
+                &params.sub,
+                params.kind,
+                params.period,
+                params.amount,
+                &postgres_conn,
+            )
+            .await
+        }
+        Some(_) => {
+            message_id = "limit-increases-on";
+            schedule_limit(
+                &params.sub,
+                params.kind,
+                params.period,
+                Some(params.amount),
+                from,
+                &postgres_conn,
+            )
+            .await
+        }
+    };
+
+    if let Err(e) = result {
+        error!("Cannot set limit: {:?}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Html(snackbar.render().unwrap()),
+        );
+    }
+
+    let mut args: FluentArgs = FluentArgs::new();
+    args.set("time", time.as_str());
+    let message: String = translate_with(message_id, &args);
+
+    confirm_limit_change(
+        &params.sub,
+        params.kind,
+        params.period,
+        &message,
+        &postgres_conn,
+    )
+    .await;
+    notify(
+        &params.sub,
+        Notification::new("limit-changed-title", message_id).arg("time", &time),
+        &state.3,
+    )
+    .await;
+
+    snackbar.title = "success";
+    snackbar.message = &message;
+    snackbar.color = "green";
+
+    (StatusCode::OK, Html(snackbar.render().unwrap()))
+}
+
+/// Removes a limit once the cooling-off period has passed.
+pub(super) async fn remove_limit(
+    State(state): State<StateParams>,
+    ValidForm(params): ValidForm<RemoveLimitParams>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    let postgres_conn: PostgresConn = match extract_conn_from_pool(&state.2, &mut snackbar).await {
+        Ok(v) => v,
+        Err(e) => return e,
+    };
+
+    if let Err(e) = promote_pending_limits(&params.sub, &*postgres_conn).await {
+        error!("Could not apply pending limits: {:?}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Html(snackbar.render().unwrap()),
+        );
+    }
+
+    let from: DateTime<Local> = cooling_off_end();
+    match schedule_limit(
+        &params.sub,
+        params.kind,
+        params.period,
+        None,
+        from,
+        &postgres_conn,
+    )
+    .await
+    {
+        Ok(0) => {
+            snackbar.message = "limit-missing";
+            return (StatusCode::BAD_REQUEST, Html(snackbar.render().unwrap()));
+        }
+        Ok(_) => {}
+        Err(e) => {
+            error!("Cannot remove limit: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    let time: String = from.format("%Y-%m-%d %H:%M").to_string();
+    let mut args: FluentArgs = FluentArgs::new();
+    args.set("time", time.as_str());
+    let message: String = translate_with("limit-removed-on", &args);
+    confirm_limit_change(
+        &params.sub,
+        params.kind,
+        params.period,
+        &message,
+        &postgres_conn,
+    )
+    .await;
+    notify(
+        &params.sub,
+        Notification::new("limit-changed-title", "limit-removed-on").arg("time", &time),
+        &state.3,
+    )
+    .await;
+
+    snackbar.title = "success";
+    snackbar.message = &message;
+    snackbar.color = "green";
+
+    (StatusCode::OK, Html(snackbar.render().unwrap()))
+}
+
+/// Mails the user, so changes they did not make themselves stand out.
+async fn confirm_limit_change(
+    sub: &str,
+    kind: LimitKind,
+    period: LimitPeriod,
+    change: &str,
+    postgres_conn: &PostgresConn<'_>,
+) {
+    let mail: LimitChangeMail = LimitChangeMail {
+        kind: kind.to_string(),
+        period: period.to_string(),
+        change: change.to_owned(),
+    };
+
+    if let Err(e) = queue_user_mail(sub, &mail, &**postgres_conn).await {
+        error!("Could not queue limit change confirmation: {:?}", e);
+    }
+}
+
+/// Minutes without a wager after which a session is over.
+const SESSION_GAP_MINUTES: f64 = 30.0;
+
+/// Reminds players how long they have been playing, each time their session time reminder passes.
+pub(super) async fn run_session_reminders(
+    postgres_pool: Pool<PostgresConnectionManager<NoTls>>,
+    redis_pool: Pool<RedisConnectionManager>,
+) {
+    let mut interval: tokio::time::Interval =
+        tokio::time::interval(std::time::Duration::from_secs(60));
+
+    loop {
+        interval.tick().await;
+
+        let postgres_conn: PostgresConn = match postgres_pool.get().await {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Cannot get connection from pool: {:?}", e);
+                continue;
+            }
+        };
+
+        let reminders: Vec<SessionReminder> = match due_session_reminders(
+            &BalanceUpdateKind::Wager.to_string(),
+            SESSION_GAP_MINUTES,
+            &postgres_conn,
+        )
+        .await
+        {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Could not get due session reminders: {:?}", e);
+                continue;
+            }
+        };
+
+        for reminder in reminders {
+            // Marked first, a failing notification must not repeat every minute
+            if let Err(e) = mark_session_reminded(&reminder.sub, &postgres_conn).await {
+                error!(
+                    "Could not mark session reminder of {}: {:?}",
+                    reminder.sub, e
+                );
+                continue;
+            }
+
+            notify(
+                &reminder.sub,
+                Notification::new("session-time-title", "session-time-reminder")
+                    .arg("minutes", reminder.minutes),
+                &redis_pool,
+            )
+            .await;
+            info!("Sent session time reminder to {}", reminder.sub);
+        }
+    }
+}
+
+/// Tells other services whether a deposit or wager is allowed for the user.
+pub(super) async fn check_limit(
+    _: ServiceToken,
+    State(state): State<StateParams>,
+    Form(params): Form<LimitCheckParams>,
+) -> (StatusCode, Json<LimitCheck>) {
+    if params.sub.is_empty() {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(LimitCheck::refused("sub is required")),
+        );
+    }
+
+    let postgres_conn: PostgresConn = match state.2.get().await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Cannot get connection from pool: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(LimitCheck::refused("could-not-process-request")),
+            );
+        }
+    };
+
+    match limit_reached(&params, &*postgres_conn).await {
+        Ok(v) => (StatusCode::OK, Json(v)),
+        Err(e) => {
+            error!("Could not check limits: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(LimitCheck::refused("could-not-process-request")),
+            )
+        }
+    }
+}
+
+async fn current_limits<C: GenericClient>(
+    sub: &str,
+    db_client: &C,
+) -> Result<Vec<Limit>, Box<dyn Error>> {
+    promote_pending_limits(sub, db_client).await?;
+    get_limits(sub, db_client).await
+}
+
+fn cooling_off_end() -> DateTime<Local> {
+    Local::now() + Duration::hours(*LIMIT_COOLING_OFF_HOURS.get().unwrap())
+}
+
+/// Whether the money movement is allowed, with the reason when it is not.
+pub(crate) async fn limit_reached<C: GenericClient>(
+    params: &LimitCheckParams,
+    db_client: &C,
+) -> Result<LimitCheck, Box<dyn Error>> {
+    if let Some(exclusion) = active_self_exclusion(&params.sub, db_client).await? {
+        return Ok(match exclusion.ends {
+            Some(v) => LimitCheck::refused("limit-self-excluded-until")
+                .arg("time", v.format("%Y-%m-%d %H:%M")),
+            None => LimitCheck::refused("limit-self-excluded"),
+        });
+    }
+
+    let (limit_kind, transaction_kinds): (LimitKind, Vec<String>) = match params.kind {
+        LimitCheckKind::Deposit => (
+            LimitKind::Deposit,
+            vec![BalanceUpdateKind::Deposit.to_string()],
+        ),
+        LimitCheckKind::Wager => (
+            LimitKind::Loss,
+            vec![
+                BalanceUpdateKind::Wager.to_string(),
+                BalanceUpdateKind::Win.to_string(),
+            ],
+        ),
+    };
+
+    let limits: Vec<Limit> = current_limits(&params.sub, db_client).await?;
+    for limit in limits {
+        let duration: Duration = match (limit.kind == limit_kind, limit.period.duration()) {
+            (true, Some(v)) => v,
+            _ => continue,
+        };
+
+        let total: f64 = sum_transactions(
+            &params.sub,
+            &transaction_kinds,
+            Local::now() - duration,
+            db_client,
+        )
+        .await?;
+
+        // Deposits are positive, losses are the negated sum of wagers and wins.
+        let used: f64 = match limit_kind {
+            LimitKind::Loss => -total,
+            _ => total,
+        };
+
+        if used + params.amount > limit.amount {
+            return Ok(LimitCheck::refused("limit-reached")
+                .arg("period", limit.period)
+                .arg("kind", limit.kind)
+                .arg("limit", limit.amount));
+        }
+    }
+
+    Ok(LimitCheck::allowed())
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{body::Body, http::Request};
+    use chrono::{DateTime, Local};
+    use reqwest::{header, Method, StatusCode};
+    use tokio_postgres::{NoTls, Row};
+    use tower::ServiceExt;
+
+    use crate::{
+        fixture::{assert_body_contains, initialize, seed_database, service_bearer},
+        ACCOUNT_CONN,
+    };
+
+    // Get limits
+    #[tokio::test]
+    async fn test_no_params_provided_get_limits() {
+        let app: axum::Router = initialize().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/user/limits")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_body_contains(response, &["Could not process request"]).await;
+    }
+
+    // Set limit
+    #[tokio::test]
+    async fn test_set_session_limit_with_invalid_period() {
+        let app: axum::Router = initialize().await;
+        seed_database().await;
+
+        let params: String = String::from("sub=auth0|0003&kind=SessionTime&period=Daily&amount=60");
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::PUT)
+                    .uri("/account/user/limits")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(params)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_body_contains(response, &["period: must be Session"]).await;
+    }
+
+    async fn put_limit(app: &axum::Router, amount: f64) -> axum::http::Response<Body> {
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::PUT)
+                    .uri("/account/user/limits")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(format!(
+                        "sub=auth0|0003&kind=Deposit&period=Weekly&amount={amount}"
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    /// Amount, pending amount and pending start of the weekly deposit limit of auth0|0003.
+    async fn stored_limit() -> (f64, Option<f64>, Option<DateTime<Local>>) {
+        let (db_client, connection) = tokio_postgres::connect(ACCOUNT_CONN.get().unwrap(), NoTls)
+            .await
+            .unwrap();
+        tokio::spawn(connection);
+
+        let r: Row = db_client
+            .query_one(
+                "SELECT amount, pending_amount, pending_from FROM limits INNER JOIN users ON users.id = limits.user_id WHERE sub = 'auth0|0003' AND kind = 'Deposit' AND period = 'Weekly'",
+                &[],
+            )
+            .await
+            .unwrap();
+
+        (
+            r.get("amount"),
+            r.get("pending_amount"),
+            r.get("pending_from"),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_set_limit() {
+        let app: axum::Router = initialize().await;
+        seed_database().await;
+
+        let response: axum::http::Response<Body> = put_limit(&app, 100.0).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_body_contains(response, &["Limit set"]).await;
+        assert_eq!(stored_limit().await, (100.0, None, None));
+
+        // Decreases apply right away
+        let response: axum::http::Response<Body> = put_limit(&app, 50.0).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_body_contains(response, &["Limit decreased"]).await;
+        assert_eq!(stored_limit().await, (50.0, None, None));
+
+        // Increases wait for the cooling-off period
+        let response: axum::http::Response<Body> = put_limit(&app, 200.0).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_body_contains(response, &["Limit increases on"]).await;
+
+        let (amount, pending_amount, pending_from) = stored_limit().await;
+        assert_eq!(amount, 50.0);
+        assert_eq!(pending_amount, Some(200.0));
+        assert!(pending_from.is_some_and(|v| v > Local::now()));
+    }
+
+    // Remove limit
+    #[tokio::test]
+    async fn test_remove_non_existing_limit() {
+        let app: axum::Router = initialize().await;
+        seed_database().await;
+
+        let params: String = String::from("sub=auth0|0002&kind=Loss&period=Monthly");
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::DELETE)
+                    .uri("/account/user/limits")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(params)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_body_contains(response, &["No limit set"]).await;
+    }
+
+    // Check limit
+    #[tokio::test]
+    async fn test_check_limit_without_limits() {
+        let app: axum::Router = initialize().await;
+        seed_database().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/user/limits/check?sub=auth0|0002&kind=Wager&amount=10")
+                    .header(header::AUTHORIZATION, service_bearer())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_body_contains(response, &["\"allowed\":true"]).await;
+    }
+}
@@ -0,0 +1,149 @@
+use super::model::{Limit, LimitKind, LimitPeriod, SessionReminder};
+
+use chrono::{DateTime, Local};
+use leprecon::utils::PostgresConn;
+use std::{error::Error, str::FromStr};
+use tokio_postgres::{GenericClient, Row, Transaction};
+
+/// Applies scheduled limit changes of which the cooling-off period has passed.
+pub(super) async fn promote_pending_limits<C: GenericClient>(
+    sub: &str,
+    db_client: &C,
+) -> Result<(), tokio_postgres::Error> {
+    db_client
+        .execute(
+            "WITH userId AS (SELECT id FROM users WHERE sub = $1) DELETE FROM limits WHERE user_id = (SELECT id FROM userId) AND pending_from <= now() AND pending_amount IS NULL",
+            &[&sub],
+        )
+        .await?;
+
+    db_client
+        .execute(
+            "WITH userId AS (SELECT id FROM users WHERE sub = $1) UPDATE limits SET amount = pending_amount, pending_amount = NULL, pending_from = NULL, updated = now() WHERE user_id = (SELECT id FROM userId) AND pending_from <= now()",
+            &[&sub],
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub(super) async fn get_limits<C: GenericClient>(
+    sub: &str,
+    db_client: &C,
+) -> Result<Vec<Limit>, Box<dyn Error>> {
+    let rows: Vec<Row> = db_client
+        .query(
+            "SELECT limits.* FROM limits INNER JOIN users ON users.id = limits.user_id WHERE sub = $1 ORDER BY kind, period",
+            &[&sub],
+        )
+        .await?;
+
+    let mut limits: Vec<Limit> = vec![];
+    for r in rows {
+        limits.push(Limit {
+            kind: LimitKind::from_str(r.get("kind"))?,
+            period: LimitPeriod::from_str(r.get("period"))?,
+            amount: r.get("amount"),
+            pending_amount: r.get("pending_amount"),
+            pending_from: r.get("pending_from"),
+        });
+    }
+
+    Ok(limits)
+}
+
+pub(super) async fn insert_limit(
+    sub: &str,
+    kind: LimitKind,
+    period: LimitPeriod,
+    amount: f64,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    db_client
+        .execute(
+            "WITH userId AS (SELECT id FROM users WHERE sub = $1) INSERT INTO limits(kind, period, amount, updated, user_id) VALUES($2, $3, $4, now(), (SELECT id FROM userId))",
+            &[&sub, &kind.to_string(), &period.to_string(), &amount],
+        )
+        .await
+}
+
+/// Changes the limit right away, and drops any scheduled change.
+pub(super) async fn update_limit(
+    sub: &str,
+    kind: LimitKind,
+    period: LimitPeriod,
+    amount: f64,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    db_client
+        .execute(
+            "WITH userId AS (SELECT id FROM users WHERE sub = $1) UPDATE limits SET amount = $4, pending_amount = NULL, pending_from = NULL, updated = now() WHERE user_id = (SELECT id FROM userId) AND kind = $2 AND period = $3",
+            &[&sub, &kind.to_string(), &period.to_string(), &amount],
+        )
+        .await
+}
+
+/// Schedules a change of the limit, a pending amount of `None` removes the limit.
+pub(super) async fn schedule_limit(
+    sub: &str,
+    kind: LimitKind,
+    period: LimitPeriod,
+    pending_amount: Option<f64>,
+    pending_from: DateTime<Local>,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    db_client
+        .execute(
+            "WITH userId AS (SELECT id FROM users WHERE sub = $1) UPDATE limits SET pending_amount = $4, pending_from = $5, updated = now() WHERE user_id = (SELECT id FROM userId) AND kind = $2 AND period = $3",
+            &[&sub, &kind.to_string(), &period.to_string(), &pending_amount, &pending_from],
+        )
+        .await
+}
+
+/// Players in a session for longer than their reminder, since the session or the last reminder.
+///
+/// A session is a run of wagers without a gap longer than `gap_minutes`, it is over once the
+/// last wager is that long ago.
+pub(super) async fn due_session_reminders(
+    wager_kind: &str,
+    gap_minutes: f64,
+    db_client: &PostgresConn<'_>,
+) -> Result<Vec<SessionReminder>, tokio_postgres::Error> {
+    let rows: Vec<Row> = db_client
+        .query(
+            "WITH wagers AS (SELECT transactions.user_id, transactions.created, lag(transactions.created) OVER (PARTITION BY transactions.user_id ORDER BY transactions.created) AS previous FROM transactions INNER JOIN limits ON limits.user_id = transactions.user_id AND limits.kind = 'SessionTime' WHERE transactions.kind = $1 AND transactions.created > now() - interval '1 day'), sessions AS (SELECT user_id, max(created) FILTER (WHERE previous IS NULL OR created - previous > $2 * interval '1 minute') AS started, max(created) AS last FROM wagers GROUP BY user_id) SELECT users.sub, limits.amount FROM sessions INNER JOIN limits ON limits.user_id = sessions.user_id AND limits.kind = 'SessionTime' INNER JOIN users ON users.id = sessions.user_id WHERE users.deleted IS NULL AND sessions.last > now() - $2 * interval '1 minute' AND GREATEST(sessions.started, limits.reminded) <= now() - limits.amount * interval '1 minute'",
+            &[&wager_kind, &gap_minutes],
+        )
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| SessionReminder {
+            sub: r.get("sub"),
+            minutes: r.get("amount"),
+        })
+        .collect())
+}
+
+pub(super) async fn mark_session_reminded(
+    sub: &str,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    db_client
+        .execute(
+            "WITH userId AS (SELECT id FROM users WHERE sub = $1) UPDATE limits SET reminded = now() WHERE user_id = (SELECT id FROM userId) AND kind = 'SessionTime'",
+            &[&sub],
+        )
+        .await
+}
+
+pub(crate) async fn delete_limits(
+    sub: &str,
+    transaction: &Transaction<'_>,
+) -> Result<Option<Row>, tokio_postgres::Error> {
+    transaction.query_opt(
+        "WITH userId AS (SELECT id FROM users WHERE sub = $1) DELETE FROM limits WHERE user_id = (SELECT id FROM userId)",
+        &[&sub],
+    )
+    .await
+}
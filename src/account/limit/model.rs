@@ -0,0 +1,151 @@
+use chrono::{DateTime, Duration, Local};
+use leprecon::utils::validate::{Validate, ValidationError};
+use serde::Deserialize;
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub(crate) enum LimitKind {
+    Deposit,
+    Loss,
+    SessionTime,
+}
+
+impl fmt::Display for LimitKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub(crate) enum LimitPeriod {
+    Session,
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl fmt::Display for LimitPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl LimitPeriod {
+    /// Rolling window the limit applies to, sessions have no window.
+    pub(super) fn duration(&self) -> Option<Duration> {
+        match self {
+            LimitPeriod::Session => None,
+            LimitPeriod::Daily => Some(Duration::days(1)),
+            LimitPeriod::Weekly => Some(Duration::weeks(1)),
+            LimitPeriod::Monthly => Some(Duration::days(30)),
+        }
+    }
+}
+
+pub(super) struct Limit {
+    pub kind: LimitKind,
+    pub period: LimitPeriod,
+    pub amount: f64,
+    pub pending_amount: Option<f64>,
+    pub pending_from: Option<DateTime<Local>>,
+}
+
+/// Player whose session ran longer than their session time reminder.
+pub(super) struct SessionReminder {
+    pub sub: String,
+    pub minutes: f64,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct LimitParams {
+    #[serde(default)]
+    pub sub: String,
+    pub kind: LimitKind,
+    pub period: LimitPeriod,
+    pub amount: f64,
+}
+
+impl Validate for LimitParams {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.sub.is_empty() {
+            return Err(ValidationError::new("sub", "is required"));
+        }
+
+        if !self.amount.is_finite() || self.amount <= 0.0 {
+            return Err(ValidationError::new("amount", "must be positive"));
+        }
+
+        match (self.kind, self.period) {
+            (LimitKind::SessionTime, LimitPeriod::Session) => Ok(()),
+            (LimitKind::SessionTime, _) => Err(ValidationError::new(
+                "period",
+                "must be Session for session time reminders",
+            )),
+            (_, LimitPeriod::Session) => Err(ValidationError::new(
+                "period",
+                "must be Daily, Weekly or Monthly",
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct RemoveLimitParams {
+    #[serde(default)]
+    pub sub: String,
+    pub kind: LimitKind,
+    pub period: LimitPeriod,
+}
+
+impl Validate for RemoveLimitParams {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.sub.is_empty() {
+            return Err(ValidationError::new("sub", "is required"));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct ParseLimitError;
+
+impl Error for ParseLimitError {}
+
+impl Display for ParseLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ParseLimitError")
+    }
+}
+
+impl FromStr for LimitKind {
+    type Err = ParseLimitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Deposit" => Ok(LimitKind::Deposit),
+            "Loss" => Ok(LimitKind::Loss),
+            "SessionTime" => Ok(LimitKind::SessionTime),
+            _ => Err(ParseLimitError),
+        }
+    }
+}
+
+impl FromStr for LimitPeriod {
+    type Err = ParseLimitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Session" => Ok(LimitPeriod::Session),
+            "Daily" => Ok(LimitPeriod::Daily),
+            "Weekly" => Ok(LimitPeriod::Weekly),
+            "Monthly" => Ok(LimitPeriod::Monthly),
+            _ => Err(ParseLimitError),
+        }
+    }
+}
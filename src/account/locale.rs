@@ -0,0 +1,198 @@
+mod db;
+mod model;
+
+use self::{
+    db::{get_locale, set_locale},
+    model::LocaleParams,
+};
+
+use crate::StateParams;
+
+use askama::Template;
+use axum::{
+    extract::State,
+    http::header,
+    response::{Html, IntoResponse, Response},
+    Form,
+};
+use leprecon::{
+    auth::AuthParam,
+    i18n::{current_locale, in_locale, locale_cookie, Locale},
+    template::{self, Snackbar},
+    utils::{
+        extract::{extract_conn_from_pool, ValidForm},
+        PostgresConn,
+    },
+};
+use reqwest::StatusCode;
+use std::str::FromStr;
+use tracing::error;
+
+/// Language chosen by the user, the cookie is set again so the choice follows the user to other
+/// browsers.
+pub(super) async fn user_locale(
+    State(state): State<StateParams>,
+    Form(auth_param): Form<AuthParam>,
+) -> Response {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    if auth_param.sub.is_empty() {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Html(snackbar.render().unwrap()),
+        )
+            .into_response();
+    };
+
+    let postgres_conn: PostgresConn = match extract_conn_from_pool(&state.2, &mut snackbar).await {
+        Ok(v) => v,
+        Err(e) => return e.into_response(),
+    };
+
+    let preference: Option<Locale> = match get_locale(&auth_param.sub, &postgres_conn).await {
+        Ok(v) => v.and_then(|v| Locale::from_str(&v).ok()),
+        Err(e) => {
+            error!("Could not get locale: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            )
+                .into_response();
+        }
+    };
+
+    let locale_template: template::LocalePreference = template::LocalePreference {
+        locale: preference.unwrap_or(current_locale()).to_string(),
+    };
+    let body: Html<String> = Html(locale_template.render().unwrap());
+
+    match preference {
+        Some(v) => (
+            StatusCode::OK,
+            [(header::SET_COOKIE, locale_cookie(v))],
+            body,
+        )
+            .into_response(),
+        None => (StatusCode::OK, body).into_response(),
+    }
+}
+
+/// Stores the language of the user, and answers in it.
+pub(super) async fn set_user_locale(
+    State(state): State<StateParams>,
+    ValidForm(params): ValidForm<LocaleParams>,
+) -> Response {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    let postgres_conn: PostgresConn = match extract_conn_from_pool(&state.2, &mut snackbar).await {
+        Ok(v) => v,
+        Err(e) => return e.into_response(),
+    };
+
+    let locale: Locale = params.locale();
+
+    match set_locale(&params.sub, locale.code(), &postgres_conn).await {
+        Ok(0) => {
+            snackbar.message = "user-missing";
+            return (StatusCode::NOT_FOUND, Html(snackbar.render().unwrap())).into_response();
+        }
+        Ok(_) => (),
+        Err(e) => {
+            error!("Could not set locale: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            )
+                .into_response();
+        }
+    }
+
+    snackbar.title = "success";
+    snackbar.message = "locale-updated";
+    snackbar.color = "green";
+
+    (
+        StatusCode::OK,
+        [(header::SET_COOKIE, locale_cookie(locale))],
+        Html(in_locale(locale, || snackbar.render().unwrap())),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{body::Body, http::Request};
+    use reqwest::{header, Method, StatusCode};
+    use tower::ServiceExt;
+
+    use crate::fixture::{assert_body_contains, initialize, seed_database};
+
+    #[tokio::test]
+    async fn test_no_params_provided_get_locale() {
+        let app: axum::Router = initialize().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/user/locale")
+                    .header(header::ACCEPT_LANGUAGE, "nl-NL,nl;q=0.9,en;q=0.8")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_body_contains(response, &["Kon het verzoek niet verwerken"]).await;
+    }
+
+    #[tokio::test]
+    async fn test_unknown_locale() {
+        let app: axum::Router = initialize().await;
+
+        let params: String = String::from("sub=auth0|0002&locale=xx");
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::PUT)
+                    .uri("/account/user/locale")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(params)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_body_contains(response, &["locale: is unknown"]).await;
+    }
+
+    #[tokio::test]
+    async fn test_set_locale() {
+        let app: axum::Router = initialize().await;
+        seed_database().await;
+
+        let params: String = String::from("sub=auth0|0002&locale=nl");
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::PUT)
+                    .uri("/account/user/locale")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(params)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response
+            .headers()
+            .get(header::SET_COOKIE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("locale=nl")));
+        assert_body_contains(response, &["Taal bijgewerkt"]).await;
+    }
+}
@@ -0,0 +1,27 @@
+use leprecon::utils::PostgresConn;
+use tokio_postgres::Row;
+
+/// Locale chosen by the user, `None` when the user never chose one or does not exist.
+pub(super) async fn get_locale(
+    sub: &str,
+    db_client: &PostgresConn<'_>,
+) -> Result<Option<String>, tokio_postgres::Error> {
+    let r: Option<Row> = db_client
+        .query_opt("SELECT locale FROM users WHERE sub = $1", &[&sub])
+        .await?;
+
+    Ok(r.and_then(|r| r.get("locale")))
+}
+
+pub(super) async fn set_locale(
+    sub: &str,
+    locale: &str,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    db_client
+        .execute(
+            "UPDATE users SET locale = $2 WHERE sub = $1",
+            &[&sub, &locale],
+        )
+        .await
+}
@@ -0,0 +1,35 @@
+use leprecon::{
+    i18n::Locale,
+    utils::validate::{Validate, ValidationError},
+};
+use serde::Deserialize;
+use std::str::FromStr;
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct LocaleParams {
+    #[serde(default)]
+    pub sub: String,
+    #[serde(default)]
+    pub locale: String,
+}
+
+impl LocaleParams {
+    /// Only call on validated params.
+    pub(super) fn locale(&self) -> Locale {
+        Locale::from_str(&self.locale).unwrap()
+    }
+}
+
+impl Validate for LocaleParams {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.sub.is_empty() {
+            return Err(ValidationError::new("sub", "is required"));
+        }
+
+        if Locale::from_str(&self.locale).is_err() {
+            return Err(ValidationError::new("locale", "is unknown"));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,217 @@
+mod model;
+
+pub(crate) mod db;
+
+pub(crate) use model::Mail;
+
+use self::{
+    db::{
+        claim_due_mails, get_email, insert_mail, mark_mail_attempt_failed, mark_mail_sent,
+        purge_handled_mails,
+    },
+    model::QueuedMail,
+};
+
+use crate::{
+    MAIL_FROM, MAIL_MAX_ATTEMPTS, SMTP_HOST, SMTP_PASSWORD, SMTP_PORT, SMTP_TLS, SMTP_USERNAME,
+};
+
+use bb8_postgres::{bb8::Pool, PostgresConnectionManager};
+use chrono::{DateTime, Duration, Local};
+use leprecon::utils::PostgresConn;
+use lettre::{
+    message::MultiPart, transport::smtp::authentication::Credentials, AsyncSmtpTransport,
+    AsyncTransport, Message, Tokio1Executor,
+};
+use std::error::Error;
+use tokio_postgres::{GenericClient, NoTls};
+use tracing::{debug, error, info};
+
+/// Renders the mail and queues it for the recipient.
+pub(crate) async fn queue_mail<C: GenericClient>(
+    recipient: &str,
+    mail: &impl Mail,
+    db_client: &C,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    insert_mail(
+        recipient,
+        &mail.subject(),
+        &mail.html()?,
+        &mail.text()?,
+        db_client,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Queues the mail for the user, users of which the email is not known yet are skipped.
+pub(crate) async fn queue_user_mail<C: GenericClient>(
+    sub: &str,
+    mail: &impl Mail,
+    db_client: &C,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    let recipient: String = match get_email(sub, db_client).await? {
+        Some(v) => v,
+        None => {
+            debug!("No email known for {}, skipping mail", sub);
+            return Ok(false);
+        }
+    };
+
+    queue_mail(&recipient, mail, db_client).await?;
+
+    Ok(true)
+}
+
+/// Transport to the configured SMTP server, without TLS for a local sink.
+pub(super) fn smtp_transport(
+) -> Result<AsyncSmtpTransport<Tokio1Executor>, lettre::transport::smtp::Error> {
+    let host: &str = SMTP_HOST.get().unwrap();
+
+    let mut builder = match SMTP_TLS.get().unwrap() {
+        true => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+        false => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+    }
+    .port(*SMTP_PORT.get().unwrap());
+
+    let username: &str = SMTP_USERNAME.get().unwrap();
+    if !username.is_empty() {
+        builder = builder.credentials(Credentials::new(
+            username.to_owned(),
+            SMTP_PASSWORD.get().unwrap().to_owned(),
+        ));
+    }
+
+    Ok(builder.build())
+}
+
+/// Sends the queued mails, failed sends are retried with an increasing delay.
+pub(super) async fn run_mail_queue(
+    postgres_pool: Pool<PostgresConnectionManager<NoTls>>,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+) {
+    let mut interval: tokio::time::Interval =
+        tokio::time::interval(std::time::Duration::from_secs(10));
+
+    loop {
+        interval.tick().await;
+
+        let postgres_conn: PostgresConn = match postgres_pool.get().await {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Cannot get connection from pool: {:?}", e);
+                continue;
+            }
+        };
+
+        // Mails of a worker which died are picked up again after the lease
+        let lease: DateTime<Local> = Local::now() + Duration::minutes(5);
+        let mails: Vec<QueuedMail> = match claim_due_mails(lease, 50, &postgres_conn).await {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Could not get queued mails: {:?}", e);
+                continue;
+            }
+        };
+
+        for mail in mails {
+            let result: Result<(), String> = send_mail(&transport, &mail)
+                .await
+                .map_err(|e| e.to_string());
+
+            let updated: Result<u64, tokio_postgres::Error> = match result {
+                Ok(_) => {
+                    info!("Sent mail {}", mail.id);
+                    mark_mail_sent(mail.id, &postgres_conn).await
+                }
+                Err(e) => {
+                    let next_attempt: Option<DateTime<Local>> = next_attempt(mail.attempts + 1);
+                    match next_attempt {
+                        Some(v) => {
+                            error!("Could not send mail {}, retrying at {}: {}", mail.id, v, e)
+                        }
+                        None => error!("Could not send mail {}, giving up: {}", mail.id, e),
+                    }
+                    mark_mail_attempt_failed(mail.id, &e, next_attempt, &postgres_conn).await
+                }
+            };
+
+            if let Err(e) = updated {
+                error!("Could not update mail {}: {:?}", mail.id, e);
+            }
+        }
+
+        match purge_handled_mails(Local::now() - Duration::days(7), &postgres_conn).await {
+            Ok(0) => (),
+            Ok(v) => info!("Purged {} handled mails", v),
+            Err(e) => error!("Could not purge handled mails: {:?}", e),
+        }
+    }
+}
+
+async fn send_mail(
+    transport: &AsyncSmtpTransport<Tokio1Executor>,
+    mail: &QueuedMail,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let message: Message = Message::builder()
+        .from(MAIL_FROM.get().unwrap().parse()?)
+        .to(mail.recipient.parse()?)
+        .subject(&mail.subject)
+        .multipart(MultiPart::alternative_plain_html(
+            mail.text.clone(),
+            mail.html.clone(),
+        ))?;
+
+    transport.send(message).await?;
+
+    Ok(())
+}
+
+/// Doubles the delay after every attempt, starting at a minute.
+fn next_attempt(attempts: i32) -> Option<DateTime<Local>> {
+    if attempts >= *MAIL_MAX_ATTEMPTS.get().unwrap() {
+        return None;
+    }
+
+    Some(Local::now() + Duration::minutes(2_i64.pow((attempts - 1).clamp(0, 10) as u32)))
+}
+
+#[cfg(test)]
+mod test {
+    use leprecon::template::VerificationMail;
+
+    use super::{model::QueuedMail, send_mail, smtp_transport, Mail};
+    use crate::init_env;
+
+    #[test]
+    fn test_mail_bodies() {
+        let mail: VerificationMail = VerificationMail {
+            link: String::from("https://auth.local/tickets/verify?ticket=a&b"),
+            expires: String::from("2024-01-01 12:00"),
+        };
+
+        let html: String = mail.html().unwrap();
+        let text: String = mail.text().unwrap();
+
+        assert!(html.contains("ticket=a&amp;b"));
+        assert!(text.contains("ticket=a&b"));
+        assert!(text.contains("2024-01-01 12:00"));
+    }
+
+    #[tokio::test]
+    async fn test_send_to_local_sink() {
+        init_env();
+
+        let mail: QueuedMail = QueuedMail {
+            id: 0,
+            recipient: String::from("user@leprecon.local"),
+            subject: String::from("Test"),
+            html: String::from("<p>Test</p>"),
+            text: String::from("Test"),
+            attempts: 0,
+        };
+
+        send_mail(&smtp_transport().unwrap(), &mail).await.unwrap();
+    }
+}
@@ -0,0 +1,127 @@
+use super::model::QueuedMail;
+
+use chrono::{DateTime, Local};
+use leprecon::utils::PostgresConn;
+use tokio_postgres::{GenericClient, Row, Transaction};
+
+/// Email address of the user, only known once the auth provider was asked for it.
+pub(crate) async fn get_email<C: GenericClient>(
+    sub: &str,
+    db_client: &C,
+) -> Result<Option<String>, tokio_postgres::Error> {
+    let r: Option<Row> = db_client
+        .query_opt(
+            "SELECT email FROM users WHERE sub = $1 AND deleted IS NULL",
+            &[&sub],
+        )
+        .await?;
+
+    Ok(r.and_then(|r| r.get("email")))
+}
+
+pub(crate) async fn set_email(
+    sub: &str,
+    email: &str,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    db_client
+        .execute(
+            "UPDATE users SET email = $2 WHERE sub = $1",
+            &[&sub, &email],
+        )
+        .await
+}
+
+pub(super) async fn insert_mail<C: GenericClient>(
+    recipient: &str,
+    subject: &str,
+    html: &str,
+    text: &str,
+    db_client: &C,
+) -> Result<u64, tokio_postgres::Error> {
+    let now: DateTime<Local> = Local::now();
+
+    db_client
+        .execute(
+            "INSERT INTO mail_queue(recipient, subject, html, text, next_attempt, created) VALUES($1, $2, $3, $4, $5, $5)",
+            &[&recipient, &subject, &html, &text, &now],
+        )
+        .await
+}
+
+/// Claims the mails which are due, until the lease ends no other worker picks them up.
+pub(super) async fn claim_due_mails(
+    lease: DateTime<Local>,
+    limit: i64,
+    db_client: &PostgresConn<'_>,
+) -> Result<Vec<QueuedMail>, tokio_postgres::Error> {
+    let rows: Vec<Row> = db_client
+        .query(
+            "UPDATE mail_queue SET next_attempt = $2 WHERE id IN (SELECT id FROM mail_queue WHERE sent IS NULL AND failed IS NULL AND next_attempt <= $1 ORDER BY next_attempt LIMIT $3 FOR UPDATE SKIP LOCKED) RETURNING id, recipient, subject, html, text, attempts",
+            &[&Local::now(), &lease, &limit],
+        )
+        .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+}
+
+pub(super) async fn mark_mail_sent(
+    id: i32,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    db_client
+        .execute(
+            "UPDATE mail_queue SET sent = $2, attempts = attempts + 1, last_error = NULL WHERE id = $1",
+            &[&id, &Local::now()],
+        )
+        .await
+}
+
+/// Records the failed attempt, the mail is given up on when there is no next attempt.
+pub(super) async fn mark_mail_attempt_failed(
+    id: i32,
+    error: &str,
+    next_attempt: Option<DateTime<Local>>,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    match next_attempt {
+        Some(v) => db_client
+            .execute(
+                "UPDATE mail_queue SET attempts = attempts + 1, last_error = $2, next_attempt = $3 WHERE id = $1",
+                &[&id, &error, &v],
+            )
+            .await,
+        None => db_client
+            .execute(
+                "UPDATE mail_queue SET attempts = attempts + 1, last_error = $2, failed = $3 WHERE id = $1",
+                &[&id, &error, &Local::now()],
+            )
+            .await,
+    }
+}
+
+/// Removes handled mails, these hold email addresses which are not kept longer than needed.
+pub(super) async fn purge_handled_mails(
+    before: DateTime<Local>,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    db_client
+        .execute(
+            "DELETE FROM mail_queue WHERE sent < $1 OR failed < $1",
+            &[&before],
+        )
+        .await
+}
+
+/// Removes the mails to the user, before their email address is forgotten.
+pub(crate) async fn delete_user_mails(
+    sub: &str,
+    transaction: &Transaction<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    transaction
+        .execute(
+            "DELETE FROM mail_queue WHERE recipient = (SELECT email FROM users WHERE sub = $1)",
+            &[&sub],
+        )
+        .await
+}
@@ -0,0 +1,93 @@
+use askama::Template;
+use leprecon::template::{
+    DepositReceiptMail, DepositReceiptMailHtml, DepositReceiptMailText, LimitChangeMail,
+    LimitChangeMailHtml, LimitChangeMailText, VerificationMail, VerificationMailHtml,
+    VerificationMailText, WithdrawalStatusMail, WithdrawalStatusMailHtml, WithdrawalStatusMailText,
+};
+use tokio_postgres::Row;
+
+/// Email which can be queued, every mail has both an HTML and a text body.
+pub(crate) trait Mail {
+    fn subject(&self) -> String;
+    fn html(&self) -> askama::Result<String>;
+    fn text(&self) -> askama::Result<String>;
+}
+
+impl Mail for VerificationMail {
+    fn subject(&self) -> String {
+        String::from("Verify your email")
+    }
+
+    fn html(&self) -> askama::Result<String> {
+        VerificationMailHtml { mail: self }.render()
+    }
+
+    fn text(&self) -> askama::Result<String> {
+        VerificationMailText { mail: self }.render()
+    }
+}
+
+impl Mail for DepositReceiptMail {
+    fn subject(&self) -> String {
+        format!("Deposit of {:.2} {} received", self.amount, self.currency)
+    }
+
+    fn html(&self) -> askama::Result<String> {
+        DepositReceiptMailHtml { mail: self }.render()
+    }
+
+    fn text(&self) -> askama::Result<String> {
+        DepositReceiptMailText { mail: self }.render()
+    }
+}
+
+impl Mail for WithdrawalStatusMail {
+    fn subject(&self) -> String {
+        format!("Withdrawal {}", self.status)
+    }
+
+    fn html(&self) -> askama::Result<String> {
+        WithdrawalStatusMailHtml { mail: self }.render()
+    }
+
+    fn text(&self) -> askama::Result<String> {
+        WithdrawalStatusMailText { mail: self }.render()
+    }
+}
+
+impl Mail for LimitChangeMail {
+    fn subject(&self) -> String {
+        String::from("Your limit was changed")
+    }
+
+    fn html(&self) -> askama::Result<String> {
+        LimitChangeMailHtml { mail: self }.render()
+    }
+
+    fn text(&self) -> askama::Result<String> {
+        LimitChangeMailText { mail: self }.render()
+    }
+}
+
+/// Rendered mail waiting in the queue.
+pub(super) struct QueuedMail {
+    pub id: i32,
+    pub recipient: String,
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+    pub attempts: i32,
+}
+
+impl From<Row> for QueuedMail {
+    fn from(r: Row) -> Self {
+        QueuedMail {
+            id: r.get("id"),
+            recipient: r.get("recipient"),
+            subject: r.get("subject"),
+            html: r.get("html"),
+            text: r.get("text"),
+            attempts: r.get("attempts"),
+        }
+    }
+}
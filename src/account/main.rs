@@ -1,44 +1,80 @@
+mod audit;
+mod balance;
+mod deletion;
 mod email;
 mod embedded;
+mod export;
 mod fixture;
+mod kyc;
+mod ledger;
+mod limit;
+mod locale;
+mod mail;
 mod model;
+mod notification;
+mod retention;
+mod self_exclusion;
 mod user;
 
-use axum::{serve, Router};
+use audit::audit_log;
+use axum::{middleware, serve, Router};
+use balance::{balance_channel, balance_events, subscribe_balance_updates};
 use bb8_postgres::{bb8::Pool, PostgresConnectionManager};
 use bb8_redis::RedisConnectionManager;
-use email::email_verification;
+use deletion::run_deletion_jobs;
+use email::{
+    email_verification, email_verification_status, email_verified_callback, sweep_expired_sessions,
+};
+use export::{download_export, user_export};
 use fixture::{add_currency, add_users, create_account_db};
-use futures::StreamExt;
+use indexmap::IndexMap;
+use kyc::{review_kyc, run_kyc_outbox, submit_kyc_document, user_kyc};
+use ledger::{consume_balance_updates, wager};
 use leprecon::{
     auth::{get_valid_jwt, JWT},
     broker::init_broker,
+    i18n::resolve_locale,
     signals::shutdown_signal,
     utils::{configure_tracing, create_conn_pool},
 };
+use limit::{check_limit, remove_limit, run_session_reminders, set_limit, user_limits};
+use locale::{set_user_locale, user_locale};
+use mail::{run_mail_queue, smtp_transport};
+use notification::{notifications, read_notifications, unread_notifications};
 use rabbitmq_stream_client::types::{ByteCapacity, OffsetSpecification};
+use retention::{run_purge_job, RETENTION_TABLES};
+use self_exclusion::{create_self_exclusion, self_exclusion};
 use std::{
     env,
     error::Error,
+    net::{IpAddr, SocketAddr},
     ops::DerefMut,
     sync::{Arc, OnceLock},
     time::Duration,
 };
 use tokio::{net::TcpListener, sync::Mutex, task};
 use tokio_postgres::NoTls;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
 use tracing::{error, info};
-use user::{create_user, delete_account, update_user_information, user_balance, user_information};
+use user::{
+    create_user, delete_account, update_user_information, user_balance, user_information,
+    user_jurisdiction, user_wallet,
+};
 
 type StateParams = (
     Arc<tokio::sync::Mutex<JWT>>,
     reqwest::Client,
     bb8_postgres::bb8::Pool<PostgresConnectionManager<NoTls>>,
     bb8_postgres::bb8::Pool<RedisConnectionManager>,
+    rabbitmq_stream_client::Producer<rabbitmq_stream_client::NoDedup>,
+    rabbitmq_stream_client::Producer<rabbitmq_stream_client::NoDedup>,
+    tokio::sync::broadcast::Sender<String>,
 );
 
 // Host variables
 static HOST: OnceLock<String> = OnceLock::new();
 static LOG_LEVEL: OnceLock<String> = OnceLock::new();
+static TRUSTED_PROXIES: OnceLock<Vec<IpAddr>> = OnceLock::new();
 
 // DB variables
 static ACCOUNT_CONN: OnceLock<String> = OnceLock::new();
@@ -47,10 +83,43 @@ static ACCOUNT_CONN: OnceLock<String> = OnceLock::new();
 static AUTH_HOST: OnceLock<String> = OnceLock::new();
 static CLIENT_ID: OnceLock<String> = OnceLock::new();
 static CLIENT_SECRET: OnceLock<String> = OnceLock::new();
+static AUTH_CALLBACK_SECRET: OnceLock<String> = OnceLock::new();
+static ACCOUNT_ADMIN_TOKEN: OnceLock<String> = OnceLock::new();
+static SERVICE_TOKEN: OnceLock<String> = OnceLock::new();
 
 // VALKEY variables
 static VALKEY_CONN: OnceLock<String> = OnceLock::new();
 
+// Email variables
+static EMAIL_SESSION_EXPIRY_MINUTES: OnceLock<i64> = OnceLock::new();
+static EMAIL_RESEND_COOLDOWN_SECONDS: OnceLock<i64> = OnceLock::new();
+static EMAIL_RESEND_DAILY_CAP: OnceLock<usize> = OnceLock::new();
+static EMAIL_VERIFIED_CACHE_SECONDS: OnceLock<u64> = OnceLock::new();
+
+// Mail variables
+static SMTP_HOST: OnceLock<String> = OnceLock::new();
+static SMTP_PORT: OnceLock<u16> = OnceLock::new();
+static SMTP_USERNAME: OnceLock<String> = OnceLock::new();
+static SMTP_PASSWORD: OnceLock<String> = OnceLock::new();
+static SMTP_TLS: OnceLock<bool> = OnceLock::new();
+static MAIL_FROM: OnceLock<String> = OnceLock::new();
+static MAIL_MAX_ATTEMPTS: OnceLock<i32> = OnceLock::new();
+
+// Notification variables
+static NOTIFICATION_LIMIT: OnceLock<isize> = OnceLock::new();
+static NOTIFICATION_EXPIRES_DAYS: OnceLock<i64> = OnceLock::new();
+
+// Limit variables
+static LIMIT_COOLING_OFF_HOURS: OnceLock<i64> = OnceLock::new();
+
+// Export variables
+static EXPORT_ASYNC_TRANSACTIONS: OnceLock<i64> = OnceLock::new();
+static EXPORT_EXPIRES_MINUTES: OnceLock<i64> = OnceLock::new();
+
+// Deletion variables
+static SOFT_DELETE: OnceLock<bool> = OnceLock::new();
+static RETENTION_DAYS: OnceLock<IndexMap<&'static str, i32>> = OnceLock::new();
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     // Initialize env variables
@@ -75,26 +144,54 @@ async fn main() -> Result<(), Box<dyn Error>> {
         println!("Error creating stream: {:?} {:?}", stream, e);
     }
 
-    let mut consumer = environment
+    let consumer = environment
         .consumer()
         .offset(OffsetSpecification::First)
         .build(stream)
         .await
         .unwrap();
 
-    consumer.handle();
-    task::spawn(async move {
-        while let Some(delivery) = consumer.next().await {
-            let d = delivery.unwrap();
-            info!(
-                "Got message: {:#?} with offset: {}",
-                d.message()
-                    .data()
-                    .map(|data| String::from_utf8(data.to_vec()).unwrap()),
-                d.offset(),
-            );
-        }
-    });
+    let kyc_stream = "kyc_status";
+    let create_response = environment
+        .stream_creator()
+        .max_length(ByteCapacity::GB(5))
+        .create(kyc_stream)
+        .await;
+
+    if let Err(e) = create_response {
+        error!("Error creating stream: {:?} {:?}", kyc_stream, e);
+    }
+
+    let producer: rabbitmq_stream_client::Producer<rabbitmq_stream_client::NoDedup> =
+        environment.producer().build(kyc_stream).await?;
+
+    let deleted_stream = "account_deleted";
+    let create_response = environment
+        .stream_creator()
+        .max_length(ByteCapacity::GB(5))
+        .create(deleted_stream)
+        .await;
+
+    if let Err(e) = create_response {
+        error!("Error creating stream: {:?} {:?}", deleted_stream, e);
+    }
+
+    let deleted_producer: rabbitmq_stream_client::Producer<rabbitmq_stream_client::NoDedup> =
+        environment.producer().build(deleted_stream).await?;
+
+    let applied_stream = "balance_applied";
+    let create_response = environment
+        .stream_creator()
+        .max_length(ByteCapacity::GB(5))
+        .create(applied_stream)
+        .await;
+
+    if let Err(e) = create_response {
+        error!("Error creating stream: {:?} {:?}", applied_stream, e);
+    }
+
+    let applied_producer: rabbitmq_stream_client::Producer<rabbitmq_stream_client::NoDedup> =
+        environment.producer().build(applied_stream).await?;
 
     // Create account db if not exists
     create_account_db().await;
@@ -132,6 +229,15 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .run_async(postgres_pool.get().await?.deref_mut())
         .await?;
 
+    // Clean up expired verification sessions
+    task::spawn(sweep_expired_sessions(postgres_pool.clone()));
+
+    // Send queued mails
+    task::spawn(run_mail_queue(postgres_pool.clone(), smtp_transport()?));
+
+    // Purge soft-deleted accounts after retention
+    task::spawn(run_purge_job(postgres_pool.clone()));
+
     add_currency(postgres_pool.get().await?.deref_mut()).await;
     let sub = env::var("SUB_NOT_VERIFIED").unwrap();
     add_users(postgres_pool.get().await?.deref_mut(), &vec![&sub]).await;
@@ -142,6 +248,14 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let redis_pool: Pool<RedisConnectionManager> =
         create_conn_pool(redis_manager, connection_timeout, max_size).await?;
 
+    // Apply balance updates
+    task::spawn(consume_balance_updates(
+        consumer,
+        applied_producer,
+        postgres_pool.clone(),
+        redis_pool.clone(),
+    ));
+
     // Get valid access token
     let jwt: JWT = get_valid_jwt(
         redis_pool.get().await?,
@@ -152,21 +266,53 @@ async fn main() -> Result<(), Box<dyn Error>> {
     )
     .await?;
 
+    let jwt: Arc<Mutex<JWT>> = Arc::new(Mutex::new(jwt));
+
+    // Forward balance updates of all replicas to the event streams
+    let balance_sender: tokio::sync::broadcast::Sender<String> = balance_channel();
+    task::spawn(subscribe_balance_updates(balance_sender.clone()));
+
+    // Retry pending account deletions
+    task::spawn(run_deletion_jobs((
+        jwt.clone(),
+        req_client.clone(),
+        postgres_pool.clone(),
+        redis_pool.clone(),
+        producer.clone(),
+        deleted_producer.clone(),
+        balance_sender.clone(),
+    )));
+
+    // Remind players of their session time
+    task::spawn(run_session_reminders(
+        postgres_pool.clone(),
+        redis_pool.clone(),
+    ));
+
+    // Publish kyc statuses which could not be published with their change
+    task::spawn(run_kyc_outbox(postgres_pool.clone(), producer.clone()));
+
     // Build application and listen to incoming requests.
     let app: Router = build_app(
-        Arc::new(Mutex::new(jwt)),
+        jwt,
         req_client,
         postgres_pool,
         redis_pool,
+        producer,
+        deleted_producer,
+        balance_sender,
     );
     let listener: TcpListener = TcpListener::bind(HOST.get().unwrap()).await?;
 
     info!("Running application");
 
     // Run the app.
-    serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?;
+    serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await?;
 
     Ok(())
 }
@@ -175,14 +321,84 @@ async fn main() -> Result<(), Box<dyn Error>> {
 fn init_env() {
     HOST.get_or_init(|| env::var("ACCOUNT_HOST").unwrap());
     LOG_LEVEL.get_or_init(|| env::var("LOG_LEVEL").unwrap());
+    TRUSTED_PROXIES.get_or_init(|| {
+        env::var("TRUSTED_PROXIES")
+            .unwrap()
+            .split(',')
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(|v| v.parse().unwrap())
+            .collect()
+    });
 
     ACCOUNT_CONN.get_or_init(|| env::var("ACCOUNT_CONN").unwrap());
 
     AUTH_HOST.get_or_init(|| env::var("AUTH_HOST").unwrap());
     CLIENT_ID.get_or_init(|| env::var("CLIENT_ID_ACCOUNT").unwrap());
     CLIENT_SECRET.get_or_init(|| env::var("CLIENT_SECRET_ACCOUNT").unwrap());
+    AUTH_CALLBACK_SECRET.get_or_init(|| env::var("AUTH_CALLBACK_SECRET").unwrap());
+    ACCOUNT_ADMIN_TOKEN.get_or_init(|| env::var("ACCOUNT_ADMIN_TOKEN").unwrap());
+    SERVICE_TOKEN.get_or_init(|| env::var("SERVICE_TOKEN").unwrap());
 
     VALKEY_CONN.get_or_init(|| env::var("VALKEY_CONN").unwrap());
+
+    EMAIL_SESSION_EXPIRY_MINUTES.get_or_init(|| {
+        env::var("EMAIL_SESSION_EXPIRY_MINUTES")
+            .unwrap()
+            .parse()
+            .unwrap()
+    });
+    EMAIL_RESEND_COOLDOWN_SECONDS.get_or_init(|| {
+        env::var("EMAIL_RESEND_COOLDOWN_SECONDS")
+            .unwrap()
+            .parse()
+            .unwrap()
+    });
+    EMAIL_RESEND_DAILY_CAP
+        .get_or_init(|| env::var("EMAIL_RESEND_DAILY_CAP").unwrap().parse().unwrap());
+
+    SMTP_HOST.get_or_init(|| env::var("SMTP_HOST").unwrap());
+    SMTP_PORT.get_or_init(|| env::var("SMTP_PORT").unwrap().parse().unwrap());
+    SMTP_USERNAME.get_or_init(|| env::var("SMTP_USERNAME").unwrap());
+    SMTP_PASSWORD.get_or_init(|| env::var("SMTP_PASSWORD").unwrap());
+    SMTP_TLS.get_or_init(|| env::var("SMTP_TLS").unwrap().parse().unwrap());
+    MAIL_FROM.get_or_init(|| env::var("MAIL_FROM").unwrap());
+    MAIL_MAX_ATTEMPTS.get_or_init(|| env::var("MAIL_MAX_ATTEMPTS").unwrap().parse().unwrap());
+
+    NOTIFICATION_LIMIT.get_or_init(|| env::var("NOTIFICATION_LIMIT").unwrap().parse().unwrap());
+    NOTIFICATION_EXPIRES_DAYS.get_or_init(|| {
+        env::var("NOTIFICATION_EXPIRES_DAYS")
+            .unwrap()
+            .parse()
+            .unwrap()
+    });
+
+    LIMIT_COOLING_OFF_HOURS.get_or_init(|| {
+        env::var("LIMIT_COOLING_OFF_HOURS")
+            .unwrap()
+            .parse()
+            .unwrap()
+    });
+
+    EXPORT_ASYNC_TRANSACTIONS.get_or_init(|| {
+        env::var("EXPORT_ASYNC_TRANSACTIONS")
+            .unwrap()
+            .parse()
+            .unwrap()
+    });
+    EXPORT_EXPIRES_MINUTES
+        .get_or_init(|| env::var("EXPORT_EXPIRES_MINUTES").unwrap().parse().unwrap());
+
+    SOFT_DELETE.get_or_init(|| env::var("SOFT_DELETE").unwrap().parse().unwrap());
+    RETENTION_DAYS.get_or_init(|| {
+        RETENTION_TABLES
+            .iter()
+            .map(|t| {
+                let key: String = format!("RETENTION_DAYS_{}", t.to_uppercase());
+                (*t, env::var(key).unwrap().parse().unwrap())
+            })
+            .collect()
+    });
 }
 
 /// Builds the application.
@@ -191,13 +407,24 @@ fn build_app(
     req_client: reqwest::Client,
     postgres_pool: Pool<PostgresConnectionManager<NoTls>>,
     redis_pool: Pool<RedisConnectionManager>,
+    producer: rabbitmq_stream_client::Producer<rabbitmq_stream_client::NoDedup>,
+    deleted_producer: rabbitmq_stream_client::Producer<rabbitmq_stream_client::NoDedup>,
+    balance_sender: tokio::sync::broadcast::Sender<String>,
 ) -> Router {
     Router::new()
         .route(
             "/account/email/verification",
-            axum::routing::post(email_verification),
+            axum::routing::get(email_verification_status).post(email_verification),
+        )
+        .route(
+            "/account/email/verified",
+            axum::routing::post(email_verified_callback),
         )
         .route("/account/user/balance", axum::routing::get(user_balance))
+        .route(
+            "/account/user/balance/events",
+            axum::routing::get(balance_events),
+        )
         .route(
             "/account/user/information",
             axum::routing::get(user_information).put(update_user_information),
@@ -206,5 +433,62 @@ fn build_app(
             "/account/user",
             axum::routing::post(create_user).delete(delete_account),
         )
-        .with_state((jwt, req_client, postgres_pool, redis_pool))
+        .route(
+            "/account/user/limits",
+            axum::routing::get(user_limits)
+                .put(set_limit)
+                .delete(remove_limit),
+        )
+        .route(
+            "/account/user/locale",
+            axum::routing::get(user_locale).put(set_user_locale),
+        )
+        .route("/account/user/export", axum::routing::get(user_export))
+        .route(
+            "/account/user/export/:token",
+            axum::routing::get(download_export),
+        )
+        .route("/account/user/kyc", axum::routing::get(user_kyc))
+        .route(
+            "/account/user/kyc/documents",
+            axum::routing::post(submit_kyc_document),
+        )
+        .route("/account/notifications", axum::routing::get(notifications))
+        .route(
+            "/account/notifications/unread",
+            axum::routing::get(unread_notifications),
+        )
+        .route(
+            "/account/notifications/read",
+            axum::routing::put(read_notifications),
+        )
+        // Internal, only for the other services
+        .route(
+            "/account/user/jurisdiction",
+            axum::routing::get(user_jurisdiction),
+        )
+        .route("/account/user/wallet", axum::routing::get(user_wallet))
+        .route("/account/user/wager", axum::routing::post(wager))
+        .route(
+            "/account/user/limits/check",
+            axum::routing::get(check_limit),
+        )
+        .route("/account/admin/kyc", axum::routing::put(review_kyc))
+        .route("/account/admin/audit", axum::routing::get(audit_log))
+        .route(
+            "/account/user/self-exclusion",
+            axum::routing::get(self_exclusion).post(create_self_exclusion),
+        )
+        .with_state((
+            jwt,
+            req_client,
+            postgres_pool,
+            redis_pool,
+            producer,
+            deleted_producer,
+            balance_sender,
+        ))
+        .layer(middleware::from_fn(resolve_locale))
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
 }
@@ -0,0 +1,36 @@
+use barrel::{backend::Pg, types, Migration};
+
+pub(crate) fn migration() -> String {
+    let mut m: Migration = Migration::new();
+
+    m.change_table("users", |t| {
+        t.add_column("email", types::text().nullable(true));
+    });
+
+    m.create_table_if_not_exists("mail_queue", |t| {
+        t.add_column("id", types::primary());
+        t.add_column("recipient", types::text());
+        t.add_column("subject", types::text());
+        t.add_column("html", types::text());
+        t.add_column("text", types::text());
+        t.add_column("attempts", types::integer().default(0));
+        t.add_column("last_error", types::text().nullable(true));
+        t.add_column(
+            "next_attempt",
+            types::custom("timestamp with time zone"),
+        );
+        t.add_column(
+            "sent",
+            types::custom("timestamp with time zone").nullable(true),
+        );
+        t.add_column(
+            "failed",
+            types::custom("timestamp with time zone").nullable(true),
+        );
+        t.add_column("created", types::custom("timestamp with time zone"));
+
+        t.add_index("mail_queue_next_attempt", types::index(vec!["next_attempt"]));
+    });
+
+    m.make::<Pg>()
+}
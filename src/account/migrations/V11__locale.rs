@@ -0,0 +1,11 @@
+use barrel::{backend::Pg, types, Migration};
+
+pub(crate) fn migration() -> String {
+    let mut m: Migration = Migration::new();
+
+    m.change_table("users", |t| {
+        t.add_column("locale", types::text().nullable(true));
+    });
+
+    m.make::<Pg>()
+}
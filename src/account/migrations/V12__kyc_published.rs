@@ -0,0 +1,17 @@
+use barrel::{backend::Pg, types, Migration};
+
+pub(crate) fn migration() -> String {
+    let mut m: Migration = Migration::new();
+
+    m.change_table("kyc_verifications", |t| {
+        t.add_column(
+            "published",
+            types::custom("timestamp with time zone").nullable(true),
+        );
+    });
+
+    // Statuses from before the outbox were published with their change
+    m.inject_custom("UPDATE kyc_verifications SET published = updated");
+
+    m.make::<Pg>()
+}
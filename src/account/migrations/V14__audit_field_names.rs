@@ -0,0 +1,16 @@
+use barrel::{backend::Pg, Migration};
+
+pub(crate) fn migration() -> String {
+    let mut m: Migration = Migration::new();
+
+    // Detail changes logged before and after values, keep only the names of the fields
+    m.inject_custom("DROP RULE IF EXISTS audit_log_no_update ON audit_log");
+    m.inject_custom(
+        "UPDATE audit_log SET diff = (SELECT jsonb_agg(field) FROM jsonb_object_keys(diff) AS field) WHERE jsonb_typeof(diff) = 'object'",
+    );
+    m.inject_custom(
+        "CREATE OR REPLACE RULE audit_log_no_update AS ON UPDATE TO audit_log DO INSTEAD NOTHING",
+    );
+
+    m.make::<Pg>()
+}
@@ -0,0 +1,51 @@
+use barrel::{backend::Pg, types, Migration};
+
+pub(crate) fn migration() -> String {
+    let mut m: Migration = Migration::new();
+
+    m.create_table_if_not_exists("transactions", |t| {
+        t.add_column("id", types::primary());
+        t.add_column("kind", types::text());
+        t.add_column("amount", types::double());
+        t.add_column("currency", types::text());
+        t.add_column("reference", types::text().unique(true));
+        t.add_column("created", types::custom("timestamp with time zone"));
+        t.add_column("user_id", types::integer());
+
+        t.add_foreign_key(&["user_id"], "users", &["id"]);
+    });
+
+    m.create_table_if_not_exists("limits", |t| {
+        t.add_column("id", types::primary());
+        t.add_column("kind", types::text());
+        t.add_column("period", types::text());
+        t.add_column("amount", types::double());
+        t.add_column("pending_amount", types::double().nullable(true));
+        t.add_column(
+            "pending_from",
+            types::custom("timestamp with time zone").nullable(true),
+        );
+        t.add_column("updated", types::custom("timestamp with time zone"));
+        t.add_column("user_id", types::integer());
+
+        t.add_foreign_key(&["user_id"], "users", &["id"]);
+        t.add_constraint(
+            "limits_user_kind_period",
+            types::unique_constraint(vec!["user_id", "kind", "period"]),
+        );
+    });
+
+    m.create_table_if_not_exists("self_exclusions", |t| {
+        t.add_column("id", types::primary());
+        t.add_column("starts", types::custom("timestamp with time zone"));
+        t.add_column(
+            "ends",
+            types::custom("timestamp with time zone").nullable(true),
+        );
+        t.add_column("user_id", types::integer());
+
+        t.add_foreign_key(&["user_id"], "users", &["id"]);
+    });
+
+    m.make::<Pg>()
+}
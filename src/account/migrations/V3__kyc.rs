@@ -0,0 +1,29 @@
+use barrel::{backend::Pg, types, Migration};
+
+pub(crate) fn migration() -> String {
+    let mut m: Migration = Migration::new();
+
+    m.create_table_if_not_exists("kyc_verifications", |t| {
+        t.add_column("id", types::primary());
+        t.add_column("status", types::text());
+        t.add_column("reason", types::text().nullable(true));
+        t.add_column("updated", types::custom("timestamp with time zone"));
+        t.add_column("user_id", types::integer().unique(true));
+
+        t.add_foreign_key(&["user_id"], "users", &["id"]);
+    });
+
+    m.create_table_if_not_exists("kyc_documents", |t| {
+        t.add_column("id", types::primary());
+        t.add_column("document_type", types::text());
+        t.add_column("file_name", types::text());
+        t.add_column("content_type", types::text());
+        t.add_column("size", types::integer());
+        t.add_column("uploaded", types::custom("timestamp with time zone"));
+        t.add_column("user_id", types::integer());
+
+        t.add_foreign_key(&["user_id"], "users", &["id"]);
+    });
+
+    m.make::<Pg>()
+}
@@ -0,0 +1,17 @@
+use barrel::{backend::Pg, types, Migration};
+
+pub(crate) fn migration() -> String {
+    let mut m: Migration = Migration::new();
+
+    m.create_table_if_not_exists("deletion_jobs", |t| {
+        t.add_column("id", types::primary());
+        t.add_column("sub", types::text().unique(true));
+        t.add_column("provider_deleted", types::boolean().default(false));
+        t.add_column("attempts", types::integer().default(0));
+        t.add_column("last_error", types::text().nullable(true));
+        t.add_column("next_attempt", types::custom("timestamp with time zone"));
+        t.add_column("created", types::custom("timestamp with time zone"));
+    });
+
+    m.make::<Pg>()
+}
@@ -0,0 +1,14 @@
+use barrel::{backend::Pg, types, Migration};
+
+pub(crate) fn migration() -> String {
+    let mut m: Migration = Migration::new();
+
+    m.change_table("users", |t| {
+        t.add_column(
+            "deleted",
+            types::custom("timestamp with time zone").nullable(true),
+        );
+    });
+
+    m.make::<Pg>()
+}
@@ -0,0 +1,28 @@
+use barrel::{backend::Pg, types, Migration};
+
+pub(crate) fn migration() -> String {
+    let mut m: Migration = Migration::new();
+
+    m.create_table_if_not_exists("audit_log", |t| {
+        t.add_column("id", types::primary());
+        t.add_column("sub", types::text());
+        t.add_column("actor", types::text());
+        t.add_column("action", types::text());
+        t.add_column("diff", types::custom("jsonb").nullable(true));
+        t.add_column("request_id", types::text().nullable(true));
+        t.add_column("ip", types::text().nullable(true));
+        t.add_column("created", types::custom("timestamp with time zone"));
+
+        t.add_index("audit_log_sub_created", types::index(vec!["sub", "created"]));
+    });
+
+    // Append-only
+    m.inject_custom(
+        "CREATE OR REPLACE RULE audit_log_no_update AS ON UPDATE TO audit_log DO INSTEAD NOTHING",
+    );
+    m.inject_custom(
+        "CREATE OR REPLACE RULE audit_log_no_delete AS ON DELETE TO audit_log DO INSTEAD NOTHING",
+    );
+
+    m.make::<Pg>()
+}
@@ -0,0 +1,11 @@
+use barrel::{backend::Pg, types, Migration};
+
+pub(crate) fn migration() -> String {
+    let mut m: Migration = Migration::new();
+
+    m.change_table("customer_details", |t| {
+        t.add_column("version", types::integer().default(0));
+    });
+
+    m.make::<Pg>()
+}
@@ -0,0 +1,12 @@
+use barrel::{backend::Pg, Migration};
+
+pub(crate) fn migration() -> String {
+    let mut m: Migration = Migration::new();
+
+    // Existing sessions count as created now
+    m.inject_custom(
+        "ALTER TABLE sessions ADD COLUMN IF NOT EXISTS created timestamp with time zone NOT NULL DEFAULT now()",
+    );
+
+    m.make::<Pg>()
+}
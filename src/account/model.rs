@@ -1,3 +1,9 @@
+use crate::{ACCOUNT_ADMIN_TOKEN, SERVICE_TOKEN};
+
+use askama::Template;
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts, response::Html};
+use leprecon::{auth::valid_bearer, template::Snackbar};
+use reqwest::StatusCode;
 use std::fmt;
 
 #[derive(Debug)]
@@ -10,3 +16,45 @@ impl fmt::Display for SessionType {
         write!(f, "{:?}", self)
     }
 }
+
+/// Only lets requests through which carry the admin token as bearer.
+pub(super) struct AdminToken;
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AdminToken
+where
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, Html<String>);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        if !valid_bearer(&parts.headers, ACCOUNT_ADMIN_TOKEN.get().unwrap()) {
+            let snackbar: Snackbar<'_> = Snackbar {
+                message: "unauthorized",
+                ..Default::default()
+            };
+            return Err((StatusCode::UNAUTHORIZED, Html(snackbar.render().unwrap())));
+        }
+
+        Ok(AdminToken)
+    }
+}
+
+/// Only lets requests through from the other services, which carry the service token as bearer.
+pub(super) struct ServiceToken;
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ServiceToken
+where
+    S: Send + Sync,
+{
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        if !valid_bearer(&parts.headers, SERVICE_TOKEN.get().unwrap()) {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+
+        Ok(ServiceToken)
+    }
+}
@@ -0,0 +1,277 @@
+mod model;
+
+pub(crate) mod db;
+
+pub(crate) use model::Notification;
+
+use self::{
+    db::{get_notifications, get_unread, mark_all_read, mark_read, push_notification},
+    model::ReadParams,
+};
+
+use crate::{StateParams, NOTIFICATION_EXPIRES_DAYS, NOTIFICATION_LIMIT};
+
+use askama::Template;
+use axum::{extract::State, response::Html, Form};
+use bb8_postgres::bb8::Pool;
+use bb8_redis::RedisConnectionManager;
+use leprecon::{
+    auth::AuthParam,
+    i18n::{translate, translate_with, FluentArgs},
+    template::{self, Snackbar},
+    utils::{
+        extract::{extract_conn_from_pool, ValidForm},
+        RedisConn,
+    },
+};
+use redis::RedisResult;
+use reqwest::StatusCode;
+use std::collections::HashSet;
+use tracing::error;
+
+/// Notification centre of the user, with the unread notifications highlighted.
+pub(super) async fn notifications(
+    State(state): State<StateParams>,
+    Form(auth_param): Form<AuthParam>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    if auth_param.sub.is_empty() {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Html(snackbar.render().unwrap()),
+        );
+    };
+
+    let mut redis_conn: RedisConn = match extract_conn_from_pool(&state.3, &mut snackbar).await {
+        Ok(v) => v,
+        Err(e) => return e,
+    };
+
+    render_notifications(&auth_param.sub, &mut redis_conn, &mut snackbar).await
+}
+
+/// Number of unread notifications, polled to keep the badge up to date.
+pub(super) async fn unread_notifications(
+    State(state): State<StateParams>,
+    Form(auth_param): Form<AuthParam>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    if auth_param.sub.is_empty() {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Html(snackbar.render().unwrap()),
+        );
+    };
+
+    let mut redis_conn: RedisConn = match extract_conn_from_pool(&state.3, &mut snackbar).await {
+        Ok(v) => v,
+        Err(e) => return e,
+    };
+
+    match user_notifications(&auth_param.sub, &mut redis_conn).await {
+        Ok(v) => {
+            let count_template: template::NotificationCount =
+                template::NotificationCount { unread: v.unread };
+            (StatusCode::OK, Html(count_template.render().unwrap()))
+        }
+        Err(e) => {
+            error!("Could not get notifications: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            )
+        }
+    }
+}
+
+/// Marks the notification as read, or all notifications when no id is given.
+pub(super) async fn read_notifications(
+    State(state): State<StateParams>,
+    ValidForm(params): ValidForm<ReadParams>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    let mut redis_conn: RedisConn = match extract_conn_from_pool(&state.3, &mut snackbar).await {
+        Ok(v) => v,
+        Err(e) => return e,
+    };
+
+    let result: RedisResult<()> = match &params.id {
+        Some(id) => mark_read(&params.sub, id, &mut redis_conn).await,
+        None => mark_all_read(&params.sub, &mut redis_conn).await,
+    };
+
+    if let Err(e) = result {
+        error!("Could not mark notifications as read: {:?}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Html(snackbar.render().unwrap()),
+        );
+    }
+
+    render_notifications(&params.sub, &mut redis_conn, &mut snackbar).await
+}
+
+/// Adds a notification for the user, failures are only logged as the event itself succeeded.
+pub(crate) async fn notify(
+    sub: &str,
+    notification: Notification,
+    redis_pool: &Pool<RedisConnectionManager>,
+) {
+    let mut redis_conn: RedisConn = match redis_pool.get().await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Cannot get connection from pool: {:?}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = push_notification(
+        sub,
+        &notification,
+        *NOTIFICATION_LIMIT.get().unwrap(),
+        NOTIFICATION_EXPIRES_DAYS.get().unwrap() * 86400,
+        &mut redis_conn,
+    )
+    .await
+    {
+        error!("Could not store notification for {}: {:?}", sub, e);
+    }
+}
+
+async fn render_notifications(
+    sub: &str,
+    redis_conn: &mut RedisConn<'_>,
+    snackbar: &mut Snackbar<'_>,
+) -> (StatusCode, Html<String>) {
+    match user_notifications(sub, redis_conn).await {
+        Ok(v) => (StatusCode::OK, Html(v.render().unwrap())),
+        Err(e) => {
+            error!("Could not get notifications: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            )
+        }
+    }
+}
+
+/// Notifications of the user, translated and newest first.
+pub(crate) async fn user_notifications(
+    sub: &str,
+    redis_conn: &mut RedisConn<'_>,
+) -> RedisResult<template::Notifications> {
+    let stored: Vec<String> = get_notifications(sub, redis_conn).await?;
+    let unread: HashSet<String> = get_unread(sub, redis_conn).await?;
+
+    let notifications: Vec<template::Notification> = stored
+        .iter()
+        .filter_map(|v| match serde_json::from_str::<Notification>(v) {
+            Ok(v) => Some(v),
+            Err(e) => {
+                error!("Skipping malformed notification: {:?}", e);
+                None
+            }
+        })
+        .map(|n| {
+            let mut args: FluentArgs = FluentArgs::new();
+            for (name, value) in &n.args {
+                args.set(name.as_str(), value.as_str());
+            }
+
+            template::Notification {
+                read: !unread.contains(&n.id),
+                title: translate(&n.title),
+                message: translate_with(&n.message, &args),
+                created: n.created.format("%Y-%m-%d %H:%M").to_string(),
+                id: n.id,
+            }
+        })
+        .collect();
+
+    Ok(template::Notifications {
+        unread: notifications.iter().filter(|n| !n.read).count(),
+        notifications,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{body::Body, http::Request};
+    use reqwest::{header, Method, StatusCode};
+    use tower::ServiceExt;
+
+    use crate::fixture::{assert_body_contains, initialize, seed_database};
+
+    #[tokio::test]
+    async fn test_no_params_provided_notifications() {
+        let app: axum::Router = initialize().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/notifications")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_body_contains(response, &["Could not process request"]).await;
+    }
+
+    #[tokio::test]
+    async fn test_notification_after_limit_change() {
+        let app: axum::Router = initialize().await;
+        seed_database().await;
+
+        let response: axum::http::Response<Body> = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::PUT)
+                    .uri("/account/user/limits")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(String::from(
+                        "sub=auth0|0006&kind=Loss&period=Daily&amount=50",
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response: axum::http::Response<Body> = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/account/notifications?sub=auth0|0006")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_body_contains(response, &["Limit changed", "Mark as read"]).await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::PUT)
+                    .uri("/account/notifications/read")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(String::from("sub=auth0|0006"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_body_contains(response, &["0 unread"]).await;
+    }
+}
@@ -0,0 +1,73 @@
+use super::model::Notification;
+
+use leprecon::utils::RedisConn;
+use redis::{AsyncCommands, RedisResult};
+use std::{collections::HashSet, error::Error};
+
+fn notifications_key(sub: &str) -> String {
+    format!("notifications:{sub}")
+}
+
+fn unread_key(sub: &str) -> String {
+    format!("notifications:{sub}:unread")
+}
+
+/// Stores the notification as unread, only the newest `keep` notifications are kept.
+pub(super) async fn push_notification(
+    sub: &str,
+    notification: &Notification,
+    keep: isize,
+    expires: i64,
+    conn: &mut RedisConn<'_>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let key: String = notifications_key(sub);
+    let unread: String = unread_key(sub);
+
+    redis::pipe()
+        .atomic()
+        .zadd(
+            &key,
+            serde_json::to_string(notification)?,
+            notification.created.timestamp_millis(),
+        )
+        .ignore()
+        .sadd(&unread, &notification.id)
+        .ignore()
+        .zremrangebyrank(&key, 0, -(keep + 1))
+        .ignore()
+        .expire(&key, expires)
+        .ignore()
+        .expire(&unread, expires)
+        .ignore()
+        .query_async::<_, ()>(&mut **conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Notifications of the user, newest first.
+pub(super) async fn get_notifications(
+    sub: &str,
+    conn: &mut RedisConn<'_>,
+) -> RedisResult<Vec<String>> {
+    conn.zrevrange(notifications_key(sub), 0, -1).await
+}
+
+pub(super) async fn get_unread(
+    sub: &str,
+    conn: &mut RedisConn<'_>,
+) -> RedisResult<HashSet<String>> {
+    conn.smembers(unread_key(sub)).await
+}
+
+pub(super) async fn mark_read(sub: &str, id: &str, conn: &mut RedisConn<'_>) -> RedisResult<()> {
+    conn.srem(unread_key(sub), id).await
+}
+
+pub(super) async fn mark_all_read(sub: &str, conn: &mut RedisConn<'_>) -> RedisResult<()> {
+    conn.del(unread_key(sub)).await
+}
+
+pub(crate) async fn delete_notifications(sub: &str, conn: &mut RedisConn<'_>) -> RedisResult<()> {
+    conn.del(&[notifications_key(sub), unread_key(sub)]).await
+}
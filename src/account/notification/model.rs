@@ -0,0 +1,53 @@
+use chrono::{DateTime, Local};
+use leprecon::utils::validate::{Validate, ValidationError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Notice shown in the notification centre of the user.
+///
+/// Title and message are message ids, translated in the locale the notification is read in.
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct Notification {
+    pub id: String,
+    pub title: String,
+    pub message: String,
+    #[serde(default)]
+    pub args: BTreeMap<String, String>,
+    pub created: DateTime<Local>,
+}
+
+impl Notification {
+    pub(crate) fn new(title: &str, message: &str) -> Notification {
+        Notification {
+            id: Uuid::new_v4().to_string(),
+            title: title.to_owned(),
+            message: message.to_owned(),
+            args: BTreeMap::new(),
+            created: Local::now(),
+        }
+    }
+
+    pub(crate) fn arg(mut self, name: &str, value: impl ToString) -> Notification {
+        self.args.insert(name.to_owned(), value.to_string());
+        self
+    }
+}
+
+/// Marks a single notification as read, or all of them when no id is given.
+#[derive(Deserialize, Debug)]
+pub(crate) struct ReadParams {
+    #[serde(default)]
+    pub sub: String,
+    pub id: Option<String>,
+}
+
+impl Validate for ReadParams {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.sub.is_empty() {
+            return Err(ValidationError::new("sub", "is required"));
+        }
+
+        Ok(())
+    }
+}
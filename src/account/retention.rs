@@ -0,0 +1,62 @@
+mod db;
+
+use self::db::{purge_table, purge_users};
+
+use crate::RETENTION_DAYS;
+
+use bb8_postgres::{bb8::Pool, PostgresConnectionManager};
+use leprecon::utils::PostgresConn;
+use std::time::Duration;
+use tokio_postgres::NoTls;
+use tracing::{error, info};
+
+/// Tables holding user data, each with its own retention period after a soft delete.
+///
+/// Sessions are not listed, these are removed right away.
+pub(super) const RETENTION_TABLES: [&str; 6] = [
+    "customer_details",
+    "transactions",
+    "limits",
+    "self_exclusions",
+    "kyc_verifications",
+    "kyc_documents",
+];
+
+/// Hard-deletes the data of soft-deleted users once its retention period has expired.
+pub(super) async fn run_purge_job(postgres_pool: Pool<PostgresConnectionManager<NoTls>>) {
+    let mut interval: tokio::time::Interval = tokio::time::interval(Duration::from_secs(3600));
+
+    loop {
+        interval.tick().await;
+
+        let postgres_conn: PostgresConn = match postgres_pool.get().await {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Cannot get connection from pool: {:?}", e);
+                continue;
+            }
+        };
+
+        for (table, days) in RETENTION_DAYS.get().unwrap() {
+            match purge_table(table, *days, &postgres_conn).await {
+                Ok(0) => (),
+                Ok(v) => info!("Purged {} rows from {}", v, table),
+                Err(e) => error!("Could not purge {}: {:?}", table, e),
+            }
+        }
+
+        let days: i32 = RETENTION_DAYS
+            .get()
+            .unwrap()
+            .values()
+            .copied()
+            .max()
+            .unwrap_or_default();
+
+        match purge_users(days, &postgres_conn).await {
+            Ok(0) => (),
+            Ok(v) => info!("Purged {} deleted users", v),
+            Err(e) => error!("Could not purge deleted users: {:?}", e),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use super::RETENTION_TABLES;
+
+use leprecon::utils::PostgresConn;
+
+/// Deletes the rows of users which were soft-deleted more than the given days ago.
+pub(super) async fn purge_table(
+    table: &str,
+    days: i32,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    db_client
+        .execute(
+            &format!("DELETE FROM {table} WHERE user_id IN (SELECT id FROM users WHERE deleted < now() - make_interval(days => $1))"),
+            &[&days],
+        )
+        .await
+}
+
+/// Deletes soft-deleted users of which no data is retained anymore.
+pub(super) async fn purge_users(
+    days: i32,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    let retained: String = RETENTION_TABLES
+        .iter()
+        .map(|t| format!("NOT EXISTS (SELECT 1 FROM {t} WHERE {t}.user_id = users.id)"))
+        .collect::<Vec<String>>()
+        .join(" AND ");
+
+    db_client
+        .execute(
+            &format!("DELETE FROM users WHERE deleted < now() - make_interval(days => $1) AND {retained}"),
+            &[&days],
+        )
+        .await
+}
@@ -0,0 +1,189 @@
+mod model;
+
+pub(crate) mod db;
+
+use self::{
+    db::{active_self_exclusion, insert_self_exclusion},
+    model::{SelfExclusion, SelfExclusionParams},
+};
+
+use crate::StateParams;
+
+use askama::Template;
+use axum::{extract::State, response::Html, Form};
+use chrono::{DateTime, Local};
+use leprecon::{
+    auth::AuthParam,
+    template::{self, Snackbar},
+    utils::{
+        extract::{extract_conn_from_pool, ValidForm},
+        PostgresConn,
+    },
+};
+use reqwest::StatusCode;
+use tracing::error;
+
+pub(super) async fn self_exclusion(
+    State(state): State<StateParams>,
+    Form(auth_param): Form<AuthParam>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    if auth_param.sub.is_empty() {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Html(snackbar.render().unwrap()),
+        );
+    };
+
+    let postgres_conn: PostgresConn = match extract_conn_from_pool(&state.2, &mut snackbar).await {
+        Ok(v) => v,
+        Err(e) => return e,
+    };
+
+    let exclusion: Option<SelfExclusion> =
+        match active_self_exclusion(&auth_param.sub, &*postgres_conn).await {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Could not fetch self-exclusion: {:?}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Html(snackbar.render().unwrap()),
+                );
+            }
+        };
+
+    let exclusion_template: template::SelfExclusion = template::SelfExclusion {
+        excluded: exclusion.is_some(),
+        until: exclusion
+            .and_then(|e| e.ends)
+            .map(|v| v.format("%Y-%m-%d %H:%M").to_string()),
+    };
+
+    (StatusCode::OK, Html(exclusion_template.render().unwrap()))
+}
+
+/// Excludes the user, an active exclusion can be extended but never shortened.
+pub(super) async fn create_self_exclusion(
+    State(state): State<StateParams>,
+    ValidForm(params): ValidForm<SelfExclusionParams>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    let postgres_conn: PostgresConn = match extract_conn_from_pool(&state.2, &mut snackbar).await {
+        Ok(v) => v,
+        Err(e) => return e,
+    };
+
+    let ends: Option<DateTime<Local>> = params.period.ends();
+
+    match active_self_exclusion(&params.sub, &*postgres_conn).await {
+        Ok(Some(active)) => {
+            let shortens: bool = match (active.ends, ends) {
+                (None, _) => true,
+                (Some(_), None) => false,
+                (Some(current), Some(new)) => new <= current,
+            };
+
+            if shortens {
+                snackbar.message = "self-exclusion-longer";
+                return (StatusCode::BAD_REQUEST, Html(snackbar.render().unwrap()));
+            }
+        }
+        Ok(None) => {}
+        Err(e) => {
+            error!("Could not fetch self-exclusion: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    if let Err(e) = insert_self_exclusion(&params.sub, ends, &postgres_conn).await {
+        error!("Cannot create self-exclusion: {:?}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Html(snackbar.render().unwrap()),
+        );
+    }
+
+    snackbar.title = "success";
+    snackbar.message = "self-exclusion-active";
+    snackbar.color = "green";
+
+    (StatusCode::OK, Html(snackbar.render().unwrap()))
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{body::Body, http::Request};
+    use reqwest::{header, Method, StatusCode};
+    use tower::ServiceExt;
+
+    use crate::fixture::{assert_body_contains, initialize, seed_database};
+
+    // Get self-exclusion
+    #[tokio::test]
+    async fn test_no_params_provided_get_self_exclusion() {
+        let app: axum::Router = initialize().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/user/self-exclusion")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_body_contains(response, &["Could not process request"]).await;
+    }
+
+    // Create self-exclusion
+    #[tokio::test]
+    async fn test_invalid_exclusion_period() {
+        let app: axum::Router = initialize().await;
+
+        let params: String = String::from("sub=auth0|0005&period=Week");
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/account/user/self-exclusion")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(params)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_create_self_exclusion() {
+        let app: axum::Router = initialize().await;
+        seed_database().await;
+
+        let params: String = String::from("sub=auth0|0005&period=Month");
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/account/user/self-exclusion")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(params)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_body_contains(response, &["Self-exclusion active"]).await;
+    }
+}
@@ -0,0 +1,46 @@
+use super::model::SelfExclusion;
+
+use chrono::{DateTime, Local};
+use leprecon::utils::PostgresConn;
+use tokio_postgres::{GenericClient, Row, Transaction};
+
+/// Returns the exclusion which lasts the longest of the ones currently active.
+pub(crate) async fn active_self_exclusion<C: GenericClient>(
+    sub: &str,
+    db_client: &C,
+) -> Result<Option<SelfExclusion>, tokio_postgres::Error> {
+    let r: Option<Row> = db_client
+        .query_opt(
+            "SELECT self_exclusions.* FROM self_exclusions INNER JOIN users ON users.id = self_exclusions.user_id WHERE sub = $1 AND starts <= now() AND (ends IS NULL OR ends > now()) ORDER BY ends DESC NULLS FIRST LIMIT 1",
+            &[&sub],
+        )
+        .await?;
+
+    Ok(r.map(|r| SelfExclusion {
+        ends: r.get("ends"),
+    }))
+}
+
+pub(super) async fn insert_self_exclusion(
+    sub: &str,
+    ends: Option<DateTime<Local>>,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    db_client
+        .execute(
+            "WITH userId AS (SELECT id FROM users WHERE sub = $1) INSERT INTO self_exclusions(starts, ends, user_id) VALUES(now(), $2, (SELECT id FROM userId))",
+            &[&sub, &ends],
+        )
+        .await
+}
+
+pub(crate) async fn delete_self_exclusions(
+    sub: &str,
+    transaction: &Transaction<'_>,
+) -> Result<Option<Row>, tokio_postgres::Error> {
+    transaction.query_opt(
+        "WITH userId AS (SELECT id FROM users WHERE sub = $1) DELETE FROM self_exclusions WHERE user_id = (SELECT id FROM userId)",
+        &[&sub],
+    )
+    .await
+}
@@ -0,0 +1,44 @@
+use chrono::{DateTime, Duration, Local};
+use leprecon::utils::validate::{Validate, ValidationError};
+use serde::Deserialize;
+
+pub(crate) struct SelfExclusion {
+    pub ends: Option<DateTime<Local>>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub(crate) enum ExclusionPeriod {
+    Month,
+    HalfYear,
+    Year,
+    Indefinite,
+}
+
+impl ExclusionPeriod {
+    /// End of an exclusion starting now, `None` when indefinite.
+    pub(super) fn ends(&self) -> Option<DateTime<Local>> {
+        match self {
+            ExclusionPeriod::Month => Some(Local::now() + Duration::days(30)),
+            ExclusionPeriod::HalfYear => Some(Local::now() + Duration::days(182)),
+            ExclusionPeriod::Year => Some(Local::now() + Duration::days(365)),
+            ExclusionPeriod::Indefinite => None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct SelfExclusionParams {
+    #[serde(default)]
+    pub sub: String,
+    pub period: ExclusionPeriod,
+}
+
+impl Validate for SelfExclusionParams {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.sub.is_empty() {
+            return Err(ValidationError::new("sub", "is required"));
+        }
+
+        Ok(())
+    }
+}
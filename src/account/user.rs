@@ -1,31 +1,46 @@
+mod country;
 mod db;
 mod model;
-mod request;
 
 use self::{
     db::{
-        create_customer_details, customer_details_exist, delete_customer_details, delete_user,
-        get_customer_details, get_user, insert_user,
+        anonymise_customer_details, delete_customer_details, delete_user, get_customer_details,
+        get_user, insert_user, soft_delete_user, upsert_customer_details,
     },
-    model::{CustomerDetails, User},
-    request::delete_user_from_auth_provider,
+    model::{CustomerDetails, CustomerDetailsParams, FieldErrors, User},
 };
 
 use crate::{
-    email::db::delete_email_sessions, user::db::update_customer_details, StateParams, AUTH_HOST,
-    CLIENT_ID, CLIENT_SECRET,
+    audit::{db::insert_audit_entry, AuditAction, AuditContext, AuditEntry},
+    deletion::{create_deletion_job, process_deletion_job},
+    email::db::delete_email_sessions,
+    kyc::db::delete_kyc,
+    ledger::db::delete_transactions,
+    limit::db::delete_limits,
+    mail::db::delete_user_mails,
+    model::ServiceToken,
+    self_exclusion::db::delete_self_exclusions,
+    StateParams, SOFT_DELETE,
 };
 
 use askama::Template;
-use axum::{extract::State, response::Html, Form};
+use axum::{
+    extract::{rejection::FormRejection, State},
+    http::{header, HeaderMap},
+    response::{Html, IntoResponse, Response},
+    Form, Json,
+};
 use indexmap::IndexMap;
 use leprecon::{
-    auth::{get_valid_jwt, AuthParam},
+    auth::AuthParam,
+    jurisdiction::Jurisdiction,
     template::{self, Snackbar},
-    utils::{extract::extract_conn_from_pool, PostgresConn, RedisConn},
+    utils::{extract::extract_conn_from_pool, PostgresConn},
+    wallet::Wallet,
 };
 use reqwest::StatusCode;
-use std::collections::HashMap;
+use std::{error::Error, num::ParseIntError};
+use tokio_postgres::Transaction;
 use tracing::{debug, error};
 
 pub(super) async fn user_information(
@@ -54,8 +69,8 @@ pub(super) async fn user_information(
         }
     };
 
-    let customer_details: CustomerDetails =
-        match get_customer_details(&auth_param.sub, &postgres_conn).await {
+    let (customer_details, version): (CustomerDetails, i32) =
+        match get_customer_details(&auth_param.sub, &*postgres_conn).await {
             Ok(v) => v,
             Err(e) => {
                 debug!("Could not get customer details: {:?}", e);
@@ -69,31 +84,88 @@ pub(super) async fn user_information(
             balance: user.balance,
             currency: user.currency.to_string(),
         },
-        name_input: template::NameInput {
-            inputs: IndexMap::from([
-                ("first_name", customer_details.first_name),
-                ("middle_name", customer_details.middle_name),
-                ("last_name", customer_details.last_name),
-            ]),
-        },
-        address_input: template::AddressInput {
-            inputs: IndexMap::from([
-                ("postal_code", customer_details.postal_code),
-                ("street_name", customer_details.street_name),
-                ("street_nr", customer_details.street_nr),
-                ("premise", customer_details.premise),
-                ("settlement", customer_details.settlement),
-                ("country", customer_details.country),
-                ("country_code", customer_details.country_code),
-            ]),
-        },
+        name_input: name_input(&customer_details, IndexMap::new(), false),
+        address_input: address_input(&customer_details, IndexMap::new(), false),
+        version_input: version_input(version, false),
     };
 
     (StatusCode::OK, Html(user_template.render().unwrap()))
 }
 
+/// Country of the user, for services which only offer games in some jurisdictions.
+pub(super) async fn user_jurisdiction(
+    _: ServiceToken,
+    State(state): State<StateParams>,
+    Form(auth_param): Form<AuthParam>,
+) -> (StatusCode, Json<Jurisdiction>) {
+    if auth_param.sub.is_empty() {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(Jurisdiction::default()),
+        );
+    };
+
+    let postgres_conn: PostgresConn = match state.2.get().await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Cannot get connection from pool: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(Jurisdiction::default()),
+            );
+        }
+    };
+
+    match get_customer_details(&auth_param.sub, &*postgres_conn).await {
+        Ok((customer_details, _)) => (
+            StatusCode::OK,
+            Json(Jurisdiction {
+                country_code: customer_details.country_code,
+            }),
+        ),
+        Err(e) => {
+            debug!("Could not get customer details: {:?}", e);
+            (StatusCode::BAD_GATEWAY, Json(Jurisdiction::default()))
+        }
+    }
+}
+
+/// Balance of the user, for game services which need to check funds before a wager.
+pub(super) async fn user_wallet(
+    _: ServiceToken,
+    State(state): State<StateParams>,
+    Form(auth_param): Form<AuthParam>,
+) -> (StatusCode, Json<Wallet>) {
+    if auth_param.sub.is_empty() {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(Wallet::default()));
+    };
+
+    let postgres_conn: PostgresConn = match state.2.get().await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Cannot get connection from pool: {:?}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(Wallet::default()));
+        }
+    };
+
+    match get_user(&auth_param.sub, &postgres_conn).await {
+        Ok(user) => (
+            StatusCode::OK,
+            Json(Wallet {
+                balance: user.balance,
+                currency: user.currency.to_string(),
+            }),
+        ),
+        Err(e) => {
+            debug!("Could not fetch balance: {:?}", e);
+            (StatusCode::NOT_FOUND, Json(Wallet::default()))
+        }
+    }
+}
+
 pub(super) async fn create_user(
     State(state): State<StateParams>,
+    context: AuditContext,
     Form(auth_param): Form<AuthParam>,
 ) -> (StatusCode, Html<String>) {
     let mut snackbar: Snackbar<'_> = Snackbar::default();
@@ -105,12 +177,24 @@ pub(super) async fn create_user(
         );
     };
 
-    let postgres_conn: PostgresConn = match extract_conn_from_pool(&state.2, &mut snackbar).await {
+    let mut postgres_conn: PostgresConn =
+        match extract_conn_from_pool(&state.2, &mut snackbar).await {
+            Ok(v) => v,
+            Err(e) => return e,
+        };
+
+    let transaction: Transaction = match postgres_conn.transaction().await {
         Ok(v) => v,
-        Err(e) => return e,
+        Err(e) => {
+            error!("Could not start transaction: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
     };
 
-    if let Err(e) = insert_user(&auth_param.sub, &postgres_conn).await {
+    if let Err(e) = insert_user(&auth_param.sub, &transaction).await {
         error!("Could not insert new user: {:?}", e);
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -118,69 +202,212 @@ pub(super) async fn create_user(
         );
     }
 
-    snackbar.title = "Succes";
-    snackbar.message = "Created user sucessfully";
+    let entry: AuditEntry = AuditEntry {
+        sub: &auth_param.sub,
+        actor: &auth_param.sub,
+        action: AuditAction::UserCreated,
+        diff: None,
+        context: &context,
+    };
+
+    // Without its audit entry the user is not created
+    if let Err(e) = insert_audit_entry(&entry, &transaction).await {
+        error!("Could not write audit log: {:?}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Html(snackbar.render().unwrap()),
+        );
+    }
+
+    if let Err(e) = transaction.commit().await {
+        error!("Could not commit new user: {:?}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Html(snackbar.render().unwrap()),
+        );
+    }
+
+    snackbar.title = "success";
+    snackbar.message = "user-created";
     snackbar.color = "green";
 
     (StatusCode::OK, Html(snackbar.render().unwrap()))
 }
 
+/// Creates or updates the customer details.
+///
+/// The version of the details the user edited is taken from the `If-Match` header, or the hidden
+/// version field. When the details were changed in the meantime nothing is written.
 pub(super) async fn update_user_information(
     State(state): State<StateParams>,
-    Form(params): Form<HashMap<String, String>>,
-) -> (StatusCode, Html<String>) {
+    context: AuditContext,
+    headers: HeaderMap,
+    params: Result<Form<CustomerDetailsParams>, FormRejection>,
+) -> Response {
     let mut snackbar: Snackbar<'_> = Snackbar::default();
 
-    let sub: &String = match params.get("sub") {
-        Some(v) => v,
-        None => {
+    let params: CustomerDetailsParams = match params {
+        Ok(Form(v)) => v,
+        Err(e) => {
+            debug!("Could not deserialize customer details: {:?}", e);
+            let message: String = e.body_text();
+            snackbar.message = &message;
             return (
                 StatusCode::UNPROCESSABLE_ENTITY,
                 Html(snackbar.render().unwrap()),
-            );
+            )
+                .into_response();
         }
     };
 
-    let customer_details: CustomerDetails = CustomerDetails {
-        first_name: params.get("first_name").cloned(),
-        middle_name: params.get("middle_name").cloned(),
-        last_name: params.get("last_name").cloned(),
-        postal_code: params.get("postal_code").cloned(),
-        street_name: params.get("street_name").cloned(),
-        street_nr: params.get("street_nr").cloned(),
-        premise: params.get("premise").cloned(),
-        settlement: params.get("settlement").cloned(),
-        country: params.get("country").cloned(),
-        country_code: params.get("country_code").cloned(),
+    if params.sub.is_empty() {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Html(snackbar.render().unwrap()),
+        )
+            .into_response();
     };
 
-    let postgres_conn: PostgresConn = match extract_conn_from_pool(&state.2, &mut snackbar).await {
+    let sub: &String = &params.sub;
+
+    let version: Option<i32> = match expected_version(&headers, params.version) {
         Ok(v) => v,
-        Err(e) => return e,
+        Err(e) => {
+            debug!("Invalid version: {:?}", e);
+            snackbar.message = "details-invalid-version";
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Html(snackbar.render().unwrap()),
+            )
+                .into_response();
+        }
     };
 
-    if customer_details_exist(sub, &postgres_conn).await {
-        debug!("Already created customer details entry");
-        if let Err(e) = update_customer_details(sub, customer_details, &postgres_conn).await {
-            error!("Cannot update customer details entry: {:?}", e);
+    let (customer_details, errors): (CustomerDetails, FieldErrors) = params.normalise();
+    if !errors.is_empty() {
+        debug!("Invalid customer details: {:?}", errors);
+        snackbar.message = "details-invalid";
+
+        // Swap the inputs out of band, so the errors show next to the fields
+        let body: String = [
+            snackbar.render().unwrap(),
+            name_input(&customer_details, errors.clone(), true)
+                .render()
+                .unwrap(),
+            address_input(&customer_details, errors, true)
+                .render()
+                .unwrap(),
+        ]
+        .concat();
+
+        return (StatusCode::UNPROCESSABLE_ENTITY, Html(body)).into_response();
+    }
+
+    let mut postgres_conn: PostgresConn =
+        match extract_conn_from_pool(&state.2, &mut snackbar).await {
+            Ok(v) => v,
+            Err(e) => return e.into_response(),
+        };
+
+    let transaction: Transaction = match postgres_conn.transaction().await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Could not start transaction: {:?}", e);
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Html(snackbar.render().unwrap()),
-            );
+            )
+                .into_response();
         }
-    } else if let Err(e) = create_customer_details(sub, customer_details, &postgres_conn).await {
-        error!("Cannot create customer details entry: {:?}", e);
+    };
+
+    let before: CustomerDetails = match get_customer_details(sub, &transaction).await {
+        Ok((v, _)) => v,
+        Err(e) => {
+            debug!("Could not get customer details: {:?}", e);
+            CustomerDetails::default()
+        }
+    };
+    // Only the names, the audit log outlives the personal data
+    let changed: serde_json::Value = before.changed_fields(&customer_details);
+
+    let new_version: i32 =
+        match upsert_customer_details(sub, &customer_details, version, &transaction).await {
+            Ok(Some(v)) => v,
+            Ok(None) => {
+                debug!("Customer details of {} were changed elsewhere", sub);
+                snackbar.message = "details-changed-elsewhere";
+                return (StatusCode::CONFLICT, Html(snackbar.render().unwrap())).into_response();
+            }
+            Err(e) => {
+                error!("Cannot upsert customer details entry: {:?}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Html(snackbar.render().unwrap()),
+                )
+                    .into_response();
+            }
+        };
+
+    let entry: AuditEntry = AuditEntry {
+        sub,
+        actor: sub,
+        action: match new_version {
+            1 => AuditAction::CustomerDetailsCreated,
+            _ => AuditAction::CustomerDetailsUpdated,
+        },
+        diff: Some(changed),
+        context: &context,
+    };
+
+    // Without its audit entry the change is not made
+    if let Err(e) = insert_audit_entry(&entry, &transaction).await {
+        error!("Could not write audit log: {:?}", e);
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
             Html(snackbar.render().unwrap()),
-        );
+        )
+            .into_response();
     }
 
-    snackbar.title = "Succes";
-    snackbar.message = "Updated personal details succesfully";
+    if let Err(e) = transaction.commit().await {
+        error!("Could not commit customer details: {:?}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Html(snackbar.render().unwrap()),
+        )
+            .into_response();
+    }
+
+    snackbar.title = "success";
+    snackbar.message = "details-updated";
     snackbar.color = "green";
 
-    (StatusCode::OK, Html(snackbar.render().unwrap()))
+    // Hand out the new version, so the next update from the same form passes the check
+    let body: String = [
+        snackbar.render().unwrap(),
+        version_input(new_version, true).render().unwrap(),
+    ]
+    .concat();
+
+    (
+        StatusCode::OK,
+        [(header::ETAG, format!("\"{new_version}\""))],
+        Html(body),
+    )
+        .into_response()
+}
+
+/// Version from the `If-Match` header, or else the form. `*` matches any version.
+fn expected_version(
+    headers: &HeaderMap,
+    form_version: Option<i32>,
+) -> Result<Option<i32>, ParseIntError> {
+    match headers.get(header::IF_MATCH).and_then(|v| v.to_str().ok()) {
+        Some("*") => Ok(None),
+        Some(v) => Ok(Some(v.trim_start_matches("W/").trim_matches('"').parse()?)),
+        None => Ok(form_version),
+    }
 }
 
 pub(super) async fn user_balance(
@@ -220,8 +447,12 @@ pub(super) async fn user_balance(
     (StatusCode::OK, Html(balance.render().unwrap()))
 }
 
+/// Deletes the local account data in one transaction, and leaves the auth provider to a deletion job.
+///
+/// The job is tried right away, when it fails it is retried in the background.
 pub(super) async fn delete_account(
     State(state): State<StateParams>,
+    context: AuditContext,
     Form(auth_param): Form<AuthParam>,
 ) -> (StatusCode, Html<String>) {
     let mut snackbar: Snackbar<'_> = Snackbar::default();
@@ -233,55 +464,44 @@ pub(super) async fn delete_account(
         );
     };
 
-    let postgres_conn: PostgresConn = match extract_conn_from_pool(&state.2, &mut snackbar).await {
-        Ok(v) => v,
-        Err(e) => return e,
-    };
-
-    if let Err(e) = delete_customer_details(&auth_param.sub, &postgres_conn).await {
-        error!("Cannot delete customer details entry: {:?}", e);
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Html(snackbar.render().unwrap()),
-        );
-    }
-
-    if let Err(e) = delete_email_sessions(&auth_param.sub, &postgres_conn).await {
-        error!("Cannot delete session entrie(s): {:?}", e);
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Html(snackbar.render().unwrap()),
-        );
-    }
+    let mut postgres_conn: PostgresConn =
+        match extract_conn_from_pool(&state.2, &mut snackbar).await {
+            Ok(v) => v,
+            Err(e) => return e,
+        };
 
-    if let Err(e) = delete_user(&auth_param.sub, &postgres_conn).await {
-        error!("Cannot delete user entry: {:?}", e);
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Html(snackbar.render().unwrap()),
-        );
-    }
+    let before: CustomerDetails = match get_customer_details(&auth_param.sub, &*postgres_conn).await
+    {
+        Ok((v, _)) => v,
+        Err(e) => {
+            debug!("Could not get customer details: {:?}", e);
+            CustomerDetails::default()
+        }
+    };
 
-    let mut lock: tokio::sync::MutexGuard<'_, leprecon::auth::JWT> = state.0.lock().await;
-    let req_client: &reqwest::Client = &state.1;
+    // Soft deletes keep the country, see `anonymise_customer_details`
+    let after: CustomerDetails = match *SOFT_DELETE.get().unwrap() {
+        true => CustomerDetails {
+            country: before.country.clone(),
+            country_code: before.country_code.clone(),
+            ..Default::default()
+        },
+        false => CustomerDetails::default(),
+    };
 
-    let redis_conn: RedisConn = match extract_conn_from_pool(&state.3, &mut snackbar).await {
-        Ok(v) => v,
-        Err(e) => return e,
+    let entry: AuditEntry = AuditEntry {
+        sub: &auth_param.sub,
+        actor: &auth_param.sub,
+        action: AuditAction::AccountDeleted,
+        // The log is append-only, so the erased values must not end up in it
+        diff: Some(before.changed_fields(&after)),
+        context: &context,
     };
 
-    *lock = match get_valid_jwt(
-        redis_conn,
-        req_client,
-        AUTH_HOST.get().unwrap(),
-        CLIENT_ID.get().unwrap(),
-        CLIENT_SECRET.get().unwrap(),
-    )
-    .await
-    {
+    let transaction: Transaction = match postgres_conn.transaction().await {
         Ok(v) => v,
         Err(e) => {
-            error!("Could not get valid jwt: {:?}", e);
+            error!("Could not start transaction: {:?}", e);
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Html(snackbar.render().unwrap()),
@@ -289,18 +509,10 @@ pub(super) async fn delete_account(
         }
     };
 
-    let res: reqwest::Response = match delete_user_from_auth_provider(
-        &auth_param.sub,
-        req_client,
-        AUTH_HOST.get().unwrap(),
-        &lock.access_token,
-    )
-    .await
-    {
+    let job_id: i32 = match delete_account_data(&entry, &transaction).await {
         Ok(v) => v,
         Err(e) => {
-            error!("Cannot delete user from auth provider: {:?}", e);
-
+            error!("Cannot delete account data: {:?}", e);
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Html(snackbar.render().unwrap()),
@@ -308,28 +520,116 @@ pub(super) async fn delete_account(
         }
     };
 
-    if res.status() != reqwest::StatusCode::NO_CONTENT {
-        error!("Could not delete user at auth provider");
+    if let Err(e) = transaction.commit().await {
+        error!("Could not commit account deletion: {:?}", e);
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
             Html(snackbar.render().unwrap()),
         );
     }
 
-    snackbar.title = "Succes";
-    snackbar.message = "Succesfully deleted account";
+    // Release the connection, the job takes its own
+    drop(postgres_conn);
+
+    if let Err(e) = process_deletion_job(&state, job_id).await {
+        error!("Deletion job {} failed, retrying later: {}", job_id, e);
+
+        snackbar.title = "success";
+        snackbar.message = "account-deletion-pending";
+        snackbar.color = "green";
+
+        return (StatusCode::ACCEPTED, Html(snackbar.render().unwrap()));
+    }
+
+    snackbar.title = "success";
+    snackbar.message = "account-deleted";
     snackbar.color = "green";
 
     (StatusCode::OK, Html(snackbar.render().unwrap()))
 }
 
+/// Removes the data of the user, and schedules the deletion at the auth provider.
+///
+/// In soft delete mode the personal details are anonymised, and the remaining data is kept
+/// until the purge job removes it after the retention period.
+async fn delete_account_data(
+    entry: &AuditEntry<'_>,
+    transaction: &Transaction<'_>,
+) -> Result<i32, Box<dyn Error + Send + Sync>> {
+    let sub: &str = entry.sub;
+
+    let deleted: u64 = if *SOFT_DELETE.get().unwrap() {
+        anonymise_customer_details(sub, transaction).await?;
+        delete_email_sessions(sub, transaction).await?;
+        delete_user_mails(sub, transaction).await?;
+        soft_delete_user(sub, transaction).await?
+    } else {
+        delete_customer_details(sub, transaction).await?;
+        delete_email_sessions(sub, transaction).await?;
+        delete_user_mails(sub, transaction).await?;
+        delete_limits(sub, transaction).await?;
+        delete_self_exclusions(sub, transaction).await?;
+        delete_kyc(sub, transaction).await?;
+        delete_transactions(sub, transaction).await?;
+        delete_user(sub, transaction).await?
+    };
+
+    if deleted == 0 {
+        Err("User does not exist")?
+    }
+
+    insert_audit_entry(entry, transaction).await?;
+
+    Ok(create_deletion_job(sub, transaction).await?)
+}
+
+fn name_input<'a>(
+    customer_details: &CustomerDetails,
+    errors: FieldErrors,
+    oob: bool,
+) -> template::NameInput<'a> {
+    template::NameInput {
+        inputs: IndexMap::from([
+            ("first_name", customer_details.first_name.clone()),
+            ("middle_name", customer_details.middle_name.clone()),
+            ("last_name", customer_details.last_name.clone()),
+        ]),
+        errors,
+        oob,
+    }
+}
+
+fn address_input<'a>(
+    customer_details: &CustomerDetails,
+    errors: FieldErrors,
+    oob: bool,
+) -> template::AddressInput<'a> {
+    template::AddressInput {
+        inputs: IndexMap::from([
+            ("postal_code", customer_details.postal_code.clone()),
+            ("street_name", customer_details.street_name.clone()),
+            ("street_nr", customer_details.street_nr.clone()),
+            ("premise", customer_details.premise.clone()),
+            ("settlement", customer_details.settlement.clone()),
+            ("country", customer_details.country.clone()),
+            ("country_code", customer_details.country_code.clone()),
+        ]),
+        errors,
+        oob,
+    }
+}
+
+fn version_input(version: i32, oob: bool) -> template::VersionInput {
+    template::VersionInput { version, oob }
+}
+
 #[cfg(test)]
 mod test {
     use axum::{body::Body, http::Request};
     use reqwest::{header, Method, StatusCode};
     use tower::ServiceExt;
 
-    use crate::fixture::{assert_body_contains, initialize, seed_database};
+    use crate::fixture::{assert_body_contains, initialize, seed_database, service_bearer};
 
     // Get user information
     #[tokio::test]
@@ -387,6 +687,101 @@ mod test {
         assert_body_contains(response, &["id: auth0|0002", "balance: 0 EUR"]).await;
     }
 
+    // Get jurisdiction
+    #[tokio::test]
+    async fn test_no_params_provided_get_jurisdiction() {
+        let app: axum::Router = initialize().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/user/jurisdiction")
+                    .header(header::AUTHORIZATION, service_bearer())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_no_country_jurisdiction() {
+        let app: axum::Router = initialize().await;
+        seed_database().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/user/jurisdiction?sub=auth0|0002")
+                    .header(header::AUTHORIZATION, service_bearer())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_body_contains(response, &["\"country_code\":null"]).await;
+    }
+
+    // Get wallet
+    #[tokio::test]
+    async fn test_get_wallet_without_token() {
+        let app: axum::Router = initialize().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/user/wallet?sub=auth0|0002")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_no_params_provided_get_wallet() {
+        let app: axum::Router = initialize().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/user/wallet")
+                    .header(header::AUTHORIZATION, service_bearer())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_get_wallet() {
+        let app: axum::Router = initialize().await;
+        seed_database().await;
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .uri("/account/user/wallet?sub=auth0|0002")
+                    .header(header::AUTHORIZATION, service_bearer())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_body_contains(response, &["\"currency\":\"EUR\""]).await;
+    }
+
     // Create user
     #[tokio::test]
     async fn test_no_params_provided_create_user() {
@@ -506,13 +901,13 @@ mod test {
         let first_name: &str = "first";
         let middle_name: &str = "middle";
         let last_name: &str = "last";
-        let postal_code: &str = "postal";
+        let postal_code: &str = "1234AB";
         let street_name: &str = "street";
         let street_nr: &str = "nr";
         let premise: &str = "premise";
         let settlement: &str = "settlement";
-        let country: &str = "country";
-        let country_code: &str = "code";
+        let country: &str = "Netherlands";
+        let country_code: &str = "nl";
 
         let params: String = format!("sub=auth0|0000&first_name={first_name}&middle_name={middle_name}&last_name={last_name}&postal_code={postal_code}&street_name={street_name}&street_nr={street_nr}&premise={premise}&settlement={settlement}&country={country}&country_code={country_code}");
 
@@ -555,6 +950,82 @@ mod test {
         assert_body_contains(response, &["Updated personal details succesfully"]).await;
     }
 
+    #[tokio::test]
+    async fn test_update_user_information_unknown_field() {
+        let app: axum::Router = initialize().await;
+
+        let params: String = String::from("sub=auth0|0000&nickname=Test");
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::PUT)
+                    .uri("/account/user/information")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(params)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_body_contains(response, &["unknown field `nickname`"]).await;
+    }
+
+    #[tokio::test]
+    async fn test_update_user_information_invalid_fields() {
+        let app: axum::Router = initialize().await;
+
+        let params: String =
+            String::from("sub=auth0|0000&country_code=XX&street_nr=12345678901&postal_code=1234");
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::PUT)
+                    .uri("/account/user/information")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(params)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_body_contains(
+            response,
+            &[
+                "Invalid personal details",
+                "Unknown country code",
+                "At most 10 characters",
+                "hx-swap-oob",
+            ],
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_update_user_information_invalid_postal_code() {
+        let app: axum::Router = initialize().await;
+
+        let params: String = String::from("sub=auth0|0000&country_code=NL&postal_code=12345");
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::PUT)
+                    .uri("/account/user/information")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(params)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_body_contains(response, &["Invalid postal code for NL"]).await;
+    }
+
     // Get user balance
     #[tokio::test]
     async fn test_no_params_provided_get_user_balance() {
@@ -678,4 +1149,68 @@ mod test {
         assert_eq!(response.status(), StatusCode::OK);
         assert_body_contains(response, &["Succesfully deleted account"]).await;
     }
+
+    #[tokio::test]
+    async fn test_update_user_information_stale_version() {
+        let app: axum::Router = initialize().await;
+        seed_database().await;
+
+        let params: String = String::from("sub=auth0|0003&first_name=First");
+
+        let response: axum::http::Response<Body> = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::PUT)
+                    .uri("/account/user/information")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(params)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().contains_key(header::ETAG));
+
+        let params: String = String::from("sub=auth0|0003&first_name=Second&version=0");
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::PUT)
+                    .uri("/account/user/information")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(params)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_body_contains(response, &["Your details were changed elsewhere"]).await;
+    }
+
+    #[tokio::test]
+    async fn test_update_user_information_invalid_if_match() {
+        let app: axum::Router = initialize().await;
+
+        let params: String = String::from("sub=auth0|0003&first_name=First");
+
+        let response: axum::http::Response<Body> = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::PUT)
+                    .uri("/account/user/information")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .header(header::IF_MATCH, "\"abc\"")
+                    .body(params)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_body_contains(response, &["Invalid version"]).await;
+    }
 }
@@ -0,0 +1,47 @@
+use regex::Regex;
+
+/// ISO 3166-1 alpha-2 country codes.
+pub(super) const COUNTRY_CODES: [&str; 249] = [
+    "AD", "AE", "AF", "AG", "AI", "AL", "AM", "AO", "AQ", "AR", "AS", "AT", "AU", "AW", "AX", "AZ",
+    "BA", "BB", "BD", "BE", "BF", "BG", "BH", "BI", "BJ", "BL", "BM", "BN", "BO", "BQ", "BR", "BS",
+    "BT", "BV", "BW", "BY", "BZ", "CA", "CC", "CD", "CF", "CG", "CH", "CI", "CK", "CL", "CM", "CN",
+    "CO", "CR", "CU", "CV", "CW", "CX", "CY", "CZ", "DE", "DJ", "DK", "DM", "DO", "DZ", "EC", "EE",
+    "EG", "EH", "ER", "ES", "ET", "FI", "FJ", "FK", "FM", "FO", "FR", "GA", "GB", "GD", "GE", "GF",
+    "GG", "GH", "GI", "GL", "GM", "GN", "GP", "GQ", "GR", "GS", "GT", "GU", "GW", "GY", "HK", "HM",
+    "HN", "HR", "HT", "HU", "ID", "IE", "IL", "IM", "IN", "IO", "IQ", "IR", "IS", "IT", "JE", "JM",
+    "JO", "JP", "KE", "KG", "KH", "KI", "KM", "KN", "KP", "KR", "KW", "KY", "KZ", "LA", "LB", "LC",
+    "LI", "LK", "LR", "LS", "LT", "LU", "LV", "LY", "MA", "MC", "MD", "ME", "MF", "MG", "MH", "MK",
+    "ML", "MM", "MN", "MO", "MP", "MQ", "MR", "MS", "MT", "MU", "MV", "MW", "MX", "MY", "MZ", "NA",
+    "NC", "NE", "NF", "NG", "NI", "NL", "NO", "NP", "NR", "NU", "NZ", "OM", "PA", "PE", "PF", "PG",
+    "PH", "PK", "PL", "PM", "PN", "PR", "PS", "PT", "PW", "PY", "QA", "RE", "RO", "RS", "RU", "RW",
+    "SA", "SB", "SC", "SD", "SE", "SG", "SH", "SI", "SJ", "SK", "SL", "SM", "SN", "SO", "SR", "SS",
+    "ST", "SV", "SX", "SY", "SZ", "TC", "TD", "TF", "TG", "TH", "TJ", "TK", "TL", "TM", "TN", "TO",
+    "TR", "TT", "TV", "TW", "TZ", "UA", "UG", "UM", "US", "UY", "UZ", "VA", "VC", "VE", "VG", "VI",
+    "VN", "VU", "WF", "WS", "YE", "YT", "ZA", "ZM", "ZW",
+];
+
+/// Postal code format of the country, countries without a known format accept any postal code.
+fn postal_code_pattern(country_code: &str) -> Option<&'static str> {
+    match country_code {
+        "AT" | "AU" | "BE" | "CH" | "DK" | "LU" | "NO" => Some(r"^\d{4}$"),
+        "DE" | "ES" | "FI" | "FR" | "IT" => Some(r"^\d{5}$"),
+        "BR" => Some(r"^\d{5}-?\d{3}$"),
+        "CA" => Some(r"^[A-Z]\d[A-Z] ?\d[A-Z]\d$"),
+        "GB" => Some(r"^[A-Z]{1,2}\d[A-Z\d]? ?\d[A-Z]{2}$"),
+        "IE" => Some(r"^[A-Z]\d[\dW] ?[A-Z\d]{4}$"),
+        "JP" => Some(r"^\d{3}-\d{4}$"),
+        "NL" => Some(r"^\d{4} ?[A-Z]{2}$"),
+        "PL" => Some(r"^\d{2}-\d{3}$"),
+        "PT" => Some(r"^\d{4}-\d{3}$"),
+        "SE" => Some(r"^\d{3} ?\d{2}$"),
+        "US" => Some(r"^\d{5}(-\d{4})?$"),
+        _ => None,
+    }
+}
+
+pub(super) fn valid_postal_code(country_code: &str, postal_code: &str) -> bool {
+    match postal_code_pattern(country_code) {
+        Some(v) => Regex::new(v).unwrap().is_match(postal_code),
+        None => true,
+    }
+}
@@ -2,12 +2,11 @@ use super::model::{Currency, CustomerDetails, User};
 
 use leprecon::utils::PostgresConn;
 use std::{error::Error, str::FromStr};
-use tokio_postgres::Row;
-use tracing::debug;
+use tokio_postgres::{GenericClient, Row, Transaction};
 
-pub(super) async fn insert_user(
+pub(super) async fn insert_user<C: GenericClient>(
     sub: &str,
-    db_client: &PostgresConn<'_>,
+    db_client: &C,
 ) -> Result<Vec<Row>, tokio_postgres::Error> {
     db_client
         .query(
@@ -19,7 +18,7 @@ pub(super) async fn insert_user(
 
 pub(super) async fn get_user(sub: &str, conn: &PostgresConn<'_>) -> Result<User, Box<dyn Error>> {
     let r: Row = conn
-        .query_one("SELECT * FROM users INNER JOIN currencies ON currencies.id = users.currency_id WHERE sub=$1 LIMIT 1", &[&sub])
+        .query_one("SELECT * FROM users INNER JOIN currencies ON currencies.id = users.currency_id WHERE sub=$1 AND deleted IS NULL LIMIT 1", &[&sub])
         .await?;
 
     Ok(User {
@@ -31,33 +30,32 @@ pub(super) async fn get_user(sub: &str, conn: &PostgresConn<'_>) -> Result<User,
 
 pub(super) async fn delete_user(
     sub: &str,
-    db_client: &PostgresConn<'_>,
-) -> Result<Vec<Row>, tokio_postgres::Error> {
-    db_client
-        .query("DELETE FROM users WHERE sub = $1", &[&sub])
+    transaction: &Transaction<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    transaction
+        .execute("DELETE FROM users WHERE sub = $1", &[&sub])
         .await
 }
 
-pub(super) async fn customer_details_exist(sub: &str, db_client: &PostgresConn<'_>) -> bool {
-    match db_client
-        .query_one(
-            "SELECT * FROM customer_details LEFT JOIN users ON users.id = customer_details.user_id WHERE sub=$1 LIMIT 1",
+/// Marks the user as deleted and forgets their email address, the row is purged once the
+/// retention period has passed.
+pub(super) async fn soft_delete_user(
+    sub: &str,
+    transaction: &Transaction<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    transaction
+        .execute(
+            "UPDATE users SET deleted = now(), email = NULL WHERE sub = $1 AND deleted IS NULL",
             &[&sub],
         )
         .await
-    {
-        Err(e) => {
-            debug!("No customer details in db: {:?}", e);
-            false
-        }
-        _ => true,
-    }
 }
 
-pub(super) async fn get_customer_details(
+/// Details of the user with their version, users without details are at version 0.
+pub(super) async fn get_customer_details<C: GenericClient>(
     sub: &str,
-    db_client: &PostgresConn<'_>,
-) -> Result<CustomerDetails, Box<dyn Error>> {
+    db_client: &C,
+) -> Result<(CustomerDetails, i32), Box<dyn Error>> {
     let r: Row = db_client
         .query_one(
             "SELECT * FROM customer_details RIGHT JOIN users ON users.id = customer_details.user_id WHERE users.sub=$1 LIMIT 1",
@@ -65,7 +63,9 @@ pub(super) async fn get_customer_details(
         )
         .await?;
 
-    Ok(CustomerDetails {
+    let version: Option<i32> = r.get("version");
+
+    let customer_details: CustomerDetails = CustomerDetails {
         first_name: r.get("first_name"),
         middle_name: r.get("middle_name"),
         last_name: r.get("last_name"),
@@ -76,42 +76,50 @@ pub(super) async fn get_customer_details(
         settlement: r.get("settlement"),
         country: r.get("country"),
         country_code: r.get("country_code"),
-    })
-}
+    };
 
-pub(super) async fn create_customer_details(
-    sub: &str,
-    customer_details: CustomerDetails,
-    db_client: &PostgresConn<'_>,
-) -> Result<Vec<Row>, tokio_postgres::Error> {
-    db_client
-        .query(
-            "WITH userId AS (SELECT id FROM users WHERE sub = $1) INSERT INTO customer_details(first_name, middle_name, last_name, postal_code, street_name, street_nr, premise, settlement, country, country_code, user_id) VALUES($2, $3, $4, $5, $6, $7, $8, $9, $10, $11, (SELECT id FROM userId))",
-            &[&sub, &customer_details.first_name, &customer_details.middle_name, &customer_details.last_name, &customer_details.postal_code, &customer_details.street_name, &customer_details.street_nr, &customer_details.premise, &customer_details.settlement, &customer_details.country, &customer_details.country_code],
-        )
-        .await
+    Ok((customer_details, version.unwrap_or_default()))
 }
 
-pub(super) async fn update_customer_details(
+/// Creates the details, or updates them when they are still at the expected version.
+///
+/// Returns the new version, or `None` when the details were changed in the meantime. Without
+/// an expected version the details are always written.
+pub(super) async fn upsert_customer_details<C: GenericClient>(
     sub: &str,
-    customer_details: CustomerDetails,
-    db_client: &PostgresConn<'_>,
-) -> Result<Vec<Row>, tokio_postgres::Error> {
-    db_client
-        .query(
-            "WITH userId AS (SELECT id FROM users WHERE sub = $1) UPDATE customer_details SET first_name = $2, middle_name = $3, last_name = $4, postal_code = $5, street_name = $6, street_nr = $7, premise = $8, settlement = $9, country = $10, country_code = $11 WHERE user_id = (SELECT id FROM userId)",
-            &[&sub, &customer_details.first_name, &customer_details.middle_name, &customer_details.last_name, &customer_details.postal_code, &customer_details.street_name, &customer_details.street_nr, &customer_details.premise, &customer_details.settlement, &customer_details.country, &customer_details.country_code],
+    customer_details: &CustomerDetails,
+    version: Option<i32>,
+    db_client: &C,
+) -> Result<Option<i32>, tokio_postgres::Error> {
+    let r: Option<Row> = db_client
+        .query_opt(
+            "WITH userId AS (SELECT id FROM users WHERE sub = $1) INSERT INTO customer_details(first_name, middle_name, last_name, postal_code, street_name, street_nr, premise, settlement, country, country_code, user_id, version) VALUES($2, $3, $4, $5, $6, $7, $8, $9, $10, $11, (SELECT id FROM userId), 1) ON CONFLICT (user_id) DO UPDATE SET first_name = EXCLUDED.first_name, middle_name = EXCLUDED.middle_name, last_name = EXCLUDED.last_name, postal_code = EXCLUDED.postal_code, street_name = EXCLUDED.street_name, street_nr = EXCLUDED.street_nr, premise = EXCLUDED.premise, settlement = EXCLUDED.settlement, country = EXCLUDED.country, country_code = EXCLUDED.country_code, version = customer_details.version + 1 WHERE $12::integer IS NULL OR customer_details.version = $12 RETURNING version",
+            &[&sub, &customer_details.first_name, &customer_details.middle_name, &customer_details.last_name, &customer_details.postal_code, &customer_details.street_name, &customer_details.street_nr, &customer_details.premise, &customer_details.settlement, &customer_details.country, &customer_details.country_code, &version],
         )
-        .await
+        .await?;
+
+    Ok(r.map(|r| r.get("version")))
 }
 
 pub(super) async fn delete_customer_details(
     sub: &str,
-    db_client: &PostgresConn<'_>,
+    transaction: &Transaction<'_>,
 ) -> Result<Option<Row>, tokio_postgres::Error> {
-    db_client.query_opt(
+    transaction.query_opt(
         "WITH userId AS (SELECT id FROM users WHERE sub = $1) DELETE FROM customer_details WHERE user_id = (SELECT id FROM userId)",
         &[&sub],
     )
     .await
 }
+
+/// Clears the personal fields, the country is kept for regulatory reporting.
+pub(super) async fn anonymise_customer_details(
+    sub: &str,
+    transaction: &Transaction<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    transaction.execute(
+        "WITH userId AS (SELECT id FROM users WHERE sub = $1) UPDATE customer_details SET first_name = NULL, middle_name = NULL, last_name = NULL, postal_code = NULL, street_name = NULL, street_nr = NULL, premise = NULL, settlement = NULL WHERE user_id = (SELECT id FROM userId)",
+        &[&sub],
+    )
+    .await
+}
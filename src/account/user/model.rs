@@ -1,3 +1,9 @@
+use super::country::{valid_postal_code, COUNTRY_CODES};
+
+use indexmap::IndexMap;
+use leprecon::i18n::{translate, translate_with, FluentArgs};
+use serde::Deserialize;
+use serde_json::Value;
 use std::{
     error::Error,
     fmt::{self, Display},
@@ -22,6 +28,7 @@ pub(super) struct User {
     pub currency: Currency,
 }
 
+#[derive(Default)]
 pub(super) struct CustomerDetails {
     pub first_name: Option<String>,
     pub middle_name: Option<String>,
@@ -35,6 +42,119 @@ pub(super) struct CustomerDetails {
     pub country_code: Option<String>,
 }
 
+impl CustomerDetails {
+    fn fields(&self) -> [(&'static str, &Option<String>); 10] {
+        [
+            ("first_name", &self.first_name),
+            ("middle_name", &self.middle_name),
+            ("last_name", &self.last_name),
+            ("postal_code", &self.postal_code),
+            ("street_name", &self.street_name),
+            ("street_nr", &self.street_nr),
+            ("premise", &self.premise),
+            ("settlement", &self.settlement),
+            ("country", &self.country),
+            ("country_code", &self.country_code),
+        ]
+    }
+
+    /// Names of the fields which differ, without their values.
+    pub(super) fn changed_fields(&self, after: &CustomerDetails) -> Value {
+        self.fields()
+            .into_iter()
+            .zip(after.fields())
+            .filter(|((_, before), (_, after))| before != after)
+            .map(|((name, _), _)| Value::from(name))
+            .collect()
+    }
+}
+
+/// Customer details as submitted by the user, see [`CustomerDetailsParams::normalise`].
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct CustomerDetailsParams {
+    #[serde(default)]
+    pub sub: String,
+    pub first_name: Option<String>,
+    pub middle_name: Option<String>,
+    pub last_name: Option<String>,
+    pub postal_code: Option<String>,
+    pub street_name: Option<String>,
+    pub street_nr: Option<String>,
+    pub premise: Option<String>,
+    pub settlement: Option<String>,
+    pub country: Option<String>,
+    pub country_code: Option<String>,
+    pub version: Option<i32>,
+}
+
+/// Field errors keyed by the input name.
+pub(super) type FieldErrors = IndexMap<&'static str, String>;
+
+impl CustomerDetailsParams {
+    /// Trims the fields, and validates them against the length limits and country formats.
+    ///
+    /// Empty fields become `None`, country and postal codes are uppercased. The details are
+    /// only valid when no field errors are returned.
+    pub(super) fn normalise(&self) -> (CustomerDetails, FieldErrors) {
+        let mut errors: FieldErrors = IndexMap::new();
+
+        let mut field = |name: &'static str, value: &Option<String>, max: usize| {
+            let value: Option<String> = value
+                .as_deref()
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_owned);
+
+            if value.as_ref().is_some_and(|v| v.chars().count() > max) {
+                let mut args: FluentArgs = FluentArgs::new();
+                args.set("max", max);
+                errors.insert(name, translate_with("details-too-long", &args));
+            }
+
+            value
+        };
+
+        let mut customer_details: CustomerDetails = CustomerDetails {
+            first_name: field("first_name", &self.first_name, 100),
+            middle_name: field("middle_name", &self.middle_name, 100),
+            last_name: field("last_name", &self.last_name, 100),
+            postal_code: field("postal_code", &self.postal_code, 16),
+            street_name: field("street_name", &self.street_name, 100),
+            street_nr: field("street_nr", &self.street_nr, 10),
+            premise: field("premise", &self.premise, 100),
+            settlement: field("settlement", &self.settlement, 100),
+            country: field("country", &self.country, 100),
+            country_code: field("country_code", &self.country_code, 2),
+        };
+
+        customer_details.country_code = customer_details.country_code.map(|v| v.to_uppercase());
+        customer_details.postal_code = customer_details.postal_code.map(|v| v.to_uppercase());
+
+        if let Some(code) = &customer_details.country_code {
+            if !COUNTRY_CODES.contains(&code.as_str()) {
+                errors.insert("country_code", translate("details-country-unknown"));
+            }
+        }
+
+        if let (Some(code), Some(postal_code)) = (
+            &customer_details.country_code,
+            &customer_details.postal_code,
+        ) {
+            if !errors.contains_key("postal_code") && !valid_postal_code(code, postal_code) {
+                let mut args: FluentArgs = FluentArgs::new();
+                args.set("country", code.as_str());
+                errors.insert(
+                    "postal_code",
+                    translate_with("details-postal-code-invalid", &args),
+                );
+            }
+        }
+
+        (customer_details, errors)
+    }
+}
+
 #[derive(Debug)]
 pub(super) struct ParseCurrencyError;
 
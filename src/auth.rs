@@ -8,6 +8,7 @@ use self::{db::store_jwt, request::jwt_from_auth_provider};
 
 use crate::{auth::db::get_jwt_from_valkey, utils::RedisConn};
 
+use axum::http::{header, HeaderMap};
 use reqwest::StatusCode;
 use std::error::Error;
 use tracing::{debug, error};
@@ -43,3 +44,55 @@ pub async fn get_valid_jwt(
 
     Ok(jwt)
 }
+
+/// Compares the bearer token with the shared secret in constant time, so it cannot be guessed
+/// byte by byte. An unset secret lets nobody through.
+pub fn valid_bearer(headers: &HeaderMap, secret: &str) -> bool {
+    let token: &[u8] = match headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+    {
+        Some(v) => v.as_bytes(),
+        None => return false,
+    };
+    let secret: &[u8] = secret.as_bytes();
+
+    !secret.is_empty()
+        && token.len() == secret.len()
+        && token
+            .iter()
+            .zip(secret)
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod test {
+    use super::valid_bearer;
+    use axum::http::{header, HeaderMap, HeaderValue};
+
+    fn bearer(v: &'static str) -> HeaderMap {
+        let mut headers: HeaderMap = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static(v));
+        headers
+    }
+
+    #[test]
+    fn test_valid_bearer() {
+        assert!(valid_bearer(&bearer("Bearer secret"), "secret"));
+    }
+
+    #[test]
+    fn test_invalid_bearer() {
+        assert!(!valid_bearer(&bearer("Bearer secreT"), "secret"));
+        assert!(!valid_bearer(&bearer("Bearer secrets"), "secret"));
+        assert!(!valid_bearer(&bearer("Basic secret"), "secret"));
+        assert!(!valid_bearer(&HeaderMap::new(), "secret"));
+    }
+
+    #[test]
+    fn test_empty_secret() {
+        assert!(!valid_bearer(&bearer("Bearer "), ""));
+    }
+}
@@ -30,8 +30,9 @@ pub(crate) async fn get_jwt_from_valkey(valkey_conn: &mut RedisConn<'_>) -> Opti
 pub(crate) async fn store_jwt(mut conn: RedisConn<'_>, token: &JWT) -> Result<(), Box<dyn Error>> {
     let v: String = serde_json::to_string(token)?;
 
-    conn.hset("session:account", "jwt", v).await?;
-    conn.expire_at("session:account", token.expires_in.timestamp())
+    conn.hset::<_, _, _, ()>("session:account", "jwt", v)
+        .await?;
+    conn.expire_at::<_, ()>("session:account", token.expires_in.timestamp())
         .await?;
 
     Ok(())
@@ -0,0 +1,40 @@
+use crate::{StateParams, ACCOUNT_URL, SERVICE_TOKEN};
+
+use leprecon::jurisdiction::{get_jurisdiction, jurisdiction_filter};
+use mongodb::bson::{doc, Document};
+use tracing::error;
+
+/// Whether the catalog offers blackjack in the country of the user.
+///
+/// When the account service cannot tell the country, only a game without country restrictions is
+/// offered.
+pub(crate) async fn is_offered(
+    sub: &str,
+    state: &StateParams,
+) -> Result<bool, mongodb::error::Error> {
+    let country_code: Option<String> = match get_jurisdiction(
+        &state.3,
+        ACCOUNT_URL.get().unwrap(),
+        SERVICE_TOKEN.get().unwrap(),
+        sub,
+    )
+    .await
+    {
+        Ok(v) => v.country_code,
+        Err(e) => {
+            error!("Could not get jurisdiction of {}: {:?}", sub, e);
+            None
+        }
+    };
+
+    let mut filter: Document = doc! { "slug": "blackjack", "enabled": true };
+    filter.extend(jurisdiction_filter(country_code.as_deref()));
+
+    let count: u64 = state
+        .4
+        .collection::<Document>("catalog")
+        .count_documents(filter, None)
+        .await?;
+
+    Ok(count > 0)
+}
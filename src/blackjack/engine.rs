@@ -0,0 +1,487 @@
+mod card;
+mod hand;
+
+pub(crate) use card::{Card, Rank, Shoe};
+pub(crate) use hand::{Hand, HandOutcome};
+
+use serde::{Deserialize, Serialize};
+use std::{error::Error, fmt};
+
+/// Most hands a player can end up with by splitting.
+pub(crate) const MAX_HANDS: usize = 4;
+
+/// Table rules, kept with the round so changing them does not touch rounds in play.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Rules {
+    pub decks: u8,
+    pub dealer_hits_soft_17: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Action {
+    Hit,
+    Stand,
+    Double,
+    Split,
+    Insurance,
+    DeclineInsurance,
+}
+
+impl Action {
+    pub(crate) const ALL: [Action; 6] = [
+        Action::Hit,
+        Action::Stand,
+        Action::Double,
+        Action::Split,
+        Action::Insurance,
+        Action::DeclineInsurance,
+    ];
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Phase {
+    /// Dealer shows an ace, the player decides on insurance before the peek.
+    Insurance,
+    Player,
+    Finished,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum EngineError {
+    NotAllowed(Action),
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EngineError::NotAllowed(action) => write!(f, "{action} is not allowed"),
+        }
+    }
+}
+
+impl Error for EngineError {}
+
+/// Complete state of a round, persisted between requests.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct Round {
+    pub rules: Rules,
+    shoe: Shoe,
+    pub dealer: Hand,
+    pub hands: Vec<Hand>,
+    /// Hand the player acts on, equals the number of hands once all are done.
+    pub active: usize,
+    pub insurance: Option<f64>,
+    pub phase: Phase,
+    /// Stakes taken so far, numbers the balance updates of the round.
+    pub wagers: u32,
+    pub payout: f64,
+}
+
+impl Round {
+    /// Deals a round from a shoe shuffled with the seed.
+    pub(crate) fn deal(rules: Rules, seed: u64, bet: f64) -> Self {
+        Round::with_shoe(rules, Shoe::new(rules.decks, seed), bet)
+    }
+
+    fn with_shoe(rules: Rules, mut shoe: Shoe, bet: f64) -> Self {
+        let mut hand: Hand = Hand::new(bet);
+        let mut dealer: Hand = Hand::new(0.0);
+
+        hand.cards.push(shoe.draw());
+        dealer.cards.push(shoe.draw());
+        hand.cards.push(shoe.draw());
+        dealer.cards.push(shoe.draw());
+
+        let mut round: Round = Round {
+            rules,
+            shoe,
+            dealer,
+            hands: vec![hand],
+            active: 0,
+            insurance: None,
+            phase: Phase::Insurance,
+            wagers: 1,
+            payout: 0.0,
+        };
+
+        if round.dealer.cards[0].rank != Rank::Ace {
+            round.peek();
+        }
+
+        round
+    }
+
+    /// Dealer checks the hole card, a blackjack on either side ends the round.
+    fn peek(&mut self) {
+        if self.dealer.is_blackjack() || self.hands[0].is_blackjack() {
+            self.settle();
+        } else {
+            self.phase = Phase::Player;
+        }
+    }
+
+    /// Hole card stays hidden until the round is finished.
+    pub(crate) fn dealer_cards(&self) -> &[Card] {
+        match self.phase {
+            Phase::Finished => &self.dealer.cards,
+            _ => &self.dealer.cards[..1],
+        }
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.phase == Phase::Finished
+    }
+
+    pub(crate) fn actions(&self) -> Vec<Action> {
+        Action::ALL
+            .into_iter()
+            .filter(|a| self.check(*a).is_ok())
+            .collect()
+    }
+
+    fn check(&self, action: Action) -> Result<(), EngineError> {
+        let allowed: bool = match (self.phase, action) {
+            (Phase::Insurance, Action::Insurance | Action::DeclineInsurance) => true,
+            (Phase::Player, Action::Hit | Action::Stand) => true,
+            (Phase::Player, Action::Double) => self.hands[self.active].cards.len() == 2,
+            (Phase::Player, Action::Split) => {
+                self.hands.len() < MAX_HANDS && self.hands[self.active].can_split()
+            }
+            _ => false,
+        };
+
+        match allowed {
+            true => Ok(()),
+            false => Err(EngineError::NotAllowed(action)),
+        }
+    }
+
+    /// Extra stake the action takes from the balance of the player.
+    pub(crate) fn cost(&self, action: Action) -> Result<f64, EngineError> {
+        self.check(action)?;
+
+        Ok(match action {
+            Action::Double | Action::Split => self.hands[self.active].bet,
+            Action::Insurance => cents(self.hands[0].bet / 2.0),
+            _ => 0.0,
+        })
+    }
+
+    pub(crate) fn apply(&mut self, action: Action) -> Result<(), EngineError> {
+        let cost: f64 = self.cost(action)?;
+        if cost > 0.0 {
+            self.wagers += 1;
+        }
+
+        match action {
+            Action::Insurance => {
+                self.insurance = Some(cost);
+                self.peek();
+            }
+            Action::DeclineInsurance => self.peek(),
+            Action::Hit => {
+                let card: Card = self.shoe.draw();
+                self.hands[self.active].cards.push(card);
+                self.advance();
+            }
+            Action::Stand => {
+                self.hands[self.active].stood = true;
+                self.advance();
+            }
+            Action::Double => {
+                let card: Card = self.shoe.draw();
+                let hand: &mut Hand = &mut self.hands[self.active];
+                hand.bet += cost;
+                hand.doubled = true;
+                hand.stood = true;
+                hand.cards.push(card);
+                self.advance();
+            }
+            Action::Split => {
+                let (first, second): (Card, Card) = (self.shoe.draw(), self.shoe.draw());
+                let hand: &mut Hand = &mut self.hands[self.active];
+                let mut other: Hand = Hand::new(hand.bet);
+
+                other.cards.push(hand.cards.pop().unwrap());
+                hand.cards.push(first);
+                other.cards.push(second);
+                hand.split = true;
+                other.split = true;
+
+                // Split aces get one card each
+                if other.cards[0].rank == Rank::Ace {
+                    hand.stood = true;
+                    other.stood = true;
+                }
+
+                self.hands.insert(self.active + 1, other);
+                self.advance();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Moves to the next hand which still takes cards, the dealer plays after the last.
+    fn advance(&mut self) {
+        while self.active < self.hands.len() && self.hands[self.active].is_done() {
+            self.active += 1;
+        }
+
+        if self.active == self.hands.len() {
+            self.play_dealer();
+            self.settle();
+        }
+    }
+
+    fn play_dealer(&mut self) {
+        if self.hands.iter().all(Hand::is_bust) {
+            return;
+        }
+
+        while self.dealer_hits() {
+            let card: Card = self.shoe.draw();
+            self.dealer.cards.push(card);
+        }
+    }
+
+    fn dealer_hits(&self) -> bool {
+        let value: u8 = self.dealer.value();
+        value < 17 || (value == 17 && self.dealer.is_soft() && self.rules.dealer_hits_soft_17)
+    }
+
+    fn settle(&mut self) {
+        let mut payout: f64 = 0.0;
+        for hand in self.hands.iter_mut() {
+            let outcome: HandOutcome = hand.outcome_against(&self.dealer);
+            payout += hand.bet * outcome.multiplier();
+            hand.outcome = Some(outcome);
+        }
+
+        // Insurance pays 2:1
+        if let (Some(stake), true) = (self.insurance, self.dealer.is_blackjack()) {
+            payout += stake * 3.0;
+        }
+
+        self.active = self.hands.len();
+        self.payout = cents(payout);
+        self.phase = Phase::Finished;
+    }
+
+    /// Everything the player staked on the round.
+    pub(crate) fn staked(&self) -> f64 {
+        let bets: f64 = self.hands.iter().map(|h| h.bet).sum();
+        cents(bets + self.insurance.unwrap_or_default())
+    }
+}
+
+fn cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::{card::Suit, *};
+
+    const H17: Rules = Rules {
+        decks: 6,
+        dealer_hits_soft_17: true,
+    };
+    const S17: Rules = Rules {
+        decks: 6,
+        dealer_hits_soft_17: false,
+    };
+
+    fn card(rank: Rank) -> Card {
+        Card {
+            rank,
+            suit: Suit::Spades,
+        }
+    }
+
+    /// Deals player, dealer, player, dealer and then the rest in order.
+    fn stacked(rules: Rules, ranks: &[Rank], bet: f64) -> Round {
+        Round::with_shoe(
+            rules,
+            Shoe::stacked(ranks.iter().map(|r| card(*r)).collect()),
+            bet,
+        )
+    }
+
+    #[test]
+    fn test_same_seed_deals_same_round() {
+        assert_eq!(Round::deal(H17, 42, 10.0), Round::deal(H17, 42, 10.0));
+        assert_ne!(Shoe::new(6, 42), Shoe::new(6, 43));
+    }
+
+    #[test]
+    fn test_shoe_holds_every_card_per_deck() {
+        let mut shoe: Shoe = Shoe::new(2, 7);
+        assert_eq!(shoe.len(), 104);
+
+        let cards: Vec<Card> = (0..104).map(|_| shoe.draw()).collect();
+        let aces_of_spades: usize = cards.iter().filter(|c| **c == card(Rank::Ace)).count();
+
+        assert_eq!(aces_of_spades, 2);
+    }
+
+    #[test]
+    fn test_soft_and_hard_totals() {
+        let mut hand: Hand = Hand::new(1.0);
+        hand.cards = vec![card(Rank::Ace), card(Rank::Six)];
+        assert_eq!(hand.value(), 17);
+        assert!(hand.is_soft());
+
+        hand.cards.push(card(Rank::Nine));
+        assert_eq!(hand.value(), 16);
+        assert!(!hand.is_soft());
+
+        hand.cards.push(card(Rank::Ace));
+        assert_eq!(hand.value(), 17);
+    }
+
+    #[test]
+    fn test_blackjack_pays_three_to_two() {
+        let round: Round = stacked(H17, &[Rank::Ace, Rank::Nine, Rank::King, Rank::Seven], 10.0);
+
+        assert!(round.is_finished());
+        assert_eq!(round.hands[0].outcome, Some(HandOutcome::Blackjack));
+        assert_eq!(round.payout, 25.0);
+    }
+
+    #[test]
+    fn test_dealer_hits_soft_17() {
+        let ranks: [Rank; 5] = [Rank::Ten, Rank::Six, Rank::Eight, Rank::Ace, Rank::Two];
+
+        let mut h17: Round = stacked(H17, &ranks, 10.0);
+        h17.apply(Action::Stand).unwrap();
+        assert_eq!(h17.dealer.value(), 19);
+        assert_eq!(h17.hands[0].outcome, Some(HandOutcome::Lose));
+
+        let mut s17: Round = stacked(S17, &ranks, 10.0);
+        s17.apply(Action::Stand).unwrap();
+        assert_eq!(s17.dealer.value(), 17);
+        assert_eq!(s17.hands[0].outcome, Some(HandOutcome::Win));
+        assert_eq!(s17.payout, 20.0);
+    }
+
+    #[test]
+    fn test_double_takes_one_card() {
+        let mut round: Round = stacked(
+            H17,
+            &[Rank::Six, Rank::Ten, Rank::Five, Rank::Seven, Rank::Ten],
+            10.0,
+        );
+
+        assert_eq!(round.cost(Action::Double), Ok(10.0));
+        round.apply(Action::Double).unwrap();
+
+        assert!(round.is_finished());
+        assert_eq!(round.hands[0].cards.len(), 3);
+        assert_eq!(round.wagers, 2);
+        assert_eq!(round.payout, 40.0);
+    }
+
+    #[test]
+    fn test_split_plays_each_hand() {
+        let mut round: Round = stacked(
+            H17,
+            &[
+                Rank::Eight,
+                Rank::Ten,
+                Rank::Eight,
+                Rank::Nine,
+                Rank::Three,
+                Rank::Ten,
+                Rank::Ten,
+            ],
+            5.0,
+        );
+
+        assert_eq!(round.cost(Action::Split), Ok(5.0));
+        round.apply(Action::Split).unwrap();
+        assert_eq!(round.hands.len(), 2);
+        assert_eq!(round.hands[0].value(), 11);
+        assert_eq!(round.hands[1].value(), 18);
+
+        round.apply(Action::Hit).unwrap();
+        assert_eq!(round.hands[0].value(), 21);
+        assert_eq!(round.active, 1);
+
+        round.apply(Action::Stand).unwrap();
+        assert!(round.is_finished());
+        assert_eq!(round.hands[0].outcome, Some(HandOutcome::Win));
+        assert_eq!(round.hands[1].outcome, Some(HandOutcome::Lose));
+        assert_eq!(round.staked(), 10.0);
+        assert_eq!(round.payout, 10.0);
+    }
+
+    #[test]
+    fn test_split_aces_take_one_card() {
+        let mut round: Round = stacked(
+            H17,
+            &[
+                Rank::Ace,
+                Rank::Ten,
+                Rank::Ace,
+                Rank::Seven,
+                Rank::King,
+                Rank::Nine,
+            ],
+            5.0,
+        );
+
+        round.apply(Action::Split).unwrap();
+
+        assert!(round.is_finished());
+        assert_eq!(round.hands[0].outcome, Some(HandOutcome::Win));
+        assert_eq!(round.hands[1].outcome, Some(HandOutcome::Win));
+        assert_eq!(round.payout, 20.0);
+    }
+
+    #[test]
+    fn test_insurance_pays_on_dealer_blackjack() {
+        let mut round: Round = stacked(H17, &[Rank::Ten, Rank::Ace, Rank::Nine, Rank::King], 10.0);
+
+        assert_eq!(
+            round.actions(),
+            vec![Action::Insurance, Action::DeclineInsurance]
+        );
+        round.apply(Action::Insurance).unwrap();
+
+        assert!(round.is_finished());
+        assert_eq!(round.hands[0].outcome, Some(HandOutcome::Lose));
+        assert_eq!(round.staked(), 15.0);
+        assert_eq!(round.payout, 15.0);
+    }
+
+    #[test]
+    fn test_action_not_allowed() {
+        let mut round: Round = stacked(
+            H17,
+            &[Rank::Ten, Rank::Nine, Rank::Six, Rank::Eight, Rank::Two],
+            10.0,
+        );
+
+        assert_eq!(
+            round.apply(Action::Split),
+            Err(EngineError::NotAllowed(Action::Split))
+        );
+        assert_eq!(
+            round.apply(Action::Insurance),
+            Err(EngineError::NotAllowed(Action::Insurance))
+        );
+
+        round.apply(Action::Hit).unwrap();
+        assert_eq!(
+            round.cost(Action::Double),
+            Err(EngineError::NotAllowed(Action::Double))
+        );
+    }
+}
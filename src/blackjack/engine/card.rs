@@ -0,0 +1,144 @@
+use leprecon::rng::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Suit {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+}
+
+impl Suit {
+    const ALL: [Suit; 4] = [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades];
+}
+
+impl fmt::Display for Suit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol: &str = match self {
+            Suit::Clubs => "♣",
+            Suit::Diamonds => "♦",
+            Suit::Hearts => "♥",
+            Suit::Spades => "♠",
+        };
+        write!(f, "{symbol}")
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Rank {
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+    Ace,
+}
+
+impl Rank {
+    const ALL: [Rank; 13] = [
+        Rank::Two,
+        Rank::Three,
+        Rank::Four,
+        Rank::Five,
+        Rank::Six,
+        Rank::Seven,
+        Rank::Eight,
+        Rank::Nine,
+        Rank::Ten,
+        Rank::Jack,
+        Rank::Queen,
+        Rank::King,
+        Rank::Ace,
+    ];
+
+    /// Hard value, an ace counts as one.
+    pub(crate) fn value(self) -> u8 {
+        match self {
+            Rank::Two => 2,
+            Rank::Three => 3,
+            Rank::Four => 4,
+            Rank::Five => 5,
+            Rank::Six => 6,
+            Rank::Seven => 7,
+            Rank::Eight => 8,
+            Rank::Nine => 9,
+            Rank::Ten | Rank::Jack | Rank::Queen | Rank::King => 10,
+            Rank::Ace => 1,
+        }
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rank::Jack => write!(f, "J"),
+            Rank::Queen => write!(f, "Q"),
+            Rank::King => write!(f, "K"),
+            Rank::Ace => write!(f, "A"),
+            v => write!(f, "{}", v.value()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Card {
+    pub rank: Rank,
+    pub suit: Suit,
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.rank, self.suit)
+    }
+}
+
+/// Cards left to deal, the next card is at the end.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct Shoe {
+    cards: Vec<Card>,
+}
+
+impl Shoe {
+    /// Shuffles `decks` full decks, the same seed always gives the same order.
+    pub(crate) fn new(decks: u8, seed: u64) -> Self {
+        let mut cards: Vec<Card> = (0..decks)
+            .flat_map(|_| Suit::ALL)
+            .flat_map(|suit| Rank::ALL.map(|rank| Card { rank, suit }))
+            .collect();
+
+        // Fisher-Yates
+        let mut rng: Rng = Rng::new(seed);
+        for i in (1..cards.len()).rev() {
+            let j: usize = rng.below(i as u64 + 1) as usize;
+            cards.swap(i, j);
+        }
+
+        Shoe { cards }
+    }
+
+    /// Stacked shoe, deals the cards in the given order.
+    #[cfg(test)]
+    pub(crate) fn stacked(mut cards: Vec<Card>) -> Self {
+        cards.reverse();
+        Shoe { cards }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    /// A round takes far fewer cards than a single deck holds.
+    pub(crate) fn draw(&mut self) -> Card {
+        self.cards.pop().expect("shoe is empty")
+    }
+}
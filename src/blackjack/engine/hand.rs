@@ -0,0 +1,109 @@
+use super::card::{Card, Rank};
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Result of a hand against the dealer, once the round is settled.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub(crate) enum HandOutcome {
+    Blackjack,
+    Win,
+    Push,
+    Lose,
+}
+
+impl HandOutcome {
+    /// Returned for every unit staked, the stake included.
+    pub(crate) fn multiplier(self) -> f64 {
+        match self {
+            HandOutcome::Blackjack => 2.5,
+            HandOutcome::Win => 2.0,
+            HandOutcome::Push => 1.0,
+            HandOutcome::Lose => 0.0,
+        }
+    }
+}
+
+impl fmt::Display for HandOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub(crate) struct Hand {
+    pub cards: Vec<Card>,
+    pub bet: f64,
+    pub doubled: bool,
+    /// Hands from a split cannot make blackjack.
+    pub split: bool,
+    /// Stood, doubled or split aces, no more cards.
+    pub stood: bool,
+    pub outcome: Option<HandOutcome>,
+}
+
+impl Hand {
+    pub(crate) fn new(bet: f64) -> Self {
+        Hand {
+            bet,
+            ..Default::default()
+        }
+    }
+
+    /// Best total, counting one ace as eleven when that does not bust.
+    pub(crate) fn value(&self) -> u8 {
+        self.total().0
+    }
+
+    pub(crate) fn is_soft(&self) -> bool {
+        self.total().1
+    }
+
+    fn total(&self) -> (u8, bool) {
+        let hard: u8 = self.cards.iter().map(|c| c.rank.value()).sum();
+        let ace: bool = self.cards.iter().any(|c| c.rank == Rank::Ace);
+
+        match ace && hard + 10 <= 21 {
+            true => (hard + 10, true),
+            false => (hard, false),
+        }
+    }
+
+    pub(crate) fn is_bust(&self) -> bool {
+        self.value() > 21
+    }
+
+    pub(crate) fn is_blackjack(&self) -> bool {
+        !self.split && self.cards.len() == 2 && self.value() == 21
+    }
+
+    /// Two cards of the same value, so a king can be split from a ten.
+    pub(crate) fn can_split(&self) -> bool {
+        self.cards.len() == 2 && self.cards[0].rank.value() == self.cards[1].rank.value()
+    }
+
+    pub(crate) fn is_done(&self) -> bool {
+        self.stood || self.value() >= 21
+    }
+
+    pub(crate) fn outcome_against(&self, dealer: &Hand) -> HandOutcome {
+        if self.is_bust() {
+            return HandOutcome::Lose;
+        }
+
+        match (self.is_blackjack(), dealer.is_blackjack()) {
+            (true, true) => return HandOutcome::Push,
+            (true, false) => return HandOutcome::Blackjack,
+            (false, true) => return HandOutcome::Lose,
+            (false, false) => {}
+        }
+
+        if dealer.is_bust() || self.value() > dealer.value() {
+            HandOutcome::Win
+        } else if self.value() == dealer.value() {
+            HandOutcome::Push
+        } else {
+            HandOutcome::Lose
+        }
+    }
+}
@@ -0,0 +1,182 @@
+mod catalog;
+mod embedded;
+mod engine;
+mod round;
+
+use axum::{middleware, serve, Router};
+use bb8_postgres::{bb8::Pool, PostgresConnectionManager};
+use leprecon::{
+    broker::init_broker,
+    i18n::resolve_locale,
+    signals::shutdown_signal,
+    utils::{configure_tracing, create_conn_pool},
+};
+use mongodb::options::ClientOptions;
+use rabbitmq_stream_client::types::ByteCapacity;
+use round::{get_table, place_bet, play_action, run_settlements};
+use std::{env, error::Error, ops::DerefMut, sync::OnceLock, time::Duration};
+use tokio::{net::TcpListener, task};
+use tokio_postgres::NoTls;
+use tracing::{error, info};
+
+type StateParams = (
+    rabbitmq_stream_client::Producer<rabbitmq_stream_client::NoDedup>,
+    rabbitmq_stream_client::Producer<rabbitmq_stream_client::NoDedup>,
+    Pool<PostgresConnectionManager<NoTls>>,
+    reqwest::Client,
+    mongodb::Database,
+);
+
+// Host variables
+static HOST: OnceLock<String> = OnceLock::new();
+static LOG_LEVEL: OnceLock<String> = OnceLock::new();
+
+// DB variables
+static DB_CONN: OnceLock<String> = OnceLock::new();
+static BLACKJACK_CONN: OnceLock<String> = OnceLock::new();
+static GAME_CATALOG_CONN: OnceLock<String> = OnceLock::new();
+static GAME_CATALOG_DB: OnceLock<String> = OnceLock::new();
+
+// Service variables
+static ACCOUNT_URL: OnceLock<String> = OnceLock::new();
+static SERVICE_TOKEN: OnceLock<String> = OnceLock::new();
+
+// Table variables
+static DECKS: OnceLock<u8> = OnceLock::new();
+static DEALER_HITS_SOFT_17: OnceLock<bool> = OnceLock::new();
+static MIN_BET: OnceLock<f64> = OnceLock::new();
+static MAX_BET: OnceLock<f64> = OnceLock::new();
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    // Initialize env variables
+    init_env();
+
+    // Initialize broker environment
+    let environment = init_broker().await;
+    let stream = "balance_update";
+    let create_response = environment
+        .stream_creator()
+        .max_length(ByteCapacity::GB(5))
+        .create(stream)
+        .await;
+
+    if let Err(e) = create_response {
+        error!("Error creating stream: {:?} {:?}", stream, e);
+    }
+
+    let producer: rabbitmq_stream_client::Producer<rabbitmq_stream_client::NoDedup> =
+        environment.producer().build(stream).await?;
+
+    let play_stream = "game_play";
+    let create_response = environment
+        .stream_creator()
+        .max_length(ByteCapacity::GB(5))
+        .create(play_stream)
+        .await;
+
+    if let Err(e) = create_response {
+        error!("Error creating stream: {:?} {:?}", play_stream, e);
+    }
+
+    let play_producer: rabbitmq_stream_client::Producer<rabbitmq_stream_client::NoDedup> =
+        environment.producer().build(play_stream).await?;
+
+    // Configure logging
+    configure_tracing(LOG_LEVEL.get().unwrap());
+
+    // Create database if not exist
+    let (db_client, connection) = tokio_postgres::connect(DB_CONN.get().unwrap(), NoTls).await?;
+
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            error!("connection error: {}", e);
+        }
+    });
+
+    if let Err(e) = db_client.query("CREATE DATABASE blackjack", &[]).await {
+        error!("Database already exists: {:?}", e);
+    };
+
+    // Connection pool config
+    let connection_timeout: Duration = Duration::from_secs(10);
+    let max_size: u32 = 20;
+
+    // Postgres connection pool
+    let postgres_manager: PostgresConnectionManager<tokio_postgres::NoTls> =
+        PostgresConnectionManager::new_from_stringlike(
+            BLACKJACK_CONN.get().unwrap(),
+            tokio_postgres::NoTls,
+        )?;
+    let postgres_pool: Pool<PostgresConnectionManager<NoTls>> =
+        create_conn_pool(postgres_manager, connection_timeout, max_size).await?;
+
+    // Run migrations
+    embedded::migrations::runner()
+        .run_async(postgres_pool.get().await?.deref_mut())
+        .await?;
+
+    // Mongo, the countries the game is offered in are part of the catalog
+    let client_options: ClientOptions =
+        ClientOptions::parse(GAME_CATALOG_CONN.get().unwrap()).await?;
+    let mongo_client: mongodb::Client = mongodb::Client::with_options(client_options).unwrap();
+    let mongo_db: mongodb::Database = mongo_client.database(GAME_CATALOG_DB.get().unwrap());
+
+    // Http client (holds connection pool internally)
+    let req_client: reqwest::Client = reqwest::Client::new();
+
+    let state: StateParams = (producer, play_producer, postgres_pool, req_client, mongo_db);
+
+    // Credit winnings which could not be published right away
+    task::spawn(run_settlements(state.clone()));
+
+    // Build application and listen to incoming requests.
+    let app: Router = build_app(state);
+    let listener: TcpListener = TcpListener::bind(HOST.get().unwrap()).await?;
+
+    info!("Running application");
+
+    // Run the app.
+    serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+
+    Ok(())
+}
+
+// Initialize env variables
+fn init_env() {
+    HOST.get_or_init(|| env::var("BLACKJACK_HOST").unwrap());
+    LOG_LEVEL.get_or_init(|| env::var("LOG_LEVEL").unwrap());
+
+    DB_CONN.get_or_init(|| env::var("DB_CONN").unwrap());
+    BLACKJACK_CONN.get_or_init(|| env::var("BLACKJACK_CONN").unwrap());
+    GAME_CATALOG_CONN.get_or_init(|| env::var("GAME_CATALOG_CONN").unwrap());
+    GAME_CATALOG_DB.get_or_init(|| env::var("GAME_CATALOG_DB").unwrap());
+
+    ACCOUNT_URL.get_or_init(|| env::var("ACCOUNT_URL").unwrap());
+    SERVICE_TOKEN.get_or_init(|| env::var("SERVICE_TOKEN").unwrap());
+
+    DECKS.get_or_init(|| {
+        let decks: u8 = env::var("BLACKJACK_DECKS").unwrap().parse().unwrap();
+        assert!(decks > 0, "BLACKJACK_DECKS must be at least 1");
+        decks
+    });
+    DEALER_HITS_SOFT_17
+        .get_or_init(|| env::var("BLACKJACK_HITS_SOFT_17").unwrap().parse().unwrap());
+    MIN_BET.get_or_init(|| env::var("BLACKJACK_MIN_BET").unwrap().parse().unwrap());
+    MAX_BET.get_or_init(|| env::var("BLACKJACK_MAX_BET").unwrap().parse().unwrap());
+}
+
+/// Builds the application.
+fn build_app(state: StateParams) -> Router {
+    Router::new()
+        .route("/game/blackjack", axum::routing::get(get_table))
+        .route("/game/blackjack/round", axum::routing::post(place_bet))
+        .route(
+            "/game/blackjack/round/action",
+            axum::routing::post(play_action),
+        )
+        .with_state(state)
+        .layer(middleware::from_fn(resolve_locale))
+}
@@ -0,0 +1,35 @@
+use barrel::{backend::Pg, types, Migration};
+
+pub(crate) fn migration() -> String {
+    let mut m: Migration = Migration::new();
+
+    m.create_table_if_not_exists("rounds", |t| {
+        t.add_column("id", types::primary());
+        t.add_column("sub", types::text());
+        t.add_column("seed", types::custom("bigint"));
+        t.add_column("currency", types::text());
+        t.add_column("bet", types::double());
+        t.add_column("state", types::custom("jsonb"));
+        t.add_column("version", types::integer().default(0));
+        t.add_column("payout", types::double().nullable(true));
+        t.add_column("created", types::custom("timestamp with time zone"));
+        t.add_column("updated", types::custom("timestamp with time zone"));
+        t.add_column(
+            "finished",
+            types::custom("timestamp with time zone").nullable(true),
+        );
+        t.add_column(
+            "settled",
+            types::custom("timestamp with time zone").nullable(true),
+        );
+
+        t.add_index("rounds_sub_created", types::index(vec!["sub", "created"]));
+    });
+
+    // One round in play per user
+    m.inject_custom(
+        "CREATE UNIQUE INDEX IF NOT EXISTS rounds_sub_open ON rounds(sub) WHERE finished IS NULL",
+    );
+
+    m.make::<Pg>()
+}
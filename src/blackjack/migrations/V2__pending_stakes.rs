@@ -0,0 +1,19 @@
+use barrel::{backend::Pg, types, Migration};
+
+pub(crate) fn migration() -> String {
+    let mut m: Migration = Migration::new();
+
+    m.change_table("rounds", |t| {
+        t.add_column("pending_wager", types::double().nullable(true));
+        t.add_column("previous_state", types::custom("jsonb").nullable(true));
+        t.add_column(
+            "voided",
+            types::custom("timestamp with time zone").nullable(true),
+        );
+    });
+
+    // The currency is known once the account debited the bet
+    m.inject_custom("ALTER TABLE rounds ALTER COLUMN currency DROP NOT NULL");
+
+    m.make::<Pg>()
+}
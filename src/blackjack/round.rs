@@ -0,0 +1,437 @@
+mod db;
+mod model;
+
+use self::{
+    db::{
+        confirm_stake, get_open_round, get_pending_rounds, get_unsettled_rounds, insert_round,
+        restore_round, settle_round, stage_round, update_round, void_round,
+    },
+    model::{table_round, ActionParams, BetParams, StoredRound},
+};
+
+use crate::{
+    catalog::is_offered,
+    engine::{Round, Rules},
+    StateParams, ACCOUNT_URL, DEALER_HITS_SOFT_17, DECKS, MAX_BET, MIN_BET, SERVICE_TOKEN,
+};
+
+use askama::Template;
+use axum::{extract::State, response::Html, Form};
+use chrono::Utc;
+use leprecon::{
+    auth::AuthParam,
+    broker::{BalanceUpdate, BalanceUpdateKind, GamePlayStarted},
+    rng::random_seed,
+    template::{self, Snackbar},
+    utils::{
+        extract::{extract_conn_from_pool, ValidForm},
+        PostgresConn,
+    },
+    wallet::{take_stake, StakeError, WagerParams},
+};
+use rabbitmq_stream_client::{error::ProducerPublishError, types::Message, NoDedup, Producer};
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::time::Duration;
+use tracing::error;
+
+/// Table of the user, with the round in play if there is one.
+pub(super) async fn get_table(
+    State(state): State<StateParams>,
+    Form(auth_param): Form<AuthParam>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    if auth_param.sub.is_empty() {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Html(snackbar.render().unwrap()),
+        );
+    };
+
+    let postgres_conn: PostgresConn = match extract_conn_from_pool(&state.2, &mut snackbar).await {
+        Ok(v) => v,
+        Err(e) => return e,
+    };
+
+    match get_open_round(&auth_param.sub, &postgres_conn).await {
+        Ok(stored) => (
+            StatusCode::OK,
+            render_table(stored.and_then(StoredRound::visible)),
+        ),
+        Err(e) => {
+            error!("Could not get round: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            )
+        }
+    }
+}
+
+/// Takes the bet and deals a new round.
+pub(super) async fn place_bet(
+    State(state): State<StateParams>,
+    ValidForm(bet): ValidForm<BetParams>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    let postgres_conn: PostgresConn = match extract_conn_from_pool(&state.2, &mut snackbar).await {
+        Ok(v) => v,
+        Err(e) => return e,
+    };
+
+    // Not offered in the country of the user
+    match is_offered(&bet.sub, &state).await {
+        Ok(true) => {}
+        Ok(false) => {
+            snackbar.message = "game-does-not-exist";
+            return (StatusCode::NOT_FOUND, Html(snackbar.render().unwrap()));
+        }
+        Err(e) => {
+            error!("Could not get game: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    match get_open_round(&bet.sub, &postgres_conn).await {
+        Ok(None) => {}
+        Ok(Some(stored)) => {
+            snackbar.message = match stored.pending_wager {
+                Some(_) => "stake-pending",
+                None => "blackjack-round-open",
+            };
+            return (StatusCode::CONFLICT, Html(snackbar.render().unwrap()));
+        }
+        Err(e) => {
+            error!("Could not get round: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    let rules: Rules = Rules {
+        decks: *DECKS.get().unwrap(),
+        dealer_hits_soft_17: *DEALER_HITS_SOFT_17.get().unwrap(),
+    };
+    let seed: u64 = random_seed();
+    let round: Round = Round::deal(rules, seed, bet.amount);
+
+    // Saved before the bet is debited, so a debit with an unknown outcome can be reconciled
+    let id: i32 = match insert_round(&bet.sub, seed, &round, &postgres_conn).await {
+        Ok(Some(v)) => v,
+        // Another bet of the user got in first
+        Ok(None) => {
+            snackbar.message = "blackjack-round-open";
+            return (StatusCode::CONFLICT, Html(snackbar.render().unwrap()));
+        }
+        Err(e) => {
+            error!("Could not insert round: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    let mut stored: StoredRound = StoredRound {
+        id,
+        sub: bet.sub,
+        version: 0,
+        currency: String::new(),
+        round,
+        pending_wager: Some(bet.amount),
+        previous: None,
+    };
+
+    match stake(&state.3, &stored).await {
+        Ok(currency) => stored.currency = currency,
+        Err(StakeError::Refused(reason)) => {
+            if let Err(e) = void_round(id, &postgres_conn).await {
+                error!("Could not void round: {:?}", e);
+            }
+
+            return StakeError::Refused(reason).response();
+        }
+        // Stays pending until the debit is reconciled
+        Err(e) => return e.response(),
+    };
+
+    if let Err(e) = settle_stake(&state, &stored, &postgres_conn).await {
+        error!("Could not confirm stake: {:?}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Html(snackbar.render().unwrap()),
+        );
+    }
+
+    (StatusCode::OK, render_table(Some(stored.round)))
+}
+
+/// Plays the action on the round in play.
+///
+/// The round is saved before the extra stake is debited, so a concurrent request cannot play it twice.
+pub(super) async fn play_action(
+    State(state): State<StateParams>,
+    ValidForm(params): ValidForm<ActionParams>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    let postgres_conn: PostgresConn = match extract_conn_from_pool(&state.2, &mut snackbar).await {
+        Ok(v) => v,
+        Err(e) => return e,
+    };
+
+    let stored: StoredRound = match get_open_round(&params.sub, &postgres_conn).await {
+        Ok(Some(v)) if v.pending_wager.is_some() => {
+            snackbar.message = "stake-pending";
+            return (StatusCode::CONFLICT, Html(snackbar.render().unwrap()));
+        }
+        Ok(Some(v)) => v,
+        Ok(None) => {
+            snackbar.message = "blackjack-no-round";
+            return (StatusCode::NOT_FOUND, Html(snackbar.render().unwrap()));
+        }
+        Err(e) => {
+            error!("Could not get round: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    let cost: f64 = match stored.round.cost(params.action) {
+        Ok(v) => v,
+        Err(_) => {
+            snackbar.message = "blackjack-action-not-allowed";
+            return (StatusCode::BAD_REQUEST, Html(snackbar.render().unwrap()));
+        }
+    };
+
+    let mut next: StoredRound = StoredRound {
+        round: stored.round.clone(),
+        ..stored
+    };
+    next.round.apply(params.action).unwrap();
+
+    let saved = if cost > 0.0 {
+        stage_round(&next, &stored.round, cost, &postgres_conn).await
+    } else {
+        update_round(&next, &postgres_conn).await
+    };
+
+    match saved {
+        Ok(0) => {
+            snackbar.message = "blackjack-round-changed";
+            return (StatusCode::CONFLICT, Html(snackbar.render().unwrap()));
+        }
+        Ok(_) => {}
+        Err(e) => {
+            error!("Could not update round: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    if cost == 0.0 {
+        if next.round.is_finished() {
+            credit_winnings(&state.0, &next, &postgres_conn).await;
+        }
+
+        return (StatusCode::OK, render_table(Some(next.round)));
+    }
+
+    next.version += 1;
+    next.pending_wager = Some(cost);
+    next.previous = Some(stored.round);
+
+    match stake(&state.3, &next).await {
+        Ok(_) => {}
+        Err(StakeError::Refused(reason)) => {
+            if let Err(e) = restore_round(next.id, &postgres_conn).await {
+                error!("Could not restore round: {:?}", e);
+            }
+
+            return StakeError::Refused(reason).response();
+        }
+        // Stays pending until the debit is reconciled
+        Err(e) => return e.response(),
+    };
+
+    if let Err(e) = settle_stake(&state, &next, &postgres_conn).await {
+        error!("Could not confirm stake: {:?}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Html(snackbar.render().unwrap()),
+        );
+    }
+
+    (StatusCode::OK, render_table(Some(next.round)))
+}
+
+/// Reconciles the pending stakes and retries crediting the finished rounds, until the service shuts down.
+pub(super) async fn run_settlements(state: StateParams) {
+    let mut interval: tokio::time::Interval = tokio::time::interval(Duration::from_secs(30));
+
+    loop {
+        interval.tick().await;
+
+        let postgres_conn: PostgresConn = match state.2.get().await {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Cannot get connection from pool: {:?}", e);
+                continue;
+            }
+        };
+
+        let pending: Vec<StoredRound> = match get_pending_rounds(&postgres_conn).await {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Could not get pending rounds: {:?}", e);
+                continue;
+            }
+        };
+
+        for mut stored in pending {
+            // Debited before when the account already booked the reference
+            match stake(&state.3, &stored).await {
+                Ok(currency) => {
+                    stored.currency = currency;
+                    if let Err(e) = settle_stake(&state, &stored, &postgres_conn).await {
+                        error!("Could not confirm stake: {:?}", e);
+                    }
+                }
+                Err(StakeError::Refused(_)) => {
+                    let undone = match stored.previous {
+                        Some(_) => restore_round(stored.id, &postgres_conn).await,
+                        None => void_round(stored.id, &postgres_conn).await,
+                    };
+                    if let Err(e) = undone {
+                        error!("Could not undo round: {:?}", e);
+                    }
+                }
+                Err(StakeError::Unavailable) => {}
+            }
+        }
+
+        let rounds: Vec<StoredRound> = match get_unsettled_rounds(&postgres_conn).await {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Could not get unsettled rounds: {:?}", e);
+                continue;
+            }
+        };
+
+        for stored in rounds {
+            credit_winnings(&state.0, &stored, &postgres_conn).await;
+        }
+    }
+}
+
+/// Has the account debit the pending stake of the round.
+async fn stake(req_client: &reqwest::Client, stored: &StoredRound) -> Result<String, StakeError> {
+    let params: WagerParams = WagerParams {
+        sub: stored.sub.clone(),
+        amount: stored.pending_wager.unwrap_or_default(),
+        reference: stored.wager_reference(),
+        win: 0.0,
+        win_reference: String::new(),
+    };
+
+    take_stake(
+        req_client,
+        ACCOUNT_URL.get().unwrap(),
+        SERVICE_TOKEN.get().unwrap(),
+        &params,
+    )
+    .await
+}
+
+/// Marks the pending stake of the round as debited, then starts or settles the round.
+async fn settle_stake(
+    state: &StateParams,
+    stored: &StoredRound,
+    db_client: &PostgresConn<'_>,
+) -> Result<(), tokio_postgres::Error> {
+    // Already confirmed or undone elsewhere
+    if confirm_stake(stored, &stored.currency, db_client).await? == 0 {
+        return Ok(());
+    }
+
+    if stored.previous.is_none() {
+        let play: GamePlayStarted = GamePlayStarted {
+            session: format!("blackjack:{}", stored.id),
+            sub: stored.sub.clone(),
+            game: String::from("blackjack"),
+            started_at: Utc::now(),
+        };
+        if let Err(e) = publish(&state.1, &play).await {
+            error!("Error while publishing message: {:?}", e);
+        }
+    }
+
+    // Blackjack on either side settles the round on the deal
+    if stored.round.is_finished() {
+        credit_winnings(&state.0, stored, db_client).await;
+    }
+
+    Ok(())
+}
+
+/// Credits the payout of a finished round, rounds which fail are retried in the background.
+async fn credit_winnings(
+    producer: &Producer<NoDedup>,
+    stored: &StoredRound,
+    db_client: &PostgresConn<'_>,
+) {
+    if stored.round.payout > 0.0 {
+        let win: BalanceUpdate = BalanceUpdate {
+            sub: stored.sub.clone(),
+            kind: BalanceUpdateKind::Win,
+            amount: stored.round.payout,
+            currency: stored.currency.clone(),
+            reference: format!("blackjack:{}:win", stored.id),
+        };
+
+        if let Err(e) = publish(producer, &win).await {
+            error!("Error while publishing message: {:?}", e);
+            return;
+        }
+    }
+
+    if let Err(e) = settle_round(stored.id, db_client).await {
+        error!("Could not settle round: {:?}", e);
+    }
+}
+
+async fn publish<T: Serialize>(
+    producer: &Producer<NoDedup>,
+    message: &T,
+) -> Result<(), ProducerPublishError> {
+    producer
+        .send_with_confirm(
+            Message::builder()
+                .body(serde_json::to_string(message).unwrap())
+                .build(),
+        )
+        .await
+        .map(|_| ())
+}
+
+fn render_table(round: Option<Round>) -> Html<String> {
+    let table: template::BlackjackTable = template::BlackjackTable {
+        round: round.as_ref().map(table_round),
+        min_bet: *MIN_BET.get().unwrap(),
+        max_bet: *MAX_BET.get().unwrap(),
+    };
+
+    Html(table.render().unwrap())
+}
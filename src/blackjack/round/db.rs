@@ -0,0 +1,160 @@
+use super::model::StoredRound;
+use crate::engine::Round;
+
+use chrono::{DateTime, Local};
+use leprecon::utils::PostgresConn;
+use std::error::Error;
+use tokio_postgres::Row;
+
+/// Saves a new round of which the bet still has to be debited, none when the user already has a
+/// round in play.
+pub(super) async fn insert_round(
+    sub: &str,
+    seed: u64,
+    round: &Round,
+    db_client: &PostgresConn<'_>,
+) -> Result<Option<i32>, Box<dyn Error + Send + Sync>> {
+    let bet: f64 = round.hands[0].bet;
+
+    let r: Option<Row> = db_client
+        .query_opt(
+            "INSERT INTO rounds(sub, seed, bet, state, pending_wager, created, updated) VALUES($1, $2, $3, $4, $3, $5, $5) ON CONFLICT (sub) WHERE finished IS NULL DO NOTHING RETURNING id",
+            &[&sub, &(seed as i64), &bet, &serde_json::to_value(round)?, &Local::now()],
+        )
+        .await?;
+
+    Ok(r.map(|r| r.get("id")))
+}
+
+/// Round of the user which is not finished yet.
+pub(super) async fn get_open_round(
+    sub: &str,
+    db_client: &PostgresConn<'_>,
+) -> Result<Option<StoredRound>, Box<dyn Error + Send + Sync>> {
+    let row: Option<Row> = db_client
+        .query_opt(
+            "SELECT * FROM rounds WHERE sub = $1 AND finished IS NULL",
+            &[&sub],
+        )
+        .await?;
+
+    row.map(StoredRound::try_from).transpose()
+}
+
+/// Saves the round unless it was changed since it was read, returns the rows updated.
+pub(super) async fn update_round(
+    stored: &StoredRound,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, Box<dyn Error + Send + Sync>> {
+    let round: &Round = &stored.round;
+    let payout: Option<f64> = round.is_finished().then_some(round.payout);
+    let finished: Option<DateTime<Local>> = round.is_finished().then(Local::now);
+
+    Ok(db_client
+        .execute(
+            "UPDATE rounds SET state = $3, payout = $4, finished = $5, updated = $6, version = version + 1 WHERE id = $1 AND version = $2",
+            &[&stored.id, &stored.version, &serde_json::to_value(round)?, &payout, &finished, &Local::now()],
+        )
+        .await?)
+}
+
+/// Saves the round after an action of which the stake still has to be debited, keeping the round
+/// from before so it can be put back. Returns the rows updated, none when it was changed since it was read.
+pub(super) async fn stage_round(
+    stored: &StoredRound,
+    previous: &Round,
+    wager: f64,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, Box<dyn Error + Send + Sync>> {
+    Ok(db_client
+        .execute(
+            "UPDATE rounds SET state = $3, previous_state = $4, pending_wager = $5, updated = $6, version = version + 1 WHERE id = $1 AND version = $2",
+            &[&stored.id, &stored.version, &serde_json::to_value(&stored.round)?, &serde_json::to_value(previous)?, &wager, &Local::now()],
+        )
+        .await?)
+}
+
+/// Marks the pending stake as debited, which finishes the round when it was played out.
+pub(super) async fn confirm_stake(
+    stored: &StoredRound,
+    currency: &str,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    let round: &Round = &stored.round;
+    let payout: Option<f64> = round.is_finished().then_some(round.payout);
+    let finished: Option<DateTime<Local>> = round.is_finished().then(Local::now);
+
+    db_client
+        .execute(
+            "UPDATE rounds SET currency = $2, payout = $3, finished = $4, pending_wager = NULL, previous_state = NULL, updated = $5 WHERE id = $1 AND pending_wager IS NOT NULL",
+            &[&stored.id, &currency, &payout, &finished, &Local::now()],
+        )
+        .await
+}
+
+/// Puts the round back as it was before the action of which the stake was refused.
+pub(super) async fn restore_round(
+    id: i32,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    db_client
+        .execute(
+            "UPDATE rounds SET state = previous_state, previous_state = NULL, pending_wager = NULL, updated = $2, version = version + 1 WHERE id = $1 AND pending_wager IS NOT NULL AND previous_state IS NOT NULL",
+            &[&id, &Local::now()],
+        )
+        .await
+}
+
+/// Closes a round of which the opening bet was refused, nothing was debited so nothing is credited.
+pub(super) async fn void_round(
+    id: i32,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    db_client
+        .execute(
+            "UPDATE rounds SET pending_wager = NULL, voided = $2, finished = $2, updated = $2 WHERE id = $1 AND pending_wager IS NOT NULL AND previous_state IS NULL",
+            &[&id, &Local::now()],
+        )
+        .await
+}
+
+/// Marks the winnings of the round as credited.
+pub(super) async fn settle_round(
+    id: i32,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    db_client
+        .execute(
+            "UPDATE rounds SET settled = $2 WHERE id = $1",
+            &[&id, &Local::now()],
+        )
+        .await
+}
+
+/// Finished rounds of which the winnings were not credited yet, leaves recent rounds to their request.
+pub(super) async fn get_unsettled_rounds(
+    db_client: &PostgresConn<'_>,
+) -> Result<Vec<StoredRound>, Box<dyn Error + Send + Sync>> {
+    let rows: Vec<Row> = db_client
+        .query(
+            "SELECT * FROM rounds WHERE finished < NOW() - INTERVAL '1 minute' AND settled IS NULL AND voided IS NULL ORDER BY finished LIMIT 100",
+            &[],
+        )
+        .await?;
+
+    rows.into_iter().map(StoredRound::try_from).collect()
+}
+
+/// Rounds of which the stake was not confirmed, leaves recent rounds to their request.
+pub(super) async fn get_pending_rounds(
+    db_client: &PostgresConn<'_>,
+) -> Result<Vec<StoredRound>, Box<dyn Error + Send + Sync>> {
+    let rows: Vec<Row> = db_client
+        .query(
+            "SELECT * FROM rounds WHERE pending_wager IS NOT NULL AND updated < NOW() - INTERVAL '1 minute' ORDER BY updated LIMIT 100",
+            &[],
+        )
+        .await?;
+
+    rows.into_iter().map(StoredRound::try_from).collect()
+}
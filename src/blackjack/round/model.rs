@@ -0,0 +1,151 @@
+use crate::{
+    engine::{Action, Hand, Round},
+    MAX_BET, MIN_BET,
+};
+
+use leprecon::{
+    template::{BlackjackHand, BlackjackRound},
+    utils::validate::{is_whole_cents, Validate, ValidationError},
+};
+use serde::Deserialize;
+use std::error::Error;
+use tokio_postgres::Row;
+
+pub(crate) struct StoredRound {
+    pub id: i32,
+    pub sub: String,
+    /// Bumped on every save, so two requests cannot both play the round.
+    pub version: i32,
+    pub currency: String,
+    pub round: Round,
+    /// Stake of the last bet or action, until the account confirmed the debit.
+    pub pending_wager: Option<f64>,
+    /// Round before the action of the pending stake, none for the opening bet.
+    pub previous: Option<Round>,
+}
+
+impl TryFrom<Row> for StoredRound {
+    type Error = Box<dyn Error + Send + Sync>;
+
+    fn try_from(r: Row) -> Result<Self, Self::Error> {
+        Ok(StoredRound {
+            id: r.get("id"),
+            sub: r.get("sub"),
+            version: r.get("version"),
+            currency: r.get::<_, Option<String>>("currency").unwrap_or_default(),
+            round: serde_json::from_value(r.get("state"))?,
+            pending_wager: r.get("pending_wager"),
+            previous: r
+                .get::<_, Option<serde_json::Value>>("previous_state")
+                .map(serde_json::from_value)
+                .transpose()?,
+        })
+    }
+}
+
+impl StoredRound {
+    /// Identifies the latest stake of the round, so a retried debit is only booked once.
+    pub fn wager_reference(&self) -> String {
+        format!("blackjack:{}:wager:{}", self.id, self.round.wagers)
+    }
+
+    /// Round as the player may see it, without the action of which the stake is pending.
+    pub fn visible(self) -> Option<Round> {
+        match self.pending_wager {
+            Some(_) => self.previous,
+            None => Some(self.round),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct BetParams {
+    #[serde(default)]
+    pub sub: String,
+    pub amount: f64,
+}
+
+impl Validate for BetParams {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.sub.is_empty() {
+            return Err(ValidationError::new("sub", "is required"));
+        }
+
+        if !self.amount.is_finite() || self.amount <= 0.0 {
+            return Err(ValidationError::new("amount", "must be positive"));
+        }
+
+        if !is_whole_cents(self.amount) {
+            return Err(ValidationError::new(
+                "amount",
+                "cannot have more than two decimals",
+            ));
+        }
+
+        let min: f64 = *MIN_BET.get().unwrap();
+        if self.amount < min {
+            return Err(ValidationError::new(
+                "amount",
+                &format!("must be at least {min}"),
+            ));
+        }
+
+        let max: f64 = *MAX_BET.get().unwrap();
+        if self.amount > max {
+            return Err(ValidationError::new(
+                "amount",
+                &format!("must be at most {max}"),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct ActionParams {
+    #[serde(default)]
+    pub sub: String,
+    pub action: Action,
+}
+
+impl Validate for ActionParams {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.sub.is_empty() {
+            return Err(ValidationError::new("sub", "is required"));
+        }
+
+        Ok(())
+    }
+}
+
+/// What the player may see of the round.
+pub(super) fn table_round(round: &Round) -> BlackjackRound {
+    let finished: bool = round.is_finished();
+
+    BlackjackRound {
+        dealer: round.dealer_cards().iter().map(|c| c.to_string()).collect(),
+        dealer_value: finished.then(|| round.dealer.value()),
+        hands: round
+            .hands
+            .iter()
+            .enumerate()
+            .map(|(i, hand)| table_hand(hand, i == round.active))
+            .collect(),
+        actions: round.actions().iter().map(|a| a.to_string()).collect(),
+        insurance: round.insurance,
+        finished,
+        staked: round.staked(),
+        payout: round.payout,
+    }
+}
+
+fn table_hand(hand: &Hand, active: bool) -> BlackjackHand {
+    BlackjackHand {
+        cards: hand.cards.iter().map(|c| c.to_string()).collect(),
+        value: hand.value(),
+        bet: hand.bet,
+        active,
+        outcome: hand.outcome.map(|o| o.to_string()),
+    }
+}
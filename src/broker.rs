@@ -1,3 +1,7 @@
+mod model;
+
+pub use model::*;
+
 use rabbitmq_stream_client::Environment;
 use tracing::warn;
 
@@ -0,0 +1,115 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{error::Error, fmt, str::FromStr};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum BalanceUpdateKind {
+    Deposit,
+    Wager,
+    Win,
+}
+
+impl fmt::Display for BalanceUpdateKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Balance change published on the `balance_update` stream.
+///
+/// The amount is signed, wagers are negative. The reference is unique per change.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BalanceUpdate {
+    pub sub: String,
+    pub kind: BalanceUpdateKind,
+    pub amount: f64,
+    pub currency: String,
+    pub reference: String,
+}
+
+/// Message on the `balance_update` stream, the updates of a batch are applied together or not at all.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum BalanceMessage {
+    Single(BalanceUpdate),
+    Batch(Vec<BalanceUpdate>),
+}
+
+impl BalanceMessage {
+    pub fn into_updates(self) -> Vec<BalanceUpdate> {
+        match self {
+            BalanceMessage::Single(v) => vec![v],
+            BalanceMessage::Batch(v) => v,
+        }
+    }
+}
+
+/// Published on the `balance_applied` stream once the account ledger booked a balance update.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BalanceApplied {
+    pub sub: String,
+    pub kind: BalanceUpdateKind,
+    pub reference: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum KycStatus {
+    Unverified,
+    Pending,
+    Verified,
+    Rejected,
+}
+
+impl fmt::Display for KycStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseKycStatusError;
+
+impl Error for ParseKycStatusError {}
+
+impl fmt::Display for ParseKycStatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ParseKycStatusError")
+    }
+}
+
+impl FromStr for KycStatus {
+    type Err = ParseKycStatusError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Unverified" => Ok(KycStatus::Unverified),
+            "Pending" => Ok(KycStatus::Pending),
+            "Verified" => Ok(KycStatus::Verified),
+            "Rejected" => Ok(KycStatus::Rejected),
+            _ => Err(ParseKycStatusError),
+        }
+    }
+}
+
+/// Published on the `kyc_status` stream whenever the verification status of a user changes.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct KycStatusChanged {
+    pub sub: String,
+    pub status: KycStatus,
+    pub reason: Option<String>,
+}
+
+/// Published on the `account_deleted` stream once an account is removed, so services can purge the user.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AccountDeleted {
+    pub sub: String,
+}
+
+/// Published on the `game_play` stream when a game session starts, the session id is unique per play.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GamePlayStarted {
+    pub session: String,
+    pub sub: String,
+    pub game: String,
+    pub started_at: DateTime<Utc>,
+}
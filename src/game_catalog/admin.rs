@@ -0,0 +1,294 @@
+mod db;
+mod model;
+
+use self::{
+    db::{
+        delete_game, delete_game_translation, insert_game, is_duplicate_slug, replace_game,
+        set_game_countries, set_game_enabled, set_game_machine, set_game_translation,
+    },
+    model::{AdminToken, CountryParams, EnabledParams, GameParams, TranslationParams},
+};
+
+use crate::{cache::invalidate_catalog, catalog::model::Game, StateParams};
+
+use askama::Template;
+use axum::{
+    extract::{Path, State},
+    response::Html,
+    Form,
+};
+use leprecon::{
+    i18n::Locale,
+    machine::Machine,
+    template::Snackbar,
+    utils::extract::{ValidForm, ValidJson},
+};
+use reqwest::StatusCode;
+use std::str::FromStr;
+use tracing::error;
+
+/// Adds a game to the catalog.
+pub(super) async fn create_game(
+    _: AdminToken,
+    State(state): State<StateParams>,
+    ValidForm(params): ValidForm<GameParams>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    let game: Game = params.into();
+
+    if let Err(e) = insert_game(&game, state.0.clone()).await {
+        if is_duplicate_slug(&e) {
+            snackbar.message = "game-slug-exists";
+            return (StatusCode::CONFLICT, Html(snackbar.render().unwrap()));
+        }
+
+        error!("Could not create game: {:?}", e);
+        return (StatusCode::BAD_GATEWAY, Html(snackbar.render().unwrap()));
+    }
+
+    invalidate_catalog(&state.1).await;
+
+    snackbar.title = "success";
+    snackbar.message = "game-created";
+    snackbar.color = "green";
+
+    (StatusCode::CREATED, Html(snackbar.render().unwrap()))
+}
+
+/// Replaces a game, the slug may be changed as long as it stays unique.
+pub(super) async fn update_game(
+    _: AdminToken,
+    State(state): State<StateParams>,
+    Path(slug): Path<String>,
+    ValidForm(params): ValidForm<GameParams>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    let game: Game = params.into();
+
+    match replace_game(&slug, &game, state.0.clone()).await {
+        Ok(true) => (),
+        Ok(false) => {
+            snackbar.message = "game-does-not-exist";
+            return (StatusCode::NOT_FOUND, Html(snackbar.render().unwrap()));
+        }
+        Err(e) if is_duplicate_slug(&e) => {
+            snackbar.message = "game-slug-exists";
+            return (StatusCode::CONFLICT, Html(snackbar.render().unwrap()));
+        }
+        Err(e) => {
+            error!("Could not update game: {:?}", e);
+            return (StatusCode::BAD_GATEWAY, Html(snackbar.render().unwrap()));
+        }
+    }
+
+    invalidate_catalog(&state.1).await;
+
+    snackbar.title = "success";
+    snackbar.message = "game-updated";
+    snackbar.color = "green";
+
+    (StatusCode::OK, Html(snackbar.render().unwrap()))
+}
+
+/// Shows or hides a game, without removing it.
+pub(super) async fn enable_game(
+    _: AdminToken,
+    State(state): State<StateParams>,
+    Path(slug): Path<String>,
+    Form(params): Form<EnabledParams>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    match set_game_enabled(&slug, params.enabled, state.0.clone()).await {
+        Ok(true) => (),
+        Ok(false) => {
+            snackbar.message = "game-does-not-exist";
+            return (StatusCode::NOT_FOUND, Html(snackbar.render().unwrap()));
+        }
+        Err(e) => {
+            error!("Could not change game: {:?}", e);
+            return (StatusCode::BAD_GATEWAY, Html(snackbar.render().unwrap()));
+        }
+    }
+
+    invalidate_catalog(&state.1).await;
+
+    snackbar.title = "success";
+    snackbar.message = match params.enabled {
+        true => "game-enabled",
+        false => "game-disabled",
+    };
+    snackbar.color = "green";
+
+    (StatusCode::OK, Html(snackbar.render().unwrap()))
+}
+
+/// Restricts the countries the game is offered in, empty lists lift the restrictions.
+pub(super) async fn set_countries(
+    _: AdminToken,
+    State(state): State<StateParams>,
+    Path(slug): Path<String>,
+    ValidForm(params): ValidForm<CountryParams>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    match set_game_countries(&slug, &params.allowed(), &params.blocked(), state.0.clone()).await {
+        Ok(true) => (),
+        Ok(false) => {
+            snackbar.message = "game-does-not-exist";
+            return (StatusCode::NOT_FOUND, Html(snackbar.render().unwrap()));
+        }
+        Err(e) => {
+            error!("Could not change game countries: {:?}", e);
+            return (StatusCode::BAD_GATEWAY, Html(snackbar.render().unwrap()));
+        }
+    }
+
+    invalidate_catalog(&state.1).await;
+
+    snackbar.title = "success";
+    snackbar.message = "game-countries-updated";
+    snackbar.color = "green";
+
+    (StatusCode::OK, Html(snackbar.render().unwrap()))
+}
+
+/// Sets the name and description of the game in a locale.
+pub(super) async fn set_translation(
+    _: AdminToken,
+    State(state): State<StateParams>,
+    Path((slug, locale)): Path<(String, String)>,
+    ValidForm(params): ValidForm<TranslationParams>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    let locale: Locale = match Locale::from_str(&locale) {
+        Ok(v) => v,
+        Err(_) => {
+            snackbar.message = "locale: is unknown";
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    match set_game_translation(&slug, locale, &params.into(), state.0.clone()).await {
+        Ok(true) => (),
+        Ok(false) => {
+            snackbar.message = "game-does-not-exist";
+            return (StatusCode::NOT_FOUND, Html(snackbar.render().unwrap()));
+        }
+        Err(e) => {
+            error!("Could not translate game: {:?}", e);
+            return (StatusCode::BAD_GATEWAY, Html(snackbar.render().unwrap()));
+        }
+    }
+
+    invalidate_catalog(&state.1).await;
+
+    snackbar.title = "success";
+    snackbar.message = "game-translation-updated";
+    snackbar.color = "green";
+
+    (StatusCode::OK, Html(snackbar.render().unwrap()))
+}
+
+pub(super) async fn remove_translation(
+    _: AdminToken,
+    State(state): State<StateParams>,
+    Path((slug, locale)): Path<(String, String)>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    let locale: Locale = match Locale::from_str(&locale) {
+        Ok(v) => v,
+        Err(_) => {
+            snackbar.message = "locale: is unknown";
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    match delete_game_translation(&slug, locale, state.0.clone()).await {
+        Ok(true) => (),
+        Ok(false) => {
+            snackbar.message = "game-translation-missing";
+            return (StatusCode::NOT_FOUND, Html(snackbar.render().unwrap()));
+        }
+        Err(e) => {
+            error!("Could not remove game translation: {:?}", e);
+            return (StatusCode::BAD_GATEWAY, Html(snackbar.render().unwrap()));
+        }
+    }
+
+    invalidate_catalog(&state.1).await;
+
+    snackbar.title = "success";
+    snackbar.message = "game-translation-removed";
+    snackbar.color = "green";
+
+    (StatusCode::OK, Html(snackbar.render().unwrap()))
+}
+
+/// Sets the reels and paytable of a slot game, as json since the machine is nested.
+pub(super) async fn set_machine(
+    _: AdminToken,
+    State(state): State<StateParams>,
+    Path(slug): Path<String>,
+    ValidJson(machine): ValidJson<Machine>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    match set_game_machine(&slug, &machine, state.0.clone()).await {
+        Ok(true) => (),
+        Ok(false) => {
+            snackbar.message = "machine-game-missing";
+            return (StatusCode::NOT_FOUND, Html(snackbar.render().unwrap()));
+        }
+        Err(e) => {
+            error!("Could not change game machine: {:?}", e);
+            return (StatusCode::BAD_GATEWAY, Html(snackbar.render().unwrap()));
+        }
+    }
+
+    invalidate_catalog(&state.1).await;
+
+    snackbar.title = "success";
+    snackbar.message = "machine-updated";
+    snackbar.color = "green";
+
+    (StatusCode::OK, Html(snackbar.render().unwrap()))
+}
+
+pub(super) async fn remove_game(
+    _: AdminToken,
+    State(state): State<StateParams>,
+    Path(slug): Path<String>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    match delete_game(&slug, state.0.clone()).await {
+        Ok(true) => (),
+        Ok(false) => {
+            snackbar.message = "game-does-not-exist";
+            return (StatusCode::NOT_FOUND, Html(snackbar.render().unwrap()));
+        }
+        Err(e) => {
+            error!("Could not delete game: {:?}", e);
+            return (StatusCode::BAD_GATEWAY, Html(snackbar.render().unwrap()));
+        }
+    }
+
+    invalidate_catalog(&state.1).await;
+
+    snackbar.title = "success";
+    snackbar.message = "game-deleted";
+    snackbar.color = "green";
+
+    (StatusCode::OK, Html(snackbar.render().unwrap()))
+}
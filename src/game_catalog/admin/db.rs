@@ -0,0 +1,157 @@
+use crate::catalog::model::{Game, GameCategory, GameTranslation};
+
+use leprecon::{i18n::Locale, machine::Machine};
+use mongodb::{
+    bson::{doc, to_bson, to_document, Document},
+    error::{ErrorKind, WriteFailure},
+    options::UpdateOptions,
+    results::{DeleteResult, UpdateResult},
+};
+
+pub(super) async fn insert_game(
+    game: &Game,
+    conn: mongodb::Database,
+) -> Result<(), mongodb::error::Error> {
+    let collection: mongodb::Collection<Game> = conn.collection::<Game>("catalog");
+    collection.insert_one(game, None).await?;
+
+    Ok(())
+}
+
+/// Replaces the game with the slug, returns false when there is no such game.
+///
+/// The play statistics, country restrictions, translations and machine are kept, they are set separately.
+pub(super) async fn replace_game(
+    slug: &str,
+    game: &Game,
+    conn: mongodb::Database,
+) -> Result<bool, mongodb::error::Error> {
+    let collection: mongodb::Collection<Game> = conn.collection::<Game>("catalog");
+
+    let mut fields: Document = to_document(game)?;
+    fields.remove("stats");
+    fields.remove("allowed_countries");
+    fields.remove("blocked_countries");
+    fields.remove("translations");
+    fields.remove("machine");
+
+    let result: UpdateResult = collection
+        .update_one(
+            doc! { "slug": slug },
+            doc! { "$set": fields },
+            UpdateOptions::default(),
+        )
+        .await?;
+
+    Ok(result.matched_count == 1)
+}
+
+pub(super) async fn set_game_enabled(
+    slug: &str,
+    enabled: bool,
+    conn: mongodb::Database,
+) -> Result<bool, mongodb::error::Error> {
+    let collection: mongodb::Collection<Game> = conn.collection::<Game>("catalog");
+    let result: UpdateResult = collection
+        .update_one(
+            doc! { "slug": slug },
+            doc! { "$set": { "enabled": enabled } },
+            UpdateOptions::default(),
+        )
+        .await?;
+
+    Ok(result.matched_count == 1)
+}
+
+/// Overrides where the game is offered, returns false when there is no such game.
+pub(super) async fn set_game_countries(
+    slug: &str,
+    allowed: &[String],
+    blocked: &[String],
+    conn: mongodb::Database,
+) -> Result<bool, mongodb::error::Error> {
+    let collection: mongodb::Collection<Game> = conn.collection::<Game>("catalog");
+    let result: UpdateResult = collection
+        .update_one(
+            doc! { "slug": slug },
+            doc! { "$set": { "allowed_countries": allowed, "blocked_countries": blocked } },
+            UpdateOptions::default(),
+        )
+        .await?;
+
+    Ok(result.matched_count == 1)
+}
+
+/// Sets the name and description in the locale, returns false when there is no such game.
+pub(super) async fn set_game_translation(
+    slug: &str,
+    locale: Locale,
+    translation: &GameTranslation,
+    conn: mongodb::Database,
+) -> Result<bool, mongodb::error::Error> {
+    let collection: mongodb::Collection<Game> = conn.collection::<Game>("catalog");
+    let result: UpdateResult = collection
+        .update_one(
+            doc! { "slug": slug },
+            doc! { "$set": { format!("translations.{locale}"): to_bson(translation)? } },
+            UpdateOptions::default(),
+        )
+        .await?;
+
+    Ok(result.matched_count == 1)
+}
+
+/// Sets the reels and paytable, returns false when there is no such slot game.
+pub(super) async fn set_game_machine(
+    slug: &str,
+    machine: &Machine,
+    conn: mongodb::Database,
+) -> Result<bool, mongodb::error::Error> {
+    let collection: mongodb::Collection<Game> = conn.collection::<Game>("catalog");
+    let result: UpdateResult = collection
+        .update_one(
+            doc! { "slug": slug, "category": GameCategory::Slots.to_string() },
+            doc! { "$set": { "machine": to_bson(machine)? } },
+            UpdateOptions::default(),
+        )
+        .await?;
+
+    Ok(result.matched_count == 1)
+}
+
+/// Removes the translation, returns false when the game is not translated in the locale.
+pub(super) async fn delete_game_translation(
+    slug: &str,
+    locale: Locale,
+    conn: mongodb::Database,
+) -> Result<bool, mongodb::error::Error> {
+    let collection: mongodb::Collection<Game> = conn.collection::<Game>("catalog");
+    let field: String = format!("translations.{locale}");
+    let result: UpdateResult = collection
+        .update_one(
+            doc! { "slug": slug, &field: { "$exists": true } },
+            doc! { "$unset": { &field: "" } },
+            UpdateOptions::default(),
+        )
+        .await?;
+
+    Ok(result.matched_count == 1)
+}
+
+pub(super) async fn delete_game(
+    slug: &str,
+    conn: mongodb::Database,
+) -> Result<bool, mongodb::error::Error> {
+    let collection: mongodb::Collection<Game> = conn.collection::<Game>("catalog");
+    let result: DeleteResult = collection.delete_one(doc! { "slug": slug }, None).await?;
+
+    Ok(result.deleted_count == 1)
+}
+
+/// Whether the write was rejected by the unique slug index.
+pub(super) fn is_duplicate_slug(e: &mongodb::error::Error) -> bool {
+    matches!(
+        e.kind.as_ref(),
+        ErrorKind::Write(WriteFailure::WriteError(v)) if v.code == 11000
+    )
+}
@@ -0,0 +1,240 @@
+use crate::{
+    catalog::model::{Game, GameCategory, GameTranslation, PlayStats},
+    CATALOG_ADMIN_TOKEN,
+};
+
+use askama::Template;
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts, response::Html};
+use chrono::NaiveDate;
+use leprecon::{
+    auth::valid_bearer,
+    template::Snackbar,
+    utils::validate::{Validate, ValidationError},
+};
+use regex::Regex;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::{collections::HashMap, sync::OnceLock};
+
+static SLUG: OnceLock<Regex> = OnceLock::new();
+static CURRENCY: OnceLock<Regex> = OnceLock::new();
+static COUNTRY: OnceLock<Regex> = OnceLock::new();
+
+/// Only lets requests through which carry the admin token as bearer.
+pub(crate) struct AdminToken;
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AdminToken
+where
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, Html<String>);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let valid: bool = valid_bearer(&parts.headers, CATALOG_ADMIN_TOKEN.get().unwrap());
+
+        if !valid {
+            let snackbar: Snackbar<'_> = Snackbar {
+                message: "unauthorized",
+                ..Default::default()
+            };
+            return Err((StatusCode::UNAUTHORIZED, Html(snackbar.render().unwrap())));
+        }
+
+        Ok(AdminToken)
+    }
+}
+
+/// Catalog entry as submitted by an admin, currencies and tags are comma separated.
+#[derive(Deserialize, Debug)]
+pub(crate) struct GameParams {
+    #[serde(default)]
+    pub slug: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub category: GameCategory,
+    #[serde(default)]
+    pub provider: String,
+    pub rtp: f64,
+    pub min_bet: f64,
+    pub max_bet: f64,
+    #[serde(default)]
+    pub currencies: String,
+    #[serde(default)]
+    pub thumbnail_url: String,
+    #[serde(default)]
+    pub tags: String,
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub release_date: String,
+}
+
+impl Validate for GameParams {
+    fn validate(&self) -> Result<(), ValidationError> {
+        let slug: &Regex = SLUG.get_or_init(|| Regex::new(r"^[a-z0-9]+(-[a-z0-9]+)*$").unwrap());
+        let currency: &Regex = CURRENCY.get_or_init(|| Regex::new(r"^[A-Z]{3}$").unwrap());
+
+        if !slug.is_match(&self.slug) {
+            return Err(ValidationError::new(
+                "slug",
+                "must be lowercase letters and digits, separated by dashes",
+            ));
+        }
+
+        // Taken by the lists of the user
+        if ["favourites", "recent"].contains(&self.slug.as_str()) {
+            return Err(ValidationError::new("slug", "is reserved"));
+        }
+
+        if self.name.trim().is_empty() {
+            return Err(ValidationError::new("name", "is required"));
+        }
+
+        if self.provider.trim().is_empty() {
+            return Err(ValidationError::new("provider", "is required"));
+        }
+
+        if !(self.rtp > 0.0 && self.rtp <= 100.0) {
+            return Err(ValidationError::new("rtp", "must be between 0 and 100"));
+        }
+
+        if self.min_bet <= 0.0 {
+            return Err(ValidationError::new("min_bet", "must be positive"));
+        }
+
+        if self.max_bet < self.min_bet {
+            return Err(ValidationError::new(
+                "max_bet",
+                "must be at least the minimum bet",
+            ));
+        }
+
+        let currencies: Vec<String> = split_list(&self.currencies);
+        if currencies.is_empty() || !currencies.iter().all(|c| currency.is_match(c)) {
+            return Err(ValidationError::new(
+                "currencies",
+                "must be one or more currency codes",
+            ));
+        }
+
+        if NaiveDate::parse_from_str(&self.release_date, "%Y-%m-%d").is_err() {
+            return Err(ValidationError::new("release_date", "must be a date"));
+        }
+
+        Ok(())
+    }
+}
+
+impl From<GameParams> for Game {
+    /// Only call on validated params.
+    fn from(params: GameParams) -> Self {
+        let release_date: NaiveDate =
+            NaiveDate::parse_from_str(&params.release_date, "%Y-%m-%d").unwrap();
+
+        Game {
+            id: None,
+            slug: params.slug,
+            name: params.name.trim().to_owned(),
+            description: params.description.trim().to_owned(),
+            category: params.category,
+            provider: params.provider.trim().to_owned(),
+            rtp: params.rtp,
+            min_bet: params.min_bet,
+            max_bet: params.max_bet,
+            currencies: split_list(&params.currencies),
+            thumbnail_url: params.thumbnail_url.trim().to_owned(),
+            tags: split_list(&params.tags),
+            enabled: params.enabled,
+            release_date: release_date.and_time(Default::default()).and_utc(),
+            allowed_countries: vec![],
+            blocked_countries: vec![],
+            translations: HashMap::new(),
+            stats: PlayStats::default(),
+            machine: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct EnabledParams {
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// Name and description of a game in another locale.
+#[derive(Deserialize, Debug)]
+pub(crate) struct TranslationParams {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+impl Validate for TranslationParams {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.name.trim().is_empty() {
+            return Err(ValidationError::new("name", "is required"));
+        }
+
+        Ok(())
+    }
+}
+
+impl From<TranslationParams> for GameTranslation {
+    fn from(params: TranslationParams) -> Self {
+        GameTranslation {
+            name: params.name.trim().to_owned(),
+            description: params.description.trim().to_owned(),
+        }
+    }
+}
+
+/// Countries the game is restricted to or blocked in, as comma separated country codes.
+#[derive(Deserialize, Debug)]
+pub(crate) struct CountryParams {
+    #[serde(default)]
+    pub allowed: String,
+    #[serde(default)]
+    pub blocked: String,
+}
+
+impl CountryParams {
+    pub(super) fn allowed(&self) -> Vec<String> {
+        country_list(&self.allowed)
+    }
+
+    pub(super) fn blocked(&self) -> Vec<String> {
+        country_list(&self.blocked)
+    }
+}
+
+impl Validate for CountryParams {
+    fn validate(&self) -> Result<(), ValidationError> {
+        let country: &Regex = COUNTRY.get_or_init(|| Regex::new(r"^[A-Z]{2}$").unwrap());
+
+        if !self.allowed().iter().all(|c| country.is_match(c)) {
+            return Err(ValidationError::new("allowed", "must be country codes"));
+        }
+
+        if !self.blocked().iter().all(|c| country.is_match(c)) {
+            return Err(ValidationError::new("blocked", "must be country codes"));
+        }
+
+        Ok(())
+    }
+}
+
+fn country_list(list: &str) -> Vec<String> {
+    split_list(list).iter().map(|v| v.to_uppercase()).collect()
+}
+
+fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
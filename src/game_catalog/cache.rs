@@ -0,0 +1,164 @@
+mod db;
+mod model;
+
+pub(crate) use model::MemoryCache;
+
+use self::{
+    db::{
+        cache_response, get_cached_response, get_catalog_version, invalidate, INVALIDATION_CHANNEL,
+    },
+    model::CachedResponse,
+};
+
+use crate::{StateParams, CATALOG_CACHE_SECONDS, VALKEY_CONN};
+
+use axum::{
+    http::{header, HeaderMap},
+    response::{Html, IntoResponse, Response},
+};
+use bb8_redis::{bb8::Pool, RedisConnectionManager};
+use futures::StreamExt;
+use leprecon::{i18n::current_locale, utils::RedisConn};
+use redis::aio::PubSub;
+use reqwest::StatusCode;
+use std::{future::Future, sync::Arc, time::Duration};
+use tracing::{error, warn};
+
+/// Responses differ per locale, which comes from these headers.
+const VARY: &str = "Accept-Language, Cookie";
+
+/// Serves the response from the cache of this replica or Valkey, and renders it on a miss.
+///
+/// Only successful responses are cached, per locale. They carry an ETag, a matching
+/// `If-None-Match` gets a 304 without body.
+pub(crate) async fn cached<F, Fut>(
+    state: &StateParams,
+    key: String,
+    headers: &HeaderMap,
+    render: F,
+) -> Response
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = (StatusCode, Html<String>)>,
+{
+    let ttl: u64 = *CATALOG_CACHE_SECONDS.get().unwrap();
+    let key: String = format!("{}:{key}", current_locale());
+
+    if let Some(v) = state.2.get(&key, Duration::from_secs(ttl)) {
+        return respond(headers, v);
+    }
+
+    let mut redis_conn: Option<RedisConn> = match state.1.get().await {
+        Ok(v) => Some(v),
+        Err(e) => {
+            error!("Cannot get connection from pool: {:?}", e);
+            None
+        }
+    };
+
+    let mut version: Option<u64> = None;
+    if let Some(conn) = redis_conn.as_mut() {
+        match get_catalog_version(conn).await {
+            Ok(v) => version = Some(v),
+            Err(e) => error!("Could not get catalog version: {:?}", e),
+        }
+    }
+
+    if let (Some(conn), Some(version)) = (redis_conn.as_mut(), version) {
+        match get_cached_response(version, &key, conn).await {
+            Ok(Some(v)) => {
+                state.2.insert(key, v.clone(), Duration::from_secs(ttl));
+                return respond(headers, v);
+            }
+            Ok(None) => (),
+            Err(e) => error!("Could not get cached response: {:?}", e),
+        }
+    }
+
+    let (status, Html(body)) = render().await;
+    if status != StatusCode::OK {
+        return (status, Html(body)).into_response();
+    }
+
+    let response: CachedResponse = CachedResponse::new(body);
+
+    if let (Some(conn), Some(version)) = (redis_conn.as_mut(), version) {
+        if let Err(e) = cache_response(version, &key, &response, ttl, conn).await {
+            error!("Could not cache response: {:?}", e);
+        }
+    }
+    state
+        .2
+        .insert(key, response.clone(), Duration::from_secs(ttl));
+
+    respond(headers, response)
+}
+
+/// Drops the cached responses of every replica, after the catalog changed.
+pub(crate) async fn invalidate_catalog(redis_pool: &Pool<RedisConnectionManager>) {
+    match redis_pool.get().await {
+        Ok(mut v) => {
+            if let Err(e) = invalidate(&mut v).await {
+                error!("Could not invalidate catalog cache: {:?}", e);
+            }
+        }
+        Err(e) => error!("Cannot get connection from pool: {:?}", e),
+    }
+}
+
+/// Clears the cache of this replica whenever any replica changes the catalog.
+pub(super) async fn subscribe_invalidations(cache: Arc<MemoryCache>) {
+    loop {
+        if let Err(e) = clear_on_invalidation(&cache).await {
+            warn!(
+                "Lost catalog invalidation subscription, reconnecting: {:?}",
+                e
+            );
+        }
+
+        // Invalidations might have been missed while disconnected
+        cache.clear();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+async fn clear_on_invalidation(cache: &MemoryCache) -> redis::RedisResult<()> {
+    let client: redis::Client = redis::Client::open(VALKEY_CONN.get().unwrap().as_str())?;
+    let mut pubsub: PubSub = client.get_async_pubsub().await?;
+    pubsub.subscribe(INVALIDATION_CHANNEL).await?;
+
+    let mut messages = pubsub.on_message();
+    while messages.next().await.is_some() {
+        cache.clear();
+    }
+
+    Ok(())
+}
+
+fn respond(headers: &HeaderMap, response: CachedResponse) -> Response {
+    let not_modified: bool = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.split(',').any(|t| t.trim() == response.etag));
+
+    if not_modified {
+        return (
+            StatusCode::NOT_MODIFIED,
+            [
+                (header::ETAG, response.etag),
+                (header::VARY, VARY.to_owned()),
+            ],
+        )
+            .into_response();
+    }
+
+    (
+        StatusCode::OK,
+        [
+            (header::ETAG, response.etag),
+            (header::VARY, VARY.to_owned()),
+        ],
+        Html(response.body),
+    )
+        .into_response()
+}
@@ -0,0 +1,54 @@
+use super::model::CachedResponse;
+
+use leprecon::utils::RedisConn;
+use redis::{AsyncCommands, RedisResult};
+use std::error::Error;
+
+/// Valkey channel on which admin writes are announced, so replicas drop their cached responses.
+pub(super) const INVALIDATION_CHANNEL: &str = "catalog_invalidated";
+
+const VERSION_KEY: &str = "catalog:version";
+
+/// Cached responses are stored per catalog version, a new version leaves the old entries to expire.
+pub(super) async fn get_catalog_version(conn: &mut RedisConn<'_>) -> RedisResult<u64> {
+    let version: Option<u64> = conn.get(VERSION_KEY).await?;
+
+    Ok(version.unwrap_or_default())
+}
+
+pub(super) async fn get_cached_response(
+    version: u64,
+    key: &str,
+    conn: &mut RedisConn<'_>,
+) -> Result<Option<CachedResponse>, Box<dyn Error + Send + Sync>> {
+    let cached: Option<String> = conn.get(format!("catalog:{version}:{key}")).await?;
+
+    Ok(match cached {
+        Some(v) => Some(serde_json::from_str(&v)?),
+        None => None,
+    })
+}
+
+pub(super) async fn cache_response(
+    version: u64,
+    key: &str,
+    response: &CachedResponse,
+    expires: u64,
+    conn: &mut RedisConn<'_>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let _: () = conn
+        .set_ex(
+            format!("catalog:{version}:{key}"),
+            serde_json::to_string(response)?,
+            expires,
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Moves to a new catalog version, and tells every replica.
+pub(super) async fn invalidate(conn: &mut RedisConn<'_>) -> RedisResult<()> {
+    let _: u64 = conn.incr(VERSION_KEY, 1).await?;
+    conn.publish(INVALIDATION_CHANNEL, "").await
+}
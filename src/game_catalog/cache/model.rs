@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Rendered response, as cached.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct CachedResponse {
+    pub body: String,
+    pub etag: String,
+}
+
+impl CachedResponse {
+    pub(super) fn new(body: String) -> CachedResponse {
+        let mut hasher: DefaultHasher = DefaultHasher::new();
+        body.hash(&mut hasher);
+
+        CachedResponse {
+            etag: format!("\"{:016x}\"", hasher.finish()),
+            body,
+        }
+    }
+}
+
+/// Responses cached by this replica, in front of the shared cache in Valkey.
+#[derive(Default)]
+pub(crate) struct MemoryCache {
+    entries: Mutex<HashMap<String, (Instant, CachedResponse)>>,
+}
+
+impl MemoryCache {
+    pub(super) fn get(&self, key: &str, ttl: Duration) -> Option<CachedResponse> {
+        let entries = self.entries.lock().unwrap();
+
+        match entries.get(key) {
+            Some((stored, v)) if stored.elapsed() < ttl => Some(v.clone()),
+            _ => None,
+        }
+    }
+
+    pub(super) fn insert(&self, key: String, response: CachedResponse, ttl: Duration) {
+        let mut entries = self.entries.lock().unwrap();
+
+        entries.retain(|_, (stored, _)| stored.elapsed() < ttl);
+        entries.insert(key, (Instant::now(), response));
+    }
+
+    pub(super) fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
@@ -1,19 +1,69 @@
-mod db;
+pub(crate) mod db;
+pub(crate) mod model;
 
 use askama::Template;
-use axum::{extract::State, response::Html};
-use leprecon::template::{self, Catalog, Snackbar};
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    response::{Html, Response},
+    Form,
+};
+use leprecon::{
+    auth::AuthParam,
+    i18n::current_locale,
+    jurisdiction::jurisdiction_filter,
+    template::{self, Catalog, Snackbar},
+    utils::extract::ValidForm,
+};
 use reqwest::StatusCode;
 use tracing::debug;
 
-use self::db::get_catalog_db;
+use crate::{cache::cached, jurisdiction::country_code, StateParams};
 
+use self::{
+    db::{get_catalog_page, get_game_db, get_games_by_slug},
+    model::{CatalogQuery, Game},
+};
+
+const PAGE_SIZE: i64 = 20;
+
+/// Games in the catalog, a page at a time.
+///
+/// The first page comes with the search form, later pages are only the games and render the
+/// trigger for the page after them, for infinite scroll. Only games offered in the country of the
+/// user are shown.
 pub(super) async fn get_catalog(
-    State(state): State<mongodb::Database>,
+    State(state): State<StateParams>,
+    headers: HeaderMap,
+    ValidForm(query): ValidForm<CatalogQuery>,
+) -> Response {
+    let country: Option<String> = country_code(&query.sub, &state).await;
+    let key: String = format!(
+        "catalog:{}?{}",
+        country.as_deref().unwrap_or_default(),
+        serde_urlencoded::to_string(&query).unwrap_or_default()
+    );
+    let db: mongodb::Database = state.0.clone();
+
+    cached(&state, key, &headers, || render_catalog(query, country, db)).await
+}
+
+async fn render_catalog(
+    query: CatalogQuery,
+    country: Option<String>,
+    db: mongodb::Database,
 ) -> (StatusCode, Html<String>) {
     let snackbar: Snackbar<'_> = Snackbar::default();
 
-    let catalogs: Vec<Catalog> = match get_catalog_db(state).await {
+    // One extra to know whether there is a next page
+    let mut games: Vec<Game> = match get_catalog_page(
+        &query,
+        jurisdiction_filter(country.as_deref()),
+        PAGE_SIZE + 1,
+        db,
+    )
+    .await
+    {
         Ok(v) => v,
         Err(e) => {
             debug!("Could not get catalog: {:?}", e);
@@ -21,6 +71,148 @@ pub(super) async fn get_catalog(
         }
     };
 
-    let catalog_template: template::Catalogs = template::Catalogs { catalogs };
+    let next: Option<String> = match games.len() as i64 > PAGE_SIZE {
+        true => {
+            games.truncate(PAGE_SIZE as usize);
+            games
+                .last()
+                .and_then(|g| query.sort.cursor(g))
+                .map(|cursor| next_page(&query, cursor))
+        }
+        false => None,
+    };
+
+    let catalogs: Vec<Catalog> = games.into_iter().map(catalog_entry).collect();
+
+    if !query.cursor.is_empty() {
+        let page_template: template::CatalogPage = template::CatalogPage { catalogs, next };
+        return (StatusCode::OK, Html(page_template.render().unwrap()));
+    }
+
+    let catalog_template: template::Catalogs = template::Catalogs {
+        title: "catalog-title".to_owned(),
+        search: true,
+        catalogs,
+        next,
+        q: query.q,
+        category: query.category,
+        tag: query.tag,
+        sort: format!("{:?}", query.sort),
+    };
+    (StatusCode::OK, Html(catalog_template.render().unwrap()))
+}
+
+/// Details of a single game.
+pub(super) async fn get_game(
+    State(state): State<StateParams>,
+    headers: HeaderMap,
+    Path(slug): Path<String>,
+    Form(auth_param): Form<AuthParam>,
+) -> Response {
+    let country: Option<String> = country_code(&auth_param.sub, &state).await;
+    let key: String = format!("game:{}:{slug}", country.as_deref().unwrap_or_default());
+    let db: mongodb::Database = state.0.clone();
+
+    cached(&state, key, &headers, || render_game(slug, country, db)).await
+}
+
+async fn render_game(
+    slug: String,
+    country: Option<String>,
+    db: mongodb::Database,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    let game: Game = match get_game_db(&slug, jurisdiction_filter(country.as_deref()), db).await {
+        Ok(Some(v)) => v.localise(current_locale()),
+        Ok(None) => {
+            snackbar.message = "game-does-not-exist";
+            return (StatusCode::NOT_FOUND, Html(snackbar.render().unwrap()));
+        }
+        Err(e) => {
+            debug!("Could not get game: {:?}", e);
+            return (StatusCode::BAD_GATEWAY, Html(snackbar.render().unwrap()));
+        }
+    };
+
+    let game_template: template::GameDetail = template::GameDetail {
+        slug: game.slug,
+        name: game.name,
+        description: game.description,
+        category: game.category.to_string(),
+        provider: game.provider,
+        rtp: game.rtp,
+        min_bet: game.min_bet,
+        max_bet: game.max_bet,
+        currencies: game.currencies,
+        thumbnail_url: game.thumbnail_url,
+        tags: game.tags,
+        release_date: game.release_date.format("%Y-%m-%d").to_string(),
+        plays_day: game.stats.day,
+        plays_week: game.stats.week,
+    };
+    (StatusCode::OK, Html(game_template.render().unwrap()))
+}
+
+/// Games of a list of the user, such as favourites, in the order of the slugs and without search.
+///
+/// The title is a message id.
+pub(crate) async fn render_game_list(
+    title: &str,
+    slugs: Vec<String>,
+    country: Option<String>,
+    db: mongodb::Database,
+) -> (StatusCode, Html<String>) {
+    let snackbar: Snackbar<'_> = Snackbar::default();
+
+    let games: Vec<Game> =
+        match get_games_by_slug(&slugs, jurisdiction_filter(country.as_deref()), db).await {
+            Ok(v) => v,
+            Err(e) => {
+                debug!("Could not get games: {:?}", e);
+                return (StatusCode::BAD_GATEWAY, Html(snackbar.render().unwrap()));
+            }
+        };
+
+    let catalog_template: template::Catalogs = template::Catalogs {
+        title: title.to_owned(),
+        search: false,
+        catalogs: games.into_iter().map(catalog_entry).collect(),
+        next: None,
+        q: String::new(),
+        category: String::new(),
+        tag: String::new(),
+        sort: String::new(),
+    };
     (StatusCode::OK, Html(catalog_template.render().unwrap()))
 }
+
+fn catalog_entry(game: Game) -> Catalog {
+    let game: Game = game.localise(current_locale());
+
+    Catalog {
+        slug: game.slug,
+        name: game.name,
+        description: game.description,
+        category: game.category.to_string(),
+        provider: game.provider,
+        thumbnail_url: game.thumbnail_url,
+        plays: game.stats.week,
+    }
+}
+
+fn next_page(query: &CatalogQuery, cursor: String) -> String {
+    let next: CatalogQuery = CatalogQuery {
+        q: query.q.clone(),
+        category: query.category.clone(),
+        tag: query.tag.clone(),
+        sort: query.sort,
+        cursor,
+        sub: String::new(),
+    };
+
+    format!(
+        "/game/catalog?{}",
+        serde_urlencoded::to_string(&next).unwrap_or_default()
+    )
+}
@@ -1,17 +1,192 @@
+use super::model::{CatalogQuery, Game, GameCategory, PageCursor};
+
+use chrono::Utc;
+
 use futures::TryStreamExt;
-use leprecon::template::Catalog;
-use mongodb::Cursor;
+use mongodb::{
+    bson::{doc, Document},
+    options::{FindOneOptions, FindOptions, IndexOptions},
+    results::UpdateResult,
+    Cursor, IndexModel,
+};
+use tracing::info;
+
+/// Enforces unique slugs and enables search, documents from before slugs existed are backfilled first.
+pub(crate) async fn init_catalog_db(conn: &mongodb::Database) -> Result<(), mongodb::error::Error> {
+    let collection: mongodb::Collection<Game> = conn.collection::<Game>("catalog");
+
+    backfill_catalog(conn).await?;
+
+    let index: IndexModel = IndexModel::builder()
+        .keys(doc! { "slug": 1 })
+        .options(IndexOptions::builder().unique(true).build())
+        .build();
+    collection.create_index(index, None).await?;
+
+    let index: IndexModel = IndexModel::builder()
+        .keys(doc! { "name": "text", "description": "text" })
+        .build();
+    collection.create_index(index, None).await?;
+
+    Ok(())
+}
+
+/// Gives games from before the current model a slug derived from their name and defaults for
+/// the fields they miss, a number is appended when the slug is taken already.
+async fn backfill_catalog(conn: &mongodb::Database) -> Result<(), mongodb::error::Error> {
+    let collection: mongodb::Collection<Document> = conn.collection::<Document>("catalog");
+
+    let mut cursor: Cursor<Document> = collection
+        .find(doc! { "slug": { "$exists": false } }, None)
+        .await?;
+    let mut legacy: Vec<Document> = vec![];
+
+    while let Some(d) = cursor.try_next().await? {
+        legacy.push(d);
+    }
 
-pub(super) async fn get_catalog_db(
+    for game in legacy {
+        let base: String = slug_from_name(game.get_str("name").unwrap_or("game"));
+        let mut slug: String = base.clone();
+        let mut n: u32 = 1;
+
+        while collection
+            .find_one(doc! { "slug": &slug }, None)
+            .await?
+            .is_some()
+        {
+            n += 1;
+            slug = format!("{}-{}", base, n);
+        }
+
+        collection
+            .update_one(
+                doc! { "_id": game.get("_id") },
+                doc! { "$set": { "slug": &slug } },
+                None,
+            )
+            .await?;
+        info!("Backfilled slug {}", slug);
+    }
+
+    let defaults: Document = doc! {
+        "name": "",
+        "description": "",
+        "category": GameCategory::Table.to_string(),
+        "provider": "Leprecon",
+        "rtp": 0.0,
+        "min_bet": 1.0,
+        "max_bet": 100.0,
+        "currencies": ["EUR"],
+        "thumbnail_url": "",
+        "tags": [],
+        "enabled": true,
+        "release_date": Utc::now(),
+    };
+
+    for (field, value) in defaults {
+        let result: UpdateResult = collection
+            .update_many(
+                doc! { &field: { "$exists": false } },
+                doc! { "$set": { &field: value } },
+                None,
+            )
+            .await?;
+        if result.modified_count > 0 {
+            info!("Defaulted {} of {} games", field, result.modified_count);
+        }
+    }
+
+    Ok(())
+}
+
+/// Lowercase letters and digits of the name, other characters separate the words.
+fn slug_from_name(name: &str) -> String {
+    let slug: String = name
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-");
+
+    if slug.is_empty() {
+        "game".to_owned()
+    } else {
+        slug
+    }
+}
+
+/// One page of enabled games matching the query, following the cursor when given.
+pub(super) async fn get_catalog_page(
+    query: &CatalogQuery,
+    jurisdiction: Document,
+    limit: i64,
+    conn: mongodb::Database,
+) -> Result<Vec<Game>, mongodb::error::Error> {
+    let collection: mongodb::Collection<Game> = conn.collection::<Game>("catalog");
+
+    let mut filter: Document = doc! { "enabled": true };
+    filter.extend(jurisdiction);
+    if !query.q.is_empty() {
+        filter.insert("$text", doc! { "$search": &query.q });
+    }
+    if !query.category.is_empty() {
+        filter.insert("category", &query.category);
+    }
+    if !query.tag.is_empty() {
+        filter.insert("tags", &query.tag);
+    }
+    if let Some(cursor) = PageCursor::parse(&query.cursor, query.sort) {
+        filter.extend(query.sort.after(&cursor));
+    }
+
+    let options: FindOptions = FindOptions::builder()
+        .sort(query.sort.sort())
+        .limit(limit)
+        .build();
+    let mut cursor: Cursor<Game> = collection.find(filter, options).await?;
+    let mut games: Vec<Game> = vec![];
+
+    while let Some(g) = cursor.try_next().await? {
+        games.push(g);
+    }
+
+    Ok(games)
+}
+
+/// Enabled game with the slug, disabled games and games outside the jurisdiction are not shown.
+pub(crate) async fn get_game_db(
+    slug: &str,
+    jurisdiction: Document,
     conn: mongodb::Database,
-) -> Result<Vec<Catalog>, mongodb::error::Error> {
-    let collection: mongodb::Collection<Catalog> = conn.collection::<Catalog>("catalog");
-    let mut cursor: Cursor<Catalog> = collection.find(None, None).await?;
-    let mut catalogs: Vec<Catalog> = vec![];
+) -> Result<Option<Game>, mongodb::error::Error> {
+    let collection: mongodb::Collection<Game> = conn.collection::<Game>("catalog");
+
+    let mut filter: Document = doc! { "slug": slug, "enabled": true };
+    filter.extend(jurisdiction);
+
+    collection.find_one(filter, FindOneOptions::default()).await
+}
 
-    while let Some(c) = cursor.try_next().await? {
-        catalogs.push(c);
+/// Enabled games with the slugs, in the order of the slugs.
+pub(super) async fn get_games_by_slug(
+    slugs: &[String],
+    jurisdiction: Document,
+    conn: mongodb::Database,
+) -> Result<Vec<Game>, mongodb::error::Error> {
+    let collection: mongodb::Collection<Game> = conn.collection::<Game>("catalog");
+
+    let mut filter: Document = doc! { "slug": { "$in": slugs }, "enabled": true };
+    filter.extend(jurisdiction);
+
+    let mut cursor: Cursor<Game> = collection.find(filter, FindOptions::default()).await?;
+    let mut games: Vec<Game> = vec![];
+
+    while let Some(g) = cursor.try_next().await? {
+        games.push(g);
     }
 
-    Ok(catalogs)
+    games.sort_by_key(|g| slugs.iter().position(|s| *s == g.slug));
+
+    Ok(games)
 }
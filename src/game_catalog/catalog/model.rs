@@ -0,0 +1,232 @@
+use chrono::{DateTime, Utc};
+use leprecon::{
+    i18n::Locale,
+    machine::Machine,
+    utils::validate::{Validate, ValidationError},
+};
+use mongodb::bson::{
+    doc, oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime, Bson, Document,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, error::Error, fmt, str::FromStr};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub(crate) enum GameCategory {
+    Table,
+    Card,
+    Slots,
+    Live,
+}
+
+impl fmt::Display for GameCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for GameCategory {
+    type Err = ParseGameCategoryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Table" => Ok(GameCategory::Table),
+            "Card" => Ok(GameCategory::Card),
+            "Slots" => Ok(GameCategory::Slots),
+            "Live" => Ok(GameCategory::Live),
+            _ => Err(ParseGameCategoryError),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct ParseGameCategoryError;
+
+impl fmt::Display for ParseGameCategoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "provided string was not a game category".fmt(f)
+    }
+}
+
+impl Error for ParseGameCategoryError {}
+
+/// Game as stored in the `catalog` collection, the slug is its stable public id.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct Game {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub slug: String,
+    pub name: String,
+    pub description: String,
+    pub category: GameCategory,
+    pub provider: String,
+    pub rtp: f64,
+    pub min_bet: f64,
+    pub max_bet: f64,
+    pub currencies: Vec<String>,
+    pub thumbnail_url: String,
+    pub tags: Vec<String>,
+    pub enabled: bool,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub release_date: DateTime<Utc>,
+    /// Empty when the game is offered everywhere, apart from the blocked countries.
+    #[serde(default)]
+    pub allowed_countries: Vec<String>,
+    #[serde(default)]
+    pub blocked_countries: Vec<String>,
+    /// Names and descriptions in other locales, keyed by locale code.
+    #[serde(default)]
+    pub translations: HashMap<String, GameTranslation>,
+    /// Maintained from play events, never set by admins.
+    #[serde(default)]
+    pub stats: PlayStats,
+    /// Reels and paytable the slots service plays, only for slot games.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub machine: Option<Machine>,
+}
+
+impl Game {
+    /// Uses the name and description of the locale, when the game is translated.
+    pub(crate) fn localise(mut self, locale: Locale) -> Game {
+        if let Some(translation) = self.translations.remove(locale.code()) {
+            self.name = translation.name;
+            self.description = translation.description;
+        }
+
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct GameTranslation {
+    pub name: String,
+    pub description: String,
+}
+
+/// Plays over the rolling windows, as last aggregated.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct PlayStats {
+    pub day: i64,
+    pub week: i64,
+    /// Plays of the last day relative to the daily average of the week.
+    pub trending: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) enum CatalogSort {
+    #[default]
+    Popularity,
+    Trending,
+    Name,
+    Newest,
+}
+
+impl CatalogSort {
+    fn field(&self) -> &'static str {
+        match self {
+            CatalogSort::Popularity => "stats.week",
+            CatalogSort::Trending => "stats.trending",
+            CatalogSort::Name => "name",
+            CatalogSort::Newest => "release_date",
+        }
+    }
+
+    fn direction(&self) -> i32 {
+        match self {
+            CatalogSort::Name => 1,
+            CatalogSort::Popularity | CatalogSort::Trending | CatalogSort::Newest => -1,
+        }
+    }
+
+    /// Sort order, ties are broken by id so every game has a stable position.
+    pub(super) fn sort(&self) -> Document {
+        doc! { self.field(): self.direction(), "_id": self.direction() }
+    }
+
+    /// Games which come after the game the cursor points at.
+    pub(super) fn after(&self, cursor: &PageCursor) -> Document {
+        let op: &str = match self.direction() {
+            1 => "$gt",
+            _ => "$lt",
+        };
+
+        doc! {
+            "$or": [
+                { self.field(): { op: cursor.value.clone() } },
+                { self.field(): cursor.value.clone(), "_id": { op: cursor.id } },
+            ]
+        }
+    }
+
+    /// Cursor pointing at the game, for the next page.
+    pub(super) fn cursor(&self, game: &Game) -> Option<String> {
+        let value: String = match self {
+            CatalogSort::Popularity => game.stats.week.to_string(),
+            CatalogSort::Trending => game.stats.trending.to_string(),
+            CatalogSort::Name => game.name.clone(),
+            CatalogSort::Newest => game.release_date.timestamp_millis().to_string(),
+        };
+
+        game.id.map(|id| format!("{value}_{}", id.to_hex()))
+    }
+}
+
+/// Position in the catalog, the sort value and id of the last game shown.
+#[derive(Debug)]
+pub(super) struct PageCursor {
+    pub value: Bson,
+    pub id: ObjectId,
+}
+
+impl PageCursor {
+    pub(super) fn parse(cursor: &str, sort: CatalogSort) -> Option<PageCursor> {
+        let (value, id) = cursor.rsplit_once('_')?;
+        let id: ObjectId = ObjectId::from_str(id).ok()?;
+
+        let value: Bson = match sort {
+            CatalogSort::Popularity => Bson::Int64(value.parse().ok()?),
+            CatalogSort::Trending => Bson::Double(value.parse().ok()?),
+            CatalogSort::Name => Bson::String(value.to_owned()),
+            CatalogSort::Newest => {
+                Bson::DateTime(mongodb::bson::DateTime::from_millis(value.parse().ok()?))
+            }
+        };
+
+        Some(PageCursor { value, id })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub(crate) struct CatalogQuery {
+    /// Only used to find the country of the user, it is not part of links or cache keys.
+    #[serde(default, skip_serializing)]
+    pub sub: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub q: String,
+    /// Empty for all categories.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub category: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub tag: String,
+    #[serde(default)]
+    pub sort: CatalogSort,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub cursor: String,
+}
+
+impl Validate for CatalogQuery {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.q.len() > 100 {
+            return Err(ValidationError::new("q", "is too long"));
+        }
+
+        if !self.category.is_empty() && GameCategory::from_str(&self.category).is_err() {
+            return Err(ValidationError::new("category", "is unknown"));
+        }
+
+        if !self.cursor.is_empty() && PageCursor::parse(&self.cursor, self.sort).is_none() {
+            return Err(ValidationError::new("cursor", "is invalid"));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+mod db;
+mod model;
+
+pub(crate) use db::{init_deleted_accounts_db, is_deleted_account};
+
+use self::db::insert_deleted_account;
+
+use crate::{favourite::delete_user_favourites, recent::delete_user_recently_played};
+
+use futures::StreamExt;
+use leprecon::broker::AccountDeleted;
+use rabbitmq_stream_client::Consumer;
+use tracing::{debug, error, info};
+
+/// Forgets the favourites and recently played games of deleted accounts.
+///
+/// The account is remembered first, so replayed plays do not list games for it again.
+pub(super) async fn consume_account_deleted(mut consumer: Consumer, mongo_db: mongodb::Database) {
+    while let Some(delivery) = consumer.next().await {
+        let d = match delivery {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Could not receive account deletion: {:?}", e);
+                continue;
+            }
+        };
+
+        let event: AccountDeleted = match d
+            .message()
+            .data()
+            .map(serde_json::from_slice::<AccountDeleted>)
+        {
+            Some(Ok(v)) => v,
+            Some(Err(e)) => {
+                debug!(
+                    "Skipping malformed account deletion at {}: {:?}",
+                    d.offset(),
+                    e
+                );
+                continue;
+            }
+            None => continue,
+        };
+
+        if let Err(e) = insert_deleted_account(&event.sub, &mongo_db).await {
+            error!("Could not remember deleted account {:?}: {:?}", event, e);
+            continue;
+        }
+
+        if let Err(e) = delete_user_favourites(&event.sub, &mongo_db).await {
+            error!("Could not purge favourites {:?}: {:?}", event, e);
+            continue;
+        }
+
+        match delete_user_recently_played(&event.sub, &mongo_db).await {
+            Ok(_) => info!("Purged games of deleted account {}", event.sub),
+            Err(e) => error!("Could not purge recently played games {:?}: {:?}", event, e),
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use super::model::DeletedAccount;
+
+use chrono::Utc;
+use mongodb::{
+    bson::doc,
+    options::{IndexOptions, UpdateOptions},
+    IndexModel,
+};
+use std::time::Duration;
+
+/// Tombstones are removed by Mongo once no replayed play can be of the account anymore.
+pub(crate) async fn init_deleted_accounts_db(
+    conn: &mongodb::Database,
+    expires: Duration,
+) -> Result<(), mongodb::error::Error> {
+    let collection: mongodb::Collection<DeletedAccount> =
+        conn.collection::<DeletedAccount>("deleted_accounts");
+
+    let index: IndexModel = IndexModel::builder()
+        .keys(doc! { "sub": 1 })
+        .options(IndexOptions::builder().unique(true).build())
+        .build();
+    collection.create_index(index, None).await?;
+
+    let index: IndexModel = IndexModel::builder()
+        .keys(doc! { "deleted_at": 1 })
+        .options(IndexOptions::builder().expire_after(expires).build())
+        .build();
+    collection.create_index(index, None).await?;
+
+    Ok(())
+}
+
+pub(super) async fn insert_deleted_account(
+    sub: &str,
+    conn: &mongodb::Database,
+) -> Result<(), mongodb::error::Error> {
+    let collection: mongodb::Collection<DeletedAccount> =
+        conn.collection::<DeletedAccount>("deleted_accounts");
+
+    collection
+        .update_one(
+            doc! { "sub": sub },
+            doc! { "$setOnInsert": { "deleted_at": Utc::now() } },
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub(crate) async fn is_deleted_account(
+    sub: &str,
+    conn: &mongodb::Database,
+) -> Result<bool, mongodb::error::Error> {
+    let collection: mongodb::Collection<DeletedAccount> =
+        conn.collection::<DeletedAccount>("deleted_accounts");
+
+    Ok(collection
+        .find_one(doc! { "sub": sub }, None)
+        .await?
+        .is_some())
+}
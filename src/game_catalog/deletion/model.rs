@@ -0,0 +1,11 @@
+use chrono::{DateTime, Utc};
+use mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime;
+use serde::{Deserialize, Serialize};
+
+/// Tombstone of a deleted account, as stored in the `deleted_accounts` collection.
+#[derive(Serialize, Deserialize, Debug)]
+pub(super) struct DeletedAccount {
+    pub sub: String,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub deleted_at: DateTime<Utc>,
+}
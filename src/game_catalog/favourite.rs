@@ -0,0 +1,121 @@
+mod db;
+mod model;
+
+pub(crate) use db::{delete_user_favourites, init_favourites_db};
+
+use self::db::{delete_favourite, get_favourites, insert_favourite};
+
+use crate::{
+    catalog::{db::get_game_db, render_game_list},
+    jurisdiction::country_code,
+    StateParams,
+};
+
+use askama::Template;
+use axum::{
+    extract::{Path, State},
+    response::Html,
+    Form,
+};
+use leprecon::{auth::AuthParam, template::Snackbar};
+use mongodb::bson::Document;
+use reqwest::StatusCode;
+use tracing::error;
+
+/// Favourite games of the user, most recently added first.
+pub(super) async fn favourites(
+    State(state): State<StateParams>,
+    Form(auth_param): Form<AuthParam>,
+) -> (StatusCode, Html<String>) {
+    let snackbar: Snackbar<'_> = Snackbar::default();
+
+    if auth_param.sub.is_empty() {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Html(snackbar.render().unwrap()),
+        );
+    };
+
+    let slugs: Vec<String> = match get_favourites(&auth_param.sub, state.0.clone()).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Could not get favourites: {:?}", e);
+            return (StatusCode::BAD_GATEWAY, Html(snackbar.render().unwrap()));
+        }
+    };
+
+    let country: Option<String> = country_code(&auth_param.sub, &state).await;
+
+    render_game_list("favourites-title", slugs, country, state.0).await
+}
+
+pub(super) async fn add_favourite(
+    State(state): State<StateParams>,
+    Path(slug): Path<String>,
+    Form(auth_param): Form<AuthParam>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    if auth_param.sub.is_empty() {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Html(snackbar.render().unwrap()),
+        );
+    };
+
+    match get_game_db(&slug, Document::new(), state.0.clone()).await {
+        Ok(Some(_)) => (),
+        Ok(None) => {
+            snackbar.message = "game-does-not-exist";
+            return (StatusCode::NOT_FOUND, Html(snackbar.render().unwrap()));
+        }
+        Err(e) => {
+            error!("Could not get game: {:?}", e);
+            return (StatusCode::BAD_GATEWAY, Html(snackbar.render().unwrap()));
+        }
+    }
+
+    if let Err(e) = insert_favourite(&auth_param.sub, &slug, state.0).await {
+        error!("Could not add favourite: {:?}", e);
+        return (StatusCode::BAD_GATEWAY, Html(snackbar.render().unwrap()));
+    }
+
+    snackbar.title = "success";
+    snackbar.message = "favourite-added";
+    snackbar.color = "green";
+
+    (StatusCode::CREATED, Html(snackbar.render().unwrap()))
+}
+
+pub(super) async fn remove_favourite(
+    State(state): State<StateParams>,
+    Path(slug): Path<String>,
+    Form(auth_param): Form<AuthParam>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    if auth_param.sub.is_empty() {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Html(snackbar.render().unwrap()),
+        );
+    };
+
+    match delete_favourite(&auth_param.sub, &slug, state.0).await {
+        Ok(true) => (),
+        Ok(false) => {
+            snackbar.message = "favourite-missing";
+            return (StatusCode::NOT_FOUND, Html(snackbar.render().unwrap()));
+        }
+        Err(e) => {
+            error!("Could not remove favourite: {:?}", e);
+            return (StatusCode::BAD_GATEWAY, Html(snackbar.render().unwrap()));
+        }
+    }
+
+    snackbar.title = "success";
+    snackbar.message = "favourite-removed";
+    snackbar.color = "green";
+
+    (StatusCode::OK, Html(snackbar.render().unwrap()))
+}
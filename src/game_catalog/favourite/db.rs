@@ -0,0 +1,86 @@
+use super::model::Favourite;
+
+use chrono::Utc;
+use futures::TryStreamExt;
+use mongodb::{
+    bson::doc,
+    options::{FindOptions, IndexOptions, UpdateOptions},
+    results::DeleteResult,
+    Cursor, IndexModel,
+};
+
+/// A game is a favourite of a user at most once.
+pub(crate) async fn init_favourites_db(
+    conn: &mongodb::Database,
+) -> Result<(), mongodb::error::Error> {
+    let collection: mongodb::Collection<Favourite> = conn.collection::<Favourite>("favourites");
+
+    let index: IndexModel = IndexModel::builder()
+        .keys(doc! { "sub": 1, "game": 1 })
+        .options(IndexOptions::builder().unique(true).build())
+        .build();
+    collection.create_index(index, None).await?;
+
+    Ok(())
+}
+
+/// Adds the favourite, adding it again keeps the original date.
+pub(super) async fn insert_favourite(
+    sub: &str,
+    game: &str,
+    conn: mongodb::Database,
+) -> Result<(), mongodb::error::Error> {
+    let collection: mongodb::Collection<Favourite> = conn.collection::<Favourite>("favourites");
+
+    collection
+        .update_one(
+            doc! { "sub": sub, "game": game },
+            doc! { "$setOnInsert": { "added_at": Utc::now() } },
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Removes the favourite, returns false when the game was not a favourite.
+pub(super) async fn delete_favourite(
+    sub: &str,
+    game: &str,
+    conn: mongodb::Database,
+) -> Result<bool, mongodb::error::Error> {
+    let collection: mongodb::Collection<Favourite> = conn.collection::<Favourite>("favourites");
+    let result: DeleteResult = collection
+        .delete_one(doc! { "sub": sub, "game": game }, None)
+        .await?;
+
+    Ok(result.deleted_count == 1)
+}
+
+/// Slugs of the favourites of the user, most recently added first.
+pub(super) async fn get_favourites(
+    sub: &str,
+    conn: mongodb::Database,
+) -> Result<Vec<String>, mongodb::error::Error> {
+    let collection: mongodb::Collection<Favourite> = conn.collection::<Favourite>("favourites");
+
+    let options: FindOptions = FindOptions::builder().sort(doc! { "added_at": -1 }).build();
+    let mut cursor: Cursor<Favourite> = collection.find(doc! { "sub": sub }, options).await?;
+    let mut favourites: Vec<String> = vec![];
+
+    while let Some(f) = cursor.try_next().await? {
+        favourites.push(f.game);
+    }
+
+    Ok(favourites)
+}
+
+pub(crate) async fn delete_user_favourites(
+    sub: &str,
+    conn: &mongodb::Database,
+) -> Result<(), mongodb::error::Error> {
+    let collection: mongodb::Collection<Favourite> = conn.collection::<Favourite>("favourites");
+    collection.delete_many(doc! { "sub": sub }, None).await?;
+
+    Ok(())
+}
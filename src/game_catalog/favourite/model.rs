@@ -0,0 +1,12 @@
+use chrono::{DateTime, Utc};
+use mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime;
+use serde::{Deserialize, Serialize};
+
+/// Game pinned by a user, as stored in the `favourites` collection.
+#[derive(Serialize, Deserialize, Debug)]
+pub(super) struct Favourite {
+    pub sub: String,
+    pub game: String,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub added_at: DateTime<Utc>,
+}
@@ -1,25 +1,128 @@
-use leprecon::template::Catalog;
+use chrono::{TimeZone, Utc};
+use leprecon::machine::Machine;
+use mongodb::{
+    bson::{doc, to_document},
+    options::UpdateOptions,
+};
+use std::collections::HashMap;
 
+use crate::catalog::model::{Game, GameCategory, GameTranslation, PlayStats};
+
+/// Adds the default games, existing games with the same slug are left as they are.
 pub async fn seed_db(client: &mongodb::Database) {
-    let coll: mongodb::Collection<Catalog> = client.collection::<Catalog>("catalog");
-    let catalog: Catalog = Catalog {
+    let coll: mongodb::Collection<Game> = client.collection::<Game>("catalog");
+    let options: UpdateOptions = UpdateOptions::builder().upsert(true).build();
+
+    let game: Game = Game {
+        id: None,
+        slug: "blackjack".to_owned(),
         name: "Blackjack".to_owned(),
         description: "This is blackjack!".to_owned(),
+        category: GameCategory::Card,
+        provider: "Leprecon".to_owned(),
+        rtp: 99.5,
+        min_bet: 1.0,
+        max_bet: 500.0,
+        currencies: vec!["EUR".to_owned()],
+        thumbnail_url: "/static/games/blackjack.png".to_owned(),
+        tags: vec!["classic".to_owned(), "cards".to_owned()],
+        enabled: true,
+        release_date: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+        allowed_countries: vec![],
+        blocked_countries: vec![],
+        translations: HashMap::from([(
+            "nl".to_owned(),
+            GameTranslation {
+                name: "Blackjack".to_owned(),
+                description: "Dit is blackjack!".to_owned(),
+            },
+        )]),
+        stats: PlayStats::default(),
+        machine: None,
     };
 
-    coll.insert_one(catalog, None).await.unwrap();
+    coll.update_one(
+        doc! { "slug": &game.slug },
+        doc! { "$setOnInsert": to_document(&game).unwrap() },
+        options.clone(),
+    )
+    .await
+    .unwrap();
 
-    let catalog: Catalog = Catalog {
+    let game: Game = Game {
+        id: None,
+        slug: "poker".to_owned(),
         name: "Poker".to_owned(),
         description: "This is poker!".to_owned(),
+        category: GameCategory::Card,
+        provider: "Leprecon".to_owned(),
+        rtp: 97.8,
+        min_bet: 1.0,
+        max_bet: 200.0,
+        currencies: vec!["EUR".to_owned()],
+        thumbnail_url: "/static/games/poker.png".to_owned(),
+        tags: vec!["classic".to_owned(), "cards".to_owned()],
+        enabled: true,
+        release_date: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+        allowed_countries: vec![],
+        blocked_countries: vec![],
+        translations: HashMap::from([(
+            "nl".to_owned(),
+            GameTranslation {
+                name: "Poker".to_owned(),
+                description: "Dit is poker!".to_owned(),
+            },
+        )]),
+        stats: PlayStats::default(),
+        machine: None,
     };
 
-    coll.insert_one(catalog, None).await.unwrap();
+    coll.update_one(
+        doc! { "slug": &game.slug },
+        doc! { "$setOnInsert": to_document(&game).unwrap() },
+        options.clone(),
+    )
+    .await
+    .unwrap();
 
-    let catalog: Catalog = Catalog {
+    let game: Game = Game {
+        id: None,
+        slug: "slots".to_owned(),
         name: "Slots".to_owned(),
         description: "This is slots!".to_owned(),
+        category: GameCategory::Slots,
+        provider: "Leprecon".to_owned(),
+        rtp: 96.0,
+        min_bet: 0.1,
+        max_bet: 100.0,
+        currencies: vec!["EUR".to_owned()],
+        thumbnail_url: "/static/games/slots.png".to_owned(),
+        tags: vec!["reels".to_owned()],
+        enabled: true,
+        release_date: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+        allowed_countries: vec![],
+        blocked_countries: vec![],
+        translations: HashMap::from([(
+            "nl".to_owned(),
+            GameTranslation {
+                name: "Slots".to_owned(),
+                description: "Dit is slots!".to_owned(),
+            },
+        )]),
+        stats: PlayStats::default(),
+        machine: Some(slots_machine()),
     };
 
-    coll.insert_one(catalog, None).await.unwrap();
+    coll.update_one(
+        doc! { "slug": &game.slug },
+        doc! { "$setOnInsert": to_document(&game).unwrap() },
+        options.clone(),
+    )
+    .await
+    .unwrap();
+}
+
+/// Five reels of three rows with ten paylines, returns 96% over all stops.
+fn slots_machine() -> Machine {
+    serde_json::from_str(include_str!("machines/slots.json")).unwrap()
 }
@@ -0,0 +1,68 @@
+mod db;
+
+use self::db::{cache_country, get_cached_country};
+
+use crate::{StateParams, ACCOUNT_URL, JURISDICTION_CACHE_SECONDS, SERVICE_TOKEN};
+
+use leprecon::{
+    jurisdiction::{get_jurisdiction, Jurisdiction},
+    utils::RedisConn,
+};
+use tracing::error;
+
+/// Country of the user, cached in Valkey.
+///
+/// Unknown for anonymous users and when the account service cannot tell, which only shows games
+/// without country restrictions.
+pub(crate) async fn country_code(sub: &str, state: &StateParams) -> Option<String> {
+    if sub.is_empty() {
+        return None;
+    }
+
+    let mut redis_conn: Option<RedisConn> = match state.1.get().await {
+        Ok(v) => Some(v),
+        Err(e) => {
+            error!("Cannot get connection from pool: {:?}", e);
+            None
+        }
+    };
+
+    if let Some(conn) = redis_conn.as_mut() {
+        match get_cached_country(sub, conn).await {
+            Ok(Some(v)) => return Some(v).filter(|v| !v.is_empty()),
+            Ok(None) => (),
+            Err(e) => error!("Could not get cached country: {:?}", e),
+        }
+    }
+
+    let jurisdiction: Jurisdiction = match get_jurisdiction(
+        &state.3,
+        ACCOUNT_URL.get().unwrap(),
+        SERVICE_TOKEN.get().unwrap(),
+        sub,
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Could not get jurisdiction of {}: {:?}", sub, e);
+            return None;
+        }
+    };
+
+    if let Some(conn) = redis_conn.as_mut() {
+        let country_code: &str = jurisdiction.country_code.as_deref().unwrap_or_default();
+        if let Err(e) = cache_country(
+            sub,
+            country_code,
+            *JURISDICTION_CACHE_SECONDS.get().unwrap(),
+            conn,
+        )
+        .await
+        {
+            error!("Could not cache country: {:?}", e);
+        }
+    }
+
+    jurisdiction.country_code
+}
@@ -0,0 +1,20 @@
+use leprecon::utils::RedisConn;
+use redis::{AsyncCommands, RedisResult};
+
+/// Cached country of the user, an empty string when the user has no country.
+pub(super) async fn get_cached_country(
+    sub: &str,
+    conn: &mut RedisConn<'_>,
+) -> RedisResult<Option<String>> {
+    conn.get(format!("jurisdiction:{sub}")).await
+}
+
+pub(super) async fn cache_country(
+    sub: &str,
+    country_code: &str,
+    expires: u64,
+    conn: &mut RedisConn<'_>,
+) -> RedisResult<()> {
+    conn.set_ex(format!("jurisdiction:{sub}"), country_code, expires)
+        .await
+}
@@ -1,14 +1,49 @@
+mod admin;
+mod cache;
 mod catalog;
+mod deletion;
+mod favourite;
 mod fixture;
+mod jurisdiction;
+mod popularity;
+mod recent;
 
-use axum::{serve, Router};
-use catalog::get_catalog;
+use admin::{
+    create_game, enable_game, remove_game, remove_translation, set_countries, set_machine,
+    set_translation, update_game,
+};
+use axum::{middleware, serve, Router};
+use bb8_redis::{bb8::Pool, RedisConnectionManager};
+use cache::{subscribe_invalidations, MemoryCache};
+use catalog::{db::init_catalog_db, get_catalog, get_game};
+use deletion::{consume_account_deleted, init_deleted_accounts_db};
+use favourite::{add_favourite, favourites, init_favourites_db, remove_favourite};
 use fixture::seed_db;
-use leprecon::{signals::shutdown_signal, utils::configure_tracing};
+use leprecon::{
+    broker::init_broker,
+    i18n::resolve_locale,
+    signals::shutdown_signal,
+    utils::{configure_tracing, create_conn_pool},
+};
 use mongodb::options::ClientOptions;
-use std::{env, error::Error, sync::OnceLock};
-use tokio::net::TcpListener;
-use tracing::info;
+use popularity::{aggregate_play_stats, consume_play_started, init_plays_db, PLAY_WINDOW_DAYS};
+use rabbitmq_stream_client::types::{ByteCapacity, OffsetSpecification};
+use recent::{init_recently_played_db, recently_played};
+use std::{
+    env,
+    error::Error,
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+use tokio::{net::TcpListener, task};
+use tracing::{error, info};
+
+type StateParams = (
+    mongodb::Database,
+    Pool<RedisConnectionManager>,
+    Arc<MemoryCache>,
+    reqwest::Client,
+);
 
 // Host variables
 static HOST: OnceLock<String> = OnceLock::new();
@@ -17,6 +52,22 @@ static LOG_LEVEL: OnceLock<String> = OnceLock::new();
 // Mongo
 static GAME_CATALOG_CONN: OnceLock<String> = OnceLock::new();
 static GAME_CATALOG_DB: OnceLock<String> = OnceLock::new();
+static GAME_CATALOG_SEED: OnceLock<bool> = OnceLock::new();
+
+// Service variables
+static ACCOUNT_URL: OnceLock<String> = OnceLock::new();
+static SERVICE_TOKEN: OnceLock<String> = OnceLock::new();
+static JURISDICTION_CACHE_SECONDS: OnceLock<u64> = OnceLock::new();
+
+// Valkey
+static VALKEY_CONN: OnceLock<String> = OnceLock::new();
+static CATALOG_CACHE_SECONDS: OnceLock<u64> = OnceLock::new();
+
+// Admin
+static CATALOG_ADMIN_TOKEN: OnceLock<String> = OnceLock::new();
+
+// Popularity
+static POPULARITY_INTERVAL_SECONDS: OnceLock<u64> = OnceLock::new();
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
@@ -32,11 +83,90 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let mongo_client: mongodb::Client = mongodb::Client::with_options(client_options).unwrap();
     let mongo_db: mongodb::Database = mongo_client.database(GAME_CATALOG_DB.get().unwrap());
 
+    // Unique slugs
+    init_catalog_db(&mongo_db).await?;
+
+    // Plays older than the longest window expire
+    init_plays_db(
+        &mongo_db,
+        Duration::from_secs(PLAY_WINDOW_DAYS as u64 * 24 * 60 * 60),
+    )
+    .await?;
+
+    // Lists of the user
+    init_favourites_db(&mongo_db).await?;
+    init_recently_played_db(&mongo_db).await?;
+
+    // Deleted accounts are remembered as long as their plays can be replayed
+    init_deleted_accounts_db(
+        &mongo_db,
+        Duration::from_secs((PLAY_WINDOW_DAYS as u64 + 1) * 24 * 60 * 60),
+    )
+    .await?;
+
     // Seed database
-    seed_db(&mongo_db).await;
+    if *GAME_CATALOG_SEED.get().unwrap() {
+        seed_db(&mongo_db).await;
+    }
+
+    // Valkey
+    let redis_manager: RedisConnectionManager =
+        RedisConnectionManager::new(VALKEY_CONN.get().unwrap().to_owned()).unwrap();
+    let redis_pool: Pool<RedisConnectionManager> =
+        create_conn_pool(redis_manager, Duration::from_secs(10), 20).await?;
+
+    // Drop cached responses when any replica changes the catalog
+    let cache: Arc<MemoryCache> = Arc::new(MemoryCache::default());
+    task::spawn(subscribe_invalidations(cache.clone()));
+
+    // Initialize broker environment
+    let environment = init_broker().await;
+    let play_stream = "game_play";
+    let create_response = environment
+        .stream_creator()
+        .max_length(ByteCapacity::GB(5))
+        .create(play_stream)
+        .await;
+
+    if let Err(e) = create_response {
+        error!("Error creating stream: {:?} {:?}", play_stream, e);
+    }
+
+    // Only plays within the longest window still count
+    let play_window_start: i64 =
+        (chrono::Utc::now() - chrono::Duration::days(PLAY_WINDOW_DAYS)).timestamp_millis();
+    let play_consumer = environment
+        .consumer()
+        .offset(OffsetSpecification::Timestamp(play_window_start))
+        .build(play_stream)
+        .await?;
+
+    let deleted_stream = "account_deleted";
+    let create_response = environment
+        .stream_creator()
+        .max_length(ByteCapacity::GB(5))
+        .create(deleted_stream)
+        .await;
+
+    if let Err(e) = create_response {
+        error!("Error creating stream: {:?} {:?}", deleted_stream, e);
+    }
+
+    let deleted_consumer = environment
+        .consumer()
+        .offset(OffsetSpecification::First)
+        .build(deleted_stream)
+        .await?;
+
+    // Popularity and recently played
+    task::spawn(consume_play_started(play_consumer, mongo_db.clone()));
+    task::spawn(aggregate_play_stats(mongo_db.clone(), redis_pool.clone()));
+
+    // Purge deleted accounts
+    task::spawn(consume_account_deleted(deleted_consumer, mongo_db.clone()));
 
     // Build application and listen to incoming requests.
-    let app: Router = build_app(mongo_db);
+    let app: Router = build_app((mongo_db, redis_pool, cache, reqwest::Client::new()));
     let listener: TcpListener = TcpListener::bind(HOST.get().unwrap()).await?;
 
     info!("Running application");
@@ -56,12 +186,63 @@ fn init_env() {
 
     GAME_CATALOG_CONN.get_or_init(|| env::var("GAME_CATALOG_CONN").unwrap());
     GAME_CATALOG_DB.get_or_init(|| env::var("GAME_CATALOG_DB").unwrap());
+    GAME_CATALOG_SEED.get_or_init(|| env::var("GAME_CATALOG_SEED").unwrap().parse().unwrap());
+
+    ACCOUNT_URL.get_or_init(|| env::var("ACCOUNT_URL").unwrap());
+    SERVICE_TOKEN.get_or_init(|| env::var("SERVICE_TOKEN").unwrap());
+    JURISDICTION_CACHE_SECONDS.get_or_init(|| {
+        env::var("JURISDICTION_CACHE_SECONDS")
+            .unwrap()
+            .parse()
+            .unwrap()
+    });
+
+    VALKEY_CONN.get_or_init(|| env::var("VALKEY_CONN").unwrap());
+    CATALOG_CACHE_SECONDS
+        .get_or_init(|| env::var("CATALOG_CACHE_SECONDS").unwrap().parse().unwrap());
+
+    CATALOG_ADMIN_TOKEN.get_or_init(|| env::var("CATALOG_ADMIN_TOKEN").unwrap());
+
+    POPULARITY_INTERVAL_SECONDS.get_or_init(|| {
+        env::var("POPULARITY_INTERVAL_SECONDS")
+            .unwrap()
+            .parse()
+            .unwrap()
+    });
 }
 
 /// Builds the application.
-fn build_app(mongo_db: mongodb::Database) -> Router {
-    Router::new().route(
-        "/game/catalog",
-        axum::routing::get(get_catalog).with_state(mongo_db),
-    )
+fn build_app(state: StateParams) -> Router {
+    Router::new()
+        .route("/game/catalog", axum::routing::get(get_catalog))
+        .route("/game/catalog/favourites", axum::routing::get(favourites))
+        .route("/game/catalog/recent", axum::routing::get(recently_played))
+        .route("/game/catalog/:slug", axum::routing::get(get_game))
+        .route(
+            "/game/catalog/:slug/favourite",
+            axum::routing::post(add_favourite).delete(remove_favourite),
+        )
+        .route("/game/admin/catalog", axum::routing::post(create_game))
+        .route(
+            "/game/admin/catalog/:slug",
+            axum::routing::put(update_game).delete(remove_game),
+        )
+        .route(
+            "/game/admin/catalog/:slug/enabled",
+            axum::routing::put(enable_game),
+        )
+        .route(
+            "/game/admin/catalog/:slug/countries",
+            axum::routing::put(set_countries),
+        )
+        .route(
+            "/game/admin/catalog/:slug/translations/:locale",
+            axum::routing::put(set_translation).delete(remove_translation),
+        )
+        .route(
+            "/game/admin/catalog/:slug/machine",
+            axum::routing::put(set_machine),
+        )
+        .with_state(state)
+        .layer(middleware::from_fn(resolve_locale))
 }
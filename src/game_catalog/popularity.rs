@@ -0,0 +1,131 @@
+mod db;
+mod model;
+
+pub(crate) use db::init_plays_db;
+
+use self::{
+    db::{count_plays, insert_play, reset_play_stats, set_play_stats},
+    model::{Play, PlayCount},
+};
+
+use crate::{
+    cache::invalidate_catalog, catalog::model::PlayStats, deletion::is_deleted_account,
+    recent::upsert_recent_play, POPULARITY_INTERVAL_SECONDS,
+};
+
+use bb8_redis::{bb8::Pool, RedisConnectionManager};
+use chrono::{DateTime, Duration, Utc};
+use futures::StreamExt;
+use leprecon::broker::GamePlayStarted;
+use rabbitmq_stream_client::Consumer;
+use tracing::{debug, error, info};
+
+/// Longest window plays are counted over, older plays are of no use.
+pub(crate) const PLAY_WINDOW_DAYS: i64 = 7;
+
+/// Records plays started in the game services, to be counted by [`aggregate_play_stats`] and
+/// listed as recently played.
+pub(super) async fn consume_play_started(mut consumer: Consumer, mongo_db: mongodb::Database) {
+    while let Some(delivery) = consumer.next().await {
+        let d = match delivery {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Could not receive play: {:?}", e);
+                continue;
+            }
+        };
+
+        let event: GamePlayStarted = match d
+            .message()
+            .data()
+            .map(serde_json::from_slice::<GamePlayStarted>)
+        {
+            Some(Ok(v)) => v,
+            Some(Err(e)) => {
+                debug!("Skipping malformed play at {}: {:?}", d.offset(), e);
+                continue;
+            }
+            None => continue,
+        };
+
+        // Plays are replayed on restart, also those of accounts purged since
+        match is_deleted_account(&event.sub, &mongo_db).await {
+            Ok(true) => debug!("Not recording recent play of deleted account {}", event.sub),
+            Ok(false) => {
+                if let Err(e) =
+                    upsert_recent_play(&event.sub, &event.game, event.started_at, &mongo_db).await
+                {
+                    error!("Could not record recent play {:?}: {:?}", event, e);
+                }
+            }
+            Err(e) => error!("Could not check deleted account {:?}: {:?}", event, e),
+        }
+
+        let play: Play = Play {
+            session: event.session,
+            game: event.game,
+            started_at: event.started_at,
+        };
+
+        if let Err(e) = insert_play(&play, &mongo_db).await {
+            error!("Could not record play {:?}: {:?}", play, e);
+        }
+    }
+}
+
+/// Periodically stores the plays over the rolling windows on the games.
+pub(super) async fn aggregate_play_stats(
+    mongo_db: mongodb::Database,
+    redis_pool: Pool<RedisConnectionManager>,
+) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+        *POPULARITY_INTERVAL_SECONDS.get().unwrap(),
+    ));
+
+    loop {
+        interval.tick().await;
+
+        // Every replica aggregates, only changes should flush the shared cache
+        match update_play_stats(&mongo_db).await {
+            Ok(0) => debug!("Play statistics unchanged"),
+            Ok(changed) => {
+                info!("Updated play statistics of {} games", changed);
+                invalidate_catalog(&redis_pool).await;
+            }
+            Err(e) => error!("Could not update play statistics: {:?}", e),
+        }
+    }
+}
+
+/// Returns how many games got different statistics.
+async fn update_play_stats(mongo_db: &mongodb::Database) -> Result<u64, mongodb::error::Error> {
+    let now: DateTime<Utc> = Utc::now();
+    let counts: Vec<PlayCount> = count_plays(
+        now - Duration::days(1),
+        now - Duration::days(PLAY_WINDOW_DAYS),
+        mongo_db,
+    )
+    .await?;
+
+    let mut changed: u64 = 0;
+    for count in &counts {
+        if set_play_stats(&count.game, &play_stats(count), mongo_db).await? {
+            changed += 1;
+        }
+    }
+
+    let played: Vec<String> = counts.into_iter().map(|c| c.game).collect();
+    changed += reset_play_stats(&played, mongo_db).await?;
+
+    Ok(changed)
+}
+
+fn play_stats(count: &PlayCount) -> PlayStats {
+    let daily_average: f64 = count.week as f64 / PLAY_WINDOW_DAYS as f64;
+
+    PlayStats {
+        day: count.day,
+        week: count.week,
+        trending: count.day as f64 / daily_average.max(1.0),
+    }
+}
@@ -0,0 +1,119 @@
+use super::model::{Play, PlayCount};
+
+use crate::catalog::model::{Game, PlayStats};
+
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, from_document, to_bson, Document},
+    error::{ErrorKind, WriteFailure},
+    options::{IndexOptions, UpdateOptions},
+    results::UpdateResult,
+    Cursor, IndexModel,
+};
+use std::time::Duration;
+
+/// Plays are removed by Mongo once they are older than the longest window.
+pub(crate) async fn init_plays_db(
+    conn: &mongodb::Database,
+    expires: Duration,
+) -> Result<(), mongodb::error::Error> {
+    let collection: mongodb::Collection<Play> = conn.collection::<Play>("plays");
+
+    let index: IndexModel = IndexModel::builder()
+        .keys(doc! { "started_at": 1 })
+        .options(IndexOptions::builder().expire_after(expires).build())
+        .build();
+    collection.create_index(index, None).await?;
+
+    Ok(())
+}
+
+/// Stores the play, a play that was already stored is ignored.
+pub(super) async fn insert_play(
+    play: &Play,
+    conn: &mongodb::Database,
+) -> Result<(), mongodb::error::Error> {
+    let collection: mongodb::Collection<Play> = conn.collection::<Play>("plays");
+
+    match collection.insert_one(play, None).await {
+        Ok(_) => Ok(()),
+        Err(e) if is_duplicate(&e) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Plays per game since the start of the week window, and how many of those since the start of
+/// the day window.
+pub(super) async fn count_plays(
+    day: DateTime<Utc>,
+    week: DateTime<Utc>,
+    conn: &mongodb::Database,
+) -> Result<Vec<PlayCount>, mongodb::error::Error> {
+    let collection: mongodb::Collection<Play> = conn.collection::<Play>("plays");
+
+    let pipeline: Vec<Document> = vec![
+        doc! { "$match": { "started_at": { "$gte": week } } },
+        doc! {
+            "$group": {
+                "_id": "$game",
+                "week": { "$sum": 1_i64 },
+                "day": { "$sum": { "$cond": [{ "$gte": ["$started_at", day] }, 1_i64, 0_i64] } },
+            }
+        },
+    ];
+
+    let mut cursor: Cursor<Document> = collection.aggregate(pipeline, None).await?;
+    let mut counts: Vec<PlayCount> = vec![];
+
+    while let Some(d) = cursor.try_next().await? {
+        counts.push(from_document(d)?);
+    }
+
+    Ok(counts)
+}
+
+/// Returns whether the statistics of the game changed.
+pub(super) async fn set_play_stats(
+    game: &str,
+    stats: &PlayStats,
+    conn: &mongodb::Database,
+) -> Result<bool, mongodb::error::Error> {
+    let collection: mongodb::Collection<Game> = conn.collection::<Game>("catalog");
+
+    let result: UpdateResult = collection
+        .update_one(
+            doc! { "slug": game },
+            doc! { "$set": { "stats": to_bson(stats)? } },
+            UpdateOptions::default(),
+        )
+        .await?;
+
+    Ok(result.modified_count > 0)
+}
+
+/// Resets the statistics of games which were not played within the windows, returns how many
+/// were reset.
+pub(super) async fn reset_play_stats(
+    played: &[String],
+    conn: &mongodb::Database,
+) -> Result<u64, mongodb::error::Error> {
+    let collection: mongodb::Collection<Game> = conn.collection::<Game>("catalog");
+
+    let result: UpdateResult = collection
+        .update_many(
+            doc! { "slug": { "$nin": played }, "stats": { "$ne": to_bson(&PlayStats::default())? } },
+            doc! { "$set": { "stats": to_bson(&PlayStats::default())? } },
+            UpdateOptions::default(),
+        )
+        .await?;
+
+    Ok(result.modified_count)
+}
+
+fn is_duplicate(e: &mongodb::error::Error) -> bool {
+    matches!(
+        e.kind.as_ref(),
+        ErrorKind::Write(WriteFailure::WriteError(v)) if v.code == 11000
+    )
+}
@@ -0,0 +1,22 @@
+use chrono::{DateTime, Utc};
+use mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime;
+use serde::{Deserialize, Serialize};
+
+/// Play as stored in the `plays` collection, until it falls out of the longest window.
+#[derive(Serialize, Deserialize, Debug)]
+pub(super) struct Play {
+    #[serde(rename = "_id")]
+    pub session: String,
+    pub game: String,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub started_at: DateTime<Utc>,
+}
+
+/// Plays of a game over the windows, as aggregated from the `plays` collection.
+#[derive(Deserialize, Debug)]
+pub(super) struct PlayCount {
+    #[serde(rename = "_id")]
+    pub game: String,
+    pub day: i64,
+    pub week: i64,
+}
@@ -0,0 +1,44 @@
+mod db;
+mod model;
+
+pub(crate) use db::{delete_user_recently_played, init_recently_played_db, upsert_recent_play};
+
+use self::db::get_recently_played;
+
+use crate::{catalog::render_game_list, jurisdiction::country_code, StateParams};
+
+use askama::Template;
+use axum::{extract::State, response::Html, Form};
+use leprecon::{auth::AuthParam, template::Snackbar};
+use reqwest::StatusCode;
+use tracing::error;
+
+const RECENTLY_PLAYED_LIMIT: i64 = 10;
+
+/// Games the user played last, fed by the play events of the game services.
+pub(super) async fn recently_played(
+    State(state): State<StateParams>,
+    Form(auth_param): Form<AuthParam>,
+) -> (StatusCode, Html<String>) {
+    let snackbar: Snackbar<'_> = Snackbar::default();
+
+    if auth_param.sub.is_empty() {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Html(snackbar.render().unwrap()),
+        );
+    };
+
+    let slugs: Vec<String> =
+        match get_recently_played(&auth_param.sub, RECENTLY_PLAYED_LIMIT, state.0.clone()).await {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Could not get recently played games: {:?}", e);
+                return (StatusCode::BAD_GATEWAY, Html(snackbar.render().unwrap()));
+            }
+        };
+
+    let country: Option<String> = country_code(&auth_param.sub, &state).await;
+
+    render_game_list("recent-title", slugs, country, state.0).await
+}
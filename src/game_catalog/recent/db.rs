@@ -0,0 +1,85 @@
+use super::model::RecentPlay;
+
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::doc,
+    options::{FindOptions, IndexOptions, UpdateOptions},
+    Cursor, IndexModel,
+};
+
+/// One entry per game and user, listed newest first.
+pub(crate) async fn init_recently_played_db(
+    conn: &mongodb::Database,
+) -> Result<(), mongodb::error::Error> {
+    let collection: mongodb::Collection<RecentPlay> =
+        conn.collection::<RecentPlay>("recently_played");
+
+    let index: IndexModel = IndexModel::builder()
+        .keys(doc! { "sub": 1, "game": 1 })
+        .options(IndexOptions::builder().unique(true).build())
+        .build();
+    collection.create_index(index, None).await?;
+
+    let index: IndexModel = IndexModel::builder()
+        .keys(doc! { "sub": 1, "played_at": -1 })
+        .build();
+    collection.create_index(index, None).await?;
+
+    Ok(())
+}
+
+/// Moves the game to the front of the list, replayed events never move it back.
+pub(crate) async fn upsert_recent_play(
+    sub: &str,
+    game: &str,
+    played_at: DateTime<Utc>,
+    conn: &mongodb::Database,
+) -> Result<(), mongodb::error::Error> {
+    let collection: mongodb::Collection<RecentPlay> =
+        conn.collection::<RecentPlay>("recently_played");
+
+    collection
+        .update_one(
+            doc! { "sub": sub, "game": game },
+            doc! { "$max": { "played_at": played_at } },
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Slugs of the games the user played last, newest first.
+pub(super) async fn get_recently_played(
+    sub: &str,
+    limit: i64,
+    conn: mongodb::Database,
+) -> Result<Vec<String>, mongodb::error::Error> {
+    let collection: mongodb::Collection<RecentPlay> =
+        conn.collection::<RecentPlay>("recently_played");
+
+    let options: FindOptions = FindOptions::builder()
+        .sort(doc! { "played_at": -1 })
+        .limit(limit)
+        .build();
+    let mut cursor: Cursor<RecentPlay> = collection.find(doc! { "sub": sub }, options).await?;
+    let mut games: Vec<String> = vec![];
+
+    while let Some(p) = cursor.try_next().await? {
+        games.push(p.game);
+    }
+
+    Ok(games)
+}
+
+pub(crate) async fn delete_user_recently_played(
+    sub: &str,
+    conn: &mongodb::Database,
+) -> Result<(), mongodb::error::Error> {
+    let collection: mongodb::Collection<RecentPlay> =
+        conn.collection::<RecentPlay>("recently_played");
+    collection.delete_many(doc! { "sub": sub }, None).await?;
+
+    Ok(())
+}
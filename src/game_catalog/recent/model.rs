@@ -0,0 +1,12 @@
+use chrono::{DateTime, Utc};
+use mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime;
+use serde::{Deserialize, Serialize};
+
+/// Last play of a game by a user, as stored in the `recently_played` collection.
+#[derive(Serialize, Deserialize, Debug)]
+pub(super) struct RecentPlay {
+    pub sub: String,
+    pub game: String,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub played_at: DateTime<Utc>,
+}
@@ -0,0 +1,152 @@
+mod model;
+
+pub use model::*;
+
+use axum::{
+    extract::Request,
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::Response,
+};
+pub use fluent_bundle::FluentArgs;
+
+use fluent_bundle::{concurrent::FluentBundle, FluentResource};
+use std::{collections::HashMap, str::FromStr, sync::OnceLock};
+
+/// Cookie with the locale chosen by the user, set by the account service.
+pub const LOCALE_COOKIE: &str = "locale";
+
+static BUNDLES: OnceLock<HashMap<Locale, FluentBundle<FluentResource>>> = OnceLock::new();
+
+tokio::task_local! {
+    static LOCALE: Locale;
+}
+
+/// Runs the request in the locale of the user, so snackbars and templates are translated.
+///
+/// The locale chosen by the user wins over `Accept-Language`, English is the default.
+pub async fn resolve_locale(req: Request, next: Next) -> Response {
+    let locale: Locale = request_locale(req.headers());
+
+    LOCALE.scope(locale, next.run(req)).await
+}
+
+pub fn request_locale(headers: &HeaderMap) -> Locale {
+    let preference: Option<Locale> = headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|v| v.trim().split_once('='))
+        .find(|(name, _)| *name == LOCALE_COOKIE)
+        .and_then(|(_, value)| Locale::from_str(value).ok());
+
+    preference
+        .or_else(|| {
+            headers
+                .get(header::ACCEPT_LANGUAGE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(Locale::from_accept_language)
+        })
+        .unwrap_or_default()
+}
+
+/// `Set-Cookie` value which remembers the locale in the browser, for every service.
+pub fn locale_cookie(locale: Locale) -> String {
+    format!("{LOCALE_COOKIE}={locale}; Path=/; Max-Age=31536000; SameSite=Lax")
+}
+
+/// Renders in the given locale, such as the locale the user just chose.
+pub fn in_locale<R>(locale: Locale, f: impl FnOnce() -> R) -> R {
+    LOCALE.sync_scope(locale, f)
+}
+
+/// Locale of the request being handled, English outside of requests.
+pub fn current_locale() -> Locale {
+    LOCALE.try_with(|v| *v).unwrap_or_default()
+}
+
+/// Message in the locale of the request.
+///
+/// Text which is not a message id, such as validation errors, is returned as it is.
+pub fn translate(id: &str) -> String {
+    translate_in(current_locale(), id, None)
+}
+
+pub fn translate_with(id: &str, args: &FluentArgs) -> String {
+    translate_in(current_locale(), id, Some(args))
+}
+
+pub fn translate_in(locale: Locale, id: &str, args: Option<&FluentArgs>) -> String {
+    let bundles: &HashMap<Locale, FluentBundle<FluentResource>> = BUNDLES.get_or_init(|| {
+        Locale::ALL
+            .iter()
+            .map(|locale| (*locale, bundle(*locale)))
+            .collect()
+    });
+
+    for locale in [locale, Locale::default()] {
+        let bundle: &FluentBundle<FluentResource> = &bundles[&locale];
+
+        if let Some(pattern) = bundle.get_message(id).and_then(|m| m.value()) {
+            let mut errors = vec![];
+            return bundle
+                .format_pattern(pattern, args, &mut errors)
+                .into_owned();
+        }
+    }
+
+    id.to_owned()
+}
+
+fn bundle(locale: Locale) -> FluentBundle<FluentResource> {
+    let mut bundle: FluentBundle<FluentResource> =
+        FluentBundle::new_concurrent(vec![locale.language_id()]);
+
+    // Unicode isolation marks end up in html attributes otherwise
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(locale.resource())
+        .expect("Duplicate translations");
+
+    bundle
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use axum::http::HeaderValue;
+
+    fn headers(cookie: Option<&'static str>, accept_language: Option<&'static str>) -> HeaderMap {
+        let mut headers: HeaderMap = HeaderMap::new();
+        if let Some(v) = cookie {
+            headers.insert(header::COOKIE, HeaderValue::from_static(v));
+        }
+        if let Some(v) = accept_language {
+            headers.insert(header::ACCEPT_LANGUAGE, HeaderValue::from_static(v));
+        }
+
+        headers
+    }
+
+    #[test]
+    fn test_cookie_wins_over_accept_language() {
+        let headers: HeaderMap = headers(Some("session=abc; locale=nl"), Some("en"));
+
+        assert_eq!(request_locale(&headers), Locale::Nl);
+    }
+
+    #[test]
+    fn test_unsupported_cookie_falls_back() {
+        let headers: HeaderMap = headers(Some("locale=fr"), Some("nl-BE, en;q=0.5"));
+
+        assert_eq!(request_locale(&headers), Locale::Nl);
+    }
+
+    #[test]
+    fn test_default_locale() {
+        assert_eq!(request_locale(&headers(None, None)), Locale::En);
+        assert_eq!(request_locale(&headers(None, Some("de, fr"))), Locale::En);
+    }
+}
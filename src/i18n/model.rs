@@ -0,0 +1,136 @@
+use fluent_bundle::FluentResource;
+use serde::{Deserialize, Serialize};
+use std::{error::Error, fmt, str::FromStr};
+use unic_langid::LanguageIdentifier;
+
+/// Languages the interface is translated in, English is the fallback for missing messages.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Locale {
+    #[default]
+    En,
+    Nl,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Nl];
+
+    /// Language tag, as used in `Accept-Language` and the locale cookie.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Nl => "nl",
+        }
+    }
+
+    /// Preferred supported locale of an `Accept-Language` header.
+    pub fn from_accept_language(header: &str) -> Option<Locale> {
+        let mut ranges: Vec<(f32, Locale)> = header
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let locale: Locale = Locale::from_str(parts.next()?.trim()).ok()?;
+                let quality: f32 = parts
+                    .find_map(|p| p.trim().strip_prefix("q="))
+                    .map_or(Some(1.0), |q| q.parse().ok())?;
+
+                Some((quality, locale))
+            })
+            .filter(|(quality, _)| *quality > 0.0)
+            .collect();
+
+        // Stable, so equally preferred languages keep their order
+        ranges.sort_by(|a, b| b.0.total_cmp(&a.0));
+        ranges.first().map(|(_, locale)| *locale)
+    }
+
+    pub(super) fn language_id(&self) -> LanguageIdentifier {
+        self.code().parse().unwrap()
+    }
+
+    pub(super) fn resource(&self) -> FluentResource {
+        let source: &str = match self {
+            Locale::En => include_str!("../../locales/en.ftl"),
+            Locale::Nl => include_str!("../../locales/nl.ftl"),
+        };
+
+        FluentResource::try_new(source.to_owned()).expect("Invalid translations")
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseLocaleError;
+
+impl Error for ParseLocaleError {}
+
+impl fmt::Display for ParseLocaleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ParseLocaleError")
+    }
+}
+
+impl FromStr for Locale {
+    type Err = ParseLocaleError;
+
+    /// Only the language is used, `nl-BE` is Dutch.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let language: &str = s.split(['-', '_']).next().unwrap_or_default();
+
+        match language.to_lowercase().as_str() {
+            "en" => Ok(Locale::En),
+            "nl" => Ok(Locale::Nl),
+            _ => Err(ParseLocaleError),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_quality_order() {
+        assert_eq!(
+            Locale::from_accept_language("en;q=0.5, nl;q=0.8"),
+            Some(Locale::Nl)
+        );
+        assert_eq!(Locale::from_accept_language("nl, en"), Some(Locale::Nl));
+        assert_eq!(
+            Locale::from_accept_language("de, en;q=0.9, nl;q=0.9"),
+            Some(Locale::En)
+        );
+    }
+
+    #[test]
+    fn test_quality_zero_excluded() {
+        assert_eq!(
+            Locale::from_accept_language("nl;q=0, en;q=0.1"),
+            Some(Locale::En)
+        );
+        assert_eq!(Locale::from_accept_language("nl;q=0"), None);
+    }
+
+    #[test]
+    fn test_malformed_quality_skipped() {
+        assert_eq!(
+            Locale::from_accept_language("nl;q=abc, en;q=0.1"),
+            Some(Locale::En)
+        );
+    }
+
+    #[test]
+    fn test_region_tags() {
+        assert_eq!(Locale::from_accept_language("nl-BE"), Some(Locale::Nl));
+        assert_eq!(
+            Locale::from_accept_language("fr-BE, en-GB;q=0.7"),
+            Some(Locale::En)
+        );
+        assert_eq!(Locale::from_str("nl_NL").unwrap(), Locale::Nl);
+        assert!(Locale::from_str("fr-BE").is_err());
+    }
+}
@@ -0,0 +1,5 @@
+mod model;
+mod request;
+
+pub use model::*;
+pub use request::get_jurisdiction;
@@ -0,0 +1,24 @@
+use mongodb::bson::{doc, Document};
+use serde::{Deserialize, Serialize};
+
+/// Country the user plays from, as far as the account service knows.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct Jurisdiction {
+    pub country_code: Option<String>,
+}
+
+/// Games offered in the country, games with any restriction are hidden when the country is unknown.
+pub fn jurisdiction_filter(country_code: Option<&str>) -> Document {
+    match country_code {
+        Some(v) => doc! {
+            "$and": [
+                { "$or": [{ "allowed_countries.0": { "$exists": false } }, { "allowed_countries": v }] },
+                { "blocked_countries": { "$ne": v } },
+            ]
+        },
+        None => doc! {
+            "allowed_countries.0": { "$exists": false },
+            "blocked_countries.0": { "$exists": false },
+        },
+    }
+}
@@ -0,0 +1,19 @@
+use super::Jurisdiction;
+
+/// Asks the account service for the country of the user.
+pub async fn get_jurisdiction(
+    req_client: &reqwest::Client,
+    account_url: &str,
+    service_token: &str,
+    sub: &str,
+) -> Result<Jurisdiction, reqwest::Error> {
+    req_client
+        .get(format!("{account_url}/account/user/jurisdiction"))
+        .bearer_auth(service_token)
+        .query(&[("sub", sub)])
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Jurisdiction>()
+        .await
+}
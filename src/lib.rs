@@ -1,5 +1,11 @@
 pub mod auth;
 pub mod broker;
+pub mod i18n;
+pub mod jurisdiction;
+pub mod limit;
+pub mod machine;
+pub mod rng;
 pub mod signals;
 pub mod template;
 pub mod utils;
+pub mod wallet;
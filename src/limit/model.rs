@@ -0,0 +1,87 @@
+use crate::i18n::{translate_with, FluentArgs};
+
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt};
+
+/// Kind of money movement which is checked against the responsible gambling limits.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum LimitCheckKind {
+    Deposit,
+    Wager,
+}
+
+impl fmt::Display for LimitCheckKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LimitCheckParams {
+    #[serde(default)]
+    pub sub: String,
+    pub kind: LimitCheckKind,
+    pub amount: f64,
+}
+
+/// Whether the money movement is allowed, the reason is a message id with its arguments.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LimitCheck {
+    pub allowed: bool,
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub args: BTreeMap<String, String>,
+}
+
+impl LimitCheck {
+    pub fn allowed() -> LimitCheck {
+        LimitCheck {
+            allowed: true,
+            reason: None,
+            args: BTreeMap::new(),
+        }
+    }
+
+    pub fn refused(reason: &str) -> LimitCheck {
+        LimitCheck {
+            allowed: false,
+            reason: Some(reason.to_owned()),
+            args: BTreeMap::new(),
+        }
+    }
+
+    pub fn arg(mut self, name: &str, value: impl ToString) -> LimitCheck {
+        self.args.insert(name.to_owned(), value.to_string());
+        self
+    }
+
+    /// The reason in the locale of the request.
+    pub fn message(&self) -> String {
+        let mut args: FluentArgs = FluentArgs::new();
+        for (name, value) in &self.args {
+            args.set(name.as_str(), value.as_str());
+        }
+
+        translate_with(self.reason.as_deref().unwrap_or_default(), &args)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::LimitCheck;
+    use crate::i18n::{in_locale, Locale};
+
+    #[test]
+    fn test_refusal_message() {
+        let check: LimitCheck = LimitCheck::refused("limit-reached")
+            .arg("period", "Daily")
+            .arg("kind", "Loss")
+            .arg("limit", 100);
+
+        assert_eq!(check.message(), "Daily Loss limit of 100 reached");
+        assert_eq!(
+            in_locale(Locale::Nl, || check.message()),
+            "Daily Loss limiet van 100 bereikt"
+        );
+    }
+}
@@ -0,0 +1,19 @@
+use super::{LimitCheck, LimitCheckParams};
+
+/// Asks the account service whether the money movement is allowed for the user.
+pub async fn check_limits(
+    req_client: &reqwest::Client,
+    account_url: &str,
+    service_token: &str,
+    params: &LimitCheckParams,
+) -> Result<LimitCheck, reqwest::Error> {
+    req_client
+        .get(format!("{account_url}/account/user/limits/check"))
+        .bearer_auth(service_token)
+        .query(params)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<LimitCheck>()
+        .await
+}
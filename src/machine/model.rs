@@ -0,0 +1,98 @@
+use crate::utils::validate::{Validate, ValidationError};
+
+use serde::{Deserialize, Serialize};
+
+/// Slot machine as stored with its catalog entry.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Machine {
+    /// Symbols on each reel strip, the window shows `rows` consecutive symbols of every reel.
+    pub reels: Vec<Vec<String>>,
+    pub rows: usize,
+    /// Row of the window per reel, every line has one entry for each reel.
+    pub paylines: Vec<Vec<usize>>,
+    pub paytable: Vec<Pay>,
+    /// Stands in for any symbol on a payline, except for the scatter.
+    pub wild: Option<String>,
+    /// Pays anywhere in the window, as multiple of the total bet.
+    pub scatter: Option<String>,
+}
+
+/// Multiplier for `count` symbols, of the line bet or of the total bet for the scatter.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Pay {
+    pub symbol: String,
+    pub count: usize,
+    pub multiplier: f64,
+}
+
+impl Validate for Machine {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.rows == 0 {
+            return Err(ValidationError::new("rows", "must be at least 1"));
+        }
+
+        if self.reels.is_empty() || self.reels.iter().any(|r| r.len() < self.rows) {
+            return Err(ValidationError::new(
+                "reels",
+                "must have at least as many symbols as rows",
+            ));
+        }
+
+        if self.paylines.is_empty()
+            || !self
+                .paylines
+                .iter()
+                .all(|l| l.len() == self.reels.len() && l.iter().all(|row| *row < self.rows))
+        {
+            return Err(ValidationError::new(
+                "paylines",
+                "must have a row within the window for every reel",
+            ));
+        }
+
+        let on_reels = |symbol: &String| self.reels.iter().flatten().any(|s| s == symbol);
+
+        if self.paytable.iter().any(|p| !on_reels(&p.symbol)) {
+            return Err(ValidationError::new(
+                "paytable",
+                "has symbols not on the reels",
+            ));
+        }
+
+        if self
+            .paytable
+            .iter()
+            .any(|p| p.count == 0 || p.count > self.reels.len())
+        {
+            return Err(ValidationError::new(
+                "paytable",
+                "counts must be between one and the number of reels",
+            ));
+        }
+
+        if self
+            .paytable
+            .iter()
+            .any(|p| !p.multiplier.is_finite() || p.multiplier < 0.0)
+        {
+            return Err(ValidationError::new(
+                "paytable",
+                "multipliers cannot be negative",
+            ));
+        }
+
+        if self.wild.as_ref().is_some_and(|w| !on_reels(w)) {
+            return Err(ValidationError::new("wild", "is not on the reels"));
+        }
+
+        if self.scatter.as_ref().is_some_and(|s| !on_reels(s)) {
+            return Err(ValidationError::new("scatter", "is not on the reels"));
+        }
+
+        if self.wild.is_some() && self.wild == self.scatter {
+            return Err(ValidationError::new("scatter", "cannot be the wild"));
+        }
+
+        Ok(())
+    }
+}
@@ -1,10 +1,29 @@
 mod model;
 
+use crate::{
+    deposit::{
+        db::{insert_deposit, lock_deposit_limits, update_deposit_status},
+        exceeded_deposit_limit, requires_verification, Currency, DepositLimits, DepositStatus,
+    },
+    StateParams, ACCOUNT_URL, SERVICE_TOKEN,
+};
+
 use askama::Template;
-use axum::{extract::State, response::Html, Form};
-use leprecon::template::{self, Snackbar};
+use axum::{extract::State, response::Html};
+use leprecon::{
+    broker::{BalanceUpdate, BalanceUpdateKind},
+    i18n::{translate_with, FluentArgs},
+    limit::{check_limits, LimitCheck, LimitCheckKind, LimitCheckParams},
+    template::{self, Snackbar},
+    utils::{
+        extract::{extract_conn_from_pool, ValidForm},
+        PostgresConn,
+    },
+};
 use rabbitmq_stream_client::types::Message;
 use reqwest::StatusCode;
+use std::str::FromStr;
+use tokio_postgres::Transaction;
 use tracing::error;
 
 pub(super) async fn get_balance_page() -> (StatusCode, Html<String>) {
@@ -13,34 +32,167 @@ pub(super) async fn get_balance_page() -> (StatusCode, Html<String>) {
 }
 
 pub(super) async fn add_balance(
-    state: State<rabbitmq_stream_client::Producer<rabbitmq_stream_client::NoDedup>>,
-    Form(balance): Form<model::Balance>,
+    State(state): State<StateParams>,
+    ValidForm(balance): ValidForm<model::Balance>,
 ) -> (StatusCode, Html<String>) {
     let mut snackbar: Snackbar<'_> = Snackbar::default();
 
+    let mut postgres_conn: PostgresConn =
+        match extract_conn_from_pool(&state.1, &mut snackbar).await {
+            Ok(v) => v,
+            Err(e) => return e,
+        };
+
+    let currency: Currency = Currency::from_str(&balance.currency).unwrap();
+    let amount: f64 = balance.amount;
+
+    // Responsible gambling limits set by the user
+    let limit_check: LimitCheck = match check_limits(
+        &state.2,
+        ACCOUNT_URL.get().unwrap(),
+        SERVICE_TOKEN.get().unwrap(),
+        &LimitCheckParams {
+            sub: balance.sub.clone(),
+            kind: LimitCheckKind::Deposit,
+            amount,
+        },
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Could not check account limits: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    let reason: String = limit_check.message();
+    if !limit_check.allowed {
+        snackbar.message = &reason;
+        return (StatusCode::BAD_REQUEST, Html(snackbar.render().unwrap()));
+    }
+
+    let transaction: Transaction = match postgres_conn.transaction().await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Could not start transaction: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    // Held until the deposit is stored, so concurrent deposits cannot both pass the limits
+    let limits: DepositLimits = match lock_deposit_limits(&balance.sub, &transaction).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Could not lock deposit limits: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    match exceeded_deposit_limit(&balance.sub, amount, &limits, &transaction).await {
+        Ok(Some(period)) => {
+            let mut args: FluentArgs = FluentArgs::new();
+            args.set("period", period.to_string());
+            args.set("limit", limits.limit(period));
+            let message: String = translate_with("deposit-limit-exceeded", &args);
+            snackbar.message = &message;
+            return (StatusCode::BAD_REQUEST, Html(snackbar.render().unwrap()));
+        }
+        Ok(None) => {}
+        Err(e) => {
+            error!("Could not check deposit limits: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    match requires_verification(&balance.sub, amount, &transaction).await {
+        Ok(true) => {
+            snackbar.message = "deposit-verification-required";
+            return (StatusCode::FORBIDDEN, Html(snackbar.render().unwrap()));
+        }
+        Ok(false) => {}
+        Err(e) => {
+            error!("Could not check verification threshold: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    let deposit_id: i32 = match insert_deposit(
+        &balance.sub,
+        amount,
+        &currency,
+        balance.provider_reference.as_deref(),
+        &transaction,
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Could not insert deposit: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    if let Err(e) = transaction.commit().await {
+        error!("Could not commit deposit: {:?}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Html(snackbar.render().unwrap()),
+        );
+    }
+
+    let balance_update: BalanceUpdate = BalanceUpdate {
+        sub: balance.sub,
+        kind: BalanceUpdateKind::Deposit,
+        amount,
+        currency: currency.to_string(),
+        reference: format!("deposit:{deposit_id}"),
+    };
+
     if let Err(e) = state
         .0
         .send_with_confirm(
             Message::builder()
-                .body(format!(
-                    "sub: {:?}, amount: {:?}",
-                    balance.sub.to_string(),
-                    balance.amount.to_string()
-                ))
+                .body(serde_json::to_string(&balance_update).unwrap())
                 .build(),
         )
         .await
     {
         error!("Error while publishing message: {:?}", e);
 
+        if let Err(e) =
+            update_deposit_status(deposit_id, DepositStatus::Failed, &postgres_conn).await
+        {
+            error!("Could not update deposit status: {:?}", e);
+        }
+
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
             Html(snackbar.render().unwrap()),
         );
     };
 
-    snackbar.title = "Succes";
-    snackbar.message = "Succesfully added balance";
+    // Completed once the account ledger confirms the credit
+    snackbar.title = "success";
+    snackbar.message = "balance-added";
     snackbar.color = "green";
 
     (StatusCode::OK, Html(snackbar.render().unwrap()))
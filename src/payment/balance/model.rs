@@ -1,7 +1,90 @@
-use serde::{Deserialize, Serialize};
+use crate::{deposit::Currency, DEPOSIT_MAX, DEPOSIT_MIN};
 
-#[derive(Serialize, Deserialize, Debug)]
+use leprecon::utils::validate::{is_whole_cents, Validate, ValidationError};
+use serde::Deserialize;
+use std::str::FromStr;
+
+#[derive(Deserialize, Debug)]
 pub struct Balance {
+    #[serde(default)]
     pub sub: String,
-    pub amount: u32,
+    pub amount: f64,
+    #[serde(default)]
+    pub currency: String,
+    pub provider_reference: Option<String>,
+}
+
+impl Validate for Balance {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.sub.is_empty() {
+            return Err(ValidationError::new("sub", "is required"));
+        }
+
+        if self.currency.is_empty() {
+            return Err(ValidationError::new("currency", "is required"));
+        }
+
+        if Currency::from_str(&self.currency).is_err() {
+            return Err(ValidationError::new("currency", "is not supported"));
+        }
+
+        if !self.amount.is_finite() || self.amount <= 0.0 {
+            return Err(ValidationError::new("amount", "must be positive"));
+        }
+
+        if !is_whole_cents(self.amount) {
+            return Err(ValidationError::new(
+                "amount",
+                "cannot have more than two decimals",
+            ));
+        }
+
+        let min: f64 = *DEPOSIT_MIN.get().unwrap();
+        if self.amount < min {
+            return Err(ValidationError::new(
+                "amount",
+                &format!("must be at least {min}"),
+            ));
+        }
+
+        let max: f64 = *DEPOSIT_MAX.get().unwrap();
+        if self.amount > max {
+            return Err(ValidationError::new(
+                "amount",
+                &format!("must be at most {max}"),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn balance(amount: f64) -> Balance {
+        DEPOSIT_MIN.get_or_init(|| 0.1);
+        DEPOSIT_MAX.get_or_init(|| 1000.0);
+
+        Balance {
+            sub: "auth0|0000".to_owned(),
+            amount,
+            currency: "EUR".to_owned(),
+            provider_reference: None,
+        }
+    }
+
+    #[test]
+    fn test_amount_with_cents() {
+        for amount in [19.99, 0.29, 1.13, 4.35] {
+            assert!(balance(amount).validate().is_ok(), "{amount}");
+        }
+    }
+
+    #[test]
+    fn test_amount_with_more_decimals() {
+        let error: ValidationError = balance(19.999).validate().unwrap_err();
+        assert_eq!(error.field, "amount");
+    }
 }
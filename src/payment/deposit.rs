@@ -0,0 +1,206 @@
+mod model;
+
+pub(crate) mod db;
+
+pub(crate) use model::{Currency, DepositLimits, DepositStatus, LimitPeriod};
+
+use self::{
+    db::{
+        deposited_since, get_deposits, total_deposited, update_deposit_status, upsert_deposit_limit,
+    },
+    model::{Deposit, DepositLimitParams},
+};
+
+use crate::{kyc::db::get_kyc_status, StateParams, KYC_DEPOSIT_THRESHOLD};
+
+use askama::Template;
+use axum::{extract::State, response::Html, Form};
+use bb8_postgres::{bb8::Pool, PostgresConnectionManager};
+use chrono::Local;
+use futures::StreamExt;
+use leprecon::{
+    auth::AuthParam,
+    broker::{BalanceApplied, BalanceUpdateKind, KycStatus},
+    template::{self, Snackbar},
+    utils::{
+        extract::{extract_conn_from_pool, ValidForm},
+        PostgresConn,
+    },
+};
+use rabbitmq_stream_client::Consumer;
+use reqwest::StatusCode;
+use std::error::Error;
+use tokio_postgres::{GenericClient, NoTls};
+use tracing::{debug, error, info};
+
+/// Returns the first limit period of the user which would be exceeded by depositing the amount.
+pub(crate) async fn exceeded_deposit_limit<C: GenericClient>(
+    sub: &str,
+    amount: f64,
+    limits: &DepositLimits,
+    db_client: &C,
+) -> Result<Option<LimitPeriod>, tokio_postgres::Error> {
+    for period in LimitPeriod::ALL {
+        let deposited: f64 =
+            deposited_since(sub, Local::now() - period.duration(), db_client).await?;
+
+        if deposited + amount > limits.limit(period) {
+            return Ok(Some(period));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Whether the deposit takes the user over the threshold above which a verified identity is required.
+pub(crate) async fn requires_verification<C: GenericClient>(
+    sub: &str,
+    amount: f64,
+    db_client: &C,
+) -> Result<bool, Box<dyn Error>> {
+    let deposited: f64 = total_deposited(sub, db_client).await?;
+    if deposited + amount <= *KYC_DEPOSIT_THRESHOLD.get().unwrap() {
+        return Ok(false);
+    }
+
+    Ok(get_kyc_status(sub, db_client).await? != KycStatus::Verified)
+}
+
+/// Sets a deposit limit of the user, below the default of the platform.
+pub(super) async fn set_deposit_limit(
+    State(state): State<StateParams>,
+    ValidForm(params): ValidForm<DepositLimitParams>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    let postgres_conn: PostgresConn = match extract_conn_from_pool(&state.1, &mut snackbar).await {
+        Ok(v) => v,
+        Err(e) => return e,
+    };
+
+    if let Err(e) =
+        upsert_deposit_limit(&params.sub, params.period, params.amount, &postgres_conn).await
+    {
+        error!("Could not set deposit limit: {:?}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Html(snackbar.render().unwrap()),
+        );
+    }
+
+    snackbar.title = "success";
+    snackbar.message = "deposit-limit-updated";
+    snackbar.color = "green";
+
+    (StatusCode::OK, Html(snackbar.render().unwrap()))
+}
+
+/// Completes deposits once the account ledger confirms it credited them.
+pub(super) async fn consume_balance_applied(
+    mut consumer: Consumer,
+    postgres_pool: Pool<PostgresConnectionManager<NoTls>>,
+) {
+    while let Some(delivery) = consumer.next().await {
+        let d = match delivery {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Could not receive balance confirmation: {:?}", e);
+                continue;
+            }
+        };
+
+        let event: BalanceApplied = match d
+            .message()
+            .data()
+            .map(serde_json::from_slice::<BalanceApplied>)
+        {
+            Some(Ok(v)) => v,
+            Some(Err(e)) => {
+                debug!(
+                    "Skipping malformed balance confirmation at {}: {:?}",
+                    d.offset(),
+                    e
+                );
+                continue;
+            }
+            None => continue,
+        };
+
+        if event.kind != BalanceUpdateKind::Deposit {
+            continue;
+        }
+
+        let id: i32 = match event
+            .reference
+            .strip_prefix("deposit:")
+            .and_then(|v| v.parse().ok())
+        {
+            Some(v) => v,
+            None => {
+                debug!("Skipping unknown deposit reference: {}", event.reference);
+                continue;
+            }
+        };
+
+        let postgres_conn: PostgresConn = match postgres_pool.get().await {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Cannot get connection from pool: {:?}", e);
+                continue;
+            }
+        };
+
+        // Also when publishing looked failed, the credit is what counts
+        match update_deposit_status(id, DepositStatus::Completed, &postgres_conn).await {
+            Ok(_) => info!("Completed deposit {}", event.reference),
+            Err(e) => error!("Could not complete deposit {:?}: {:?}", event, e),
+        }
+    }
+}
+
+pub(super) async fn user_deposits(
+    State(state): State<StateParams>,
+    Form(auth_param): Form<AuthParam>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    if auth_param.sub.is_empty() {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Html(snackbar.render().unwrap()),
+        );
+    };
+
+    let postgres_conn: PostgresConn = match extract_conn_from_pool(&state.1, &mut snackbar).await {
+        Ok(v) => v,
+        Err(e) => return e,
+    };
+
+    let deposits: Vec<Deposit> = match get_deposits(&auth_param.sub, &postgres_conn).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Could not fetch deposits: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    let deposits_template: template::Deposits = template::Deposits {
+        deposits: deposits
+            .into_iter()
+            .map(|d| template::Deposit {
+                id: d.id,
+                amount: d.amount,
+                currency: d.currency.to_string(),
+                status: d.status.to_string(),
+                provider_reference: d.provider_reference,
+                created: d.created.format("%Y-%m-%d %H:%M").to_string(),
+                updated: d.updated.format("%Y-%m-%d %H:%M").to_string(),
+            })
+            .collect(),
+    };
+
+    (StatusCode::OK, Html(deposits_template.render().unwrap()))
+}
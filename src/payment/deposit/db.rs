@@ -0,0 +1,135 @@
+use super::model::{Currency, Deposit, DepositLimits, DepositStatus, LimitPeriod};
+
+use chrono::{DateTime, Local};
+use leprecon::utils::PostgresConn;
+use std::{error::Error, str::FromStr};
+use tokio_postgres::{GenericClient, Row, Transaction};
+
+pub(crate) async fn insert_deposit<C: GenericClient>(
+    sub: &str,
+    amount: f64,
+    currency: &Currency,
+    provider_reference: Option<&str>,
+    db_client: &C,
+) -> Result<i32, tokio_postgres::Error> {
+    let r: Row = db_client
+        .query_one(
+            "INSERT INTO deposits(sub, amount, currency, status, provider_reference, created, updated) VALUES($1, $2, $3, $4, $5, $6, $6) RETURNING id",
+            &[&sub, &amount, &currency.to_string(), &DepositStatus::Pending.to_string(), &provider_reference, &Local::now()],
+        )
+        .await?;
+
+    Ok(r.get("id"))
+}
+
+pub(crate) async fn update_deposit_status(
+    id: i32,
+    status: DepositStatus,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    db_client
+        .execute(
+            "UPDATE deposits SET status = $2, updated = $3 WHERE id = $1",
+            &[&id, &status.to_string(), &Local::now()],
+        )
+        .await
+}
+
+pub(super) async fn get_deposits(
+    sub: &str,
+    db_client: &PostgresConn<'_>,
+) -> Result<Vec<Deposit>, Box<dyn Error>> {
+    let rows: Vec<Row> = db_client
+        .query(
+            "SELECT * FROM deposits WHERE sub = $1 ORDER BY created DESC",
+            &[&sub],
+        )
+        .await?;
+
+    let mut deposits: Vec<Deposit> = vec![];
+    for r in rows {
+        deposits.push(Deposit {
+            id: r.get("id"),
+            amount: r.get("amount"),
+            currency: Currency::from_str(r.get("currency"))?,
+            status: DepositStatus::from_str(r.get("status"))?,
+            provider_reference: r.get("provider_reference"),
+            created: r.get("created"),
+            updated: r.get("updated"),
+        });
+    }
+
+    Ok(deposits)
+}
+
+pub(crate) async fn deposited_since<C: GenericClient>(
+    sub: &str,
+    since: DateTime<Local>,
+    db_client: &C,
+) -> Result<f64, tokio_postgres::Error> {
+    let r: Row = db_client
+        .query_one(
+            "SELECT COALESCE(SUM(amount), 0) AS total FROM deposits WHERE sub = $1 AND status <> $2 AND created > $3",
+            &[&sub, &DepositStatus::Failed.to_string(), &since],
+        )
+        .await?;
+
+    Ok(r.get("total"))
+}
+
+pub(crate) async fn total_deposited<C: GenericClient>(
+    sub: &str,
+    db_client: &C,
+) -> Result<f64, tokio_postgres::Error> {
+    let r: Row = db_client
+        .query_one(
+            "SELECT COALESCE(SUM(amount), 0) AS total FROM deposits WHERE sub = $1 AND status <> $2",
+            &[&sub, &DepositStatus::Failed.to_string()],
+        )
+        .await?;
+
+    Ok(r.get("total"))
+}
+
+/// Limits of the user, locked until the transaction ends so concurrent deposits are checked one by one.
+pub(crate) async fn lock_deposit_limits(
+    sub: &str,
+    transaction: &Transaction<'_>,
+) -> Result<DepositLimits, tokio_postgres::Error> {
+    transaction
+        .execute(
+            "INSERT INTO deposit_limits(sub) VALUES($1) ON CONFLICT (sub) DO NOTHING",
+            &[&sub],
+        )
+        .await?;
+
+    let r: Row = transaction
+        .query_one(
+            "SELECT daily, weekly, monthly FROM deposit_limits WHERE sub = $1 FOR UPDATE",
+            &[&sub],
+        )
+        .await?;
+
+    Ok(DepositLimits {
+        daily: r.get("daily"),
+        weekly: r.get("weekly"),
+        monthly: r.get("monthly"),
+    })
+}
+
+/// Sets the limit of the user for the period, `None` falls back to the default.
+pub(crate) async fn upsert_deposit_limit(
+    sub: &str,
+    period: LimitPeriod,
+    amount: Option<f64>,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    let column: &str = period.column();
+
+    db_client
+        .execute(
+            &format!("INSERT INTO deposit_limits(sub, {column}) VALUES($1, $2) ON CONFLICT (sub) DO UPDATE SET {column} = EXCLUDED.{column}"),
+            &[&sub, &amount],
+        )
+        .await
+}
@@ -0,0 +1,190 @@
+use crate::{DEPOSIT_LIMIT_DAILY, DEPOSIT_LIMIT_MONTHLY, DEPOSIT_LIMIT_WEEKLY};
+
+use chrono::{DateTime, Duration, Local};
+use leprecon::utils::validate::{is_whole_cents, Validate, ValidationError};
+use serde::Deserialize;
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+#[derive(Debug)]
+#[allow(clippy::upper_case_acronyms)]
+pub(crate) enum Currency {
+    EUR,
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum DepositStatus {
+    Pending,
+    Completed,
+    Failed,
+}
+
+impl fmt::Display for DepositStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub(crate) enum LimitPeriod {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl fmt::Display for LimitPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl LimitPeriod {
+    pub(crate) const ALL: [LimitPeriod; 3] = [
+        LimitPeriod::Daily,
+        LimitPeriod::Weekly,
+        LimitPeriod::Monthly,
+    ];
+
+    pub(crate) fn duration(&self) -> Duration {
+        match self {
+            LimitPeriod::Daily => Duration::days(1),
+            LimitPeriod::Weekly => Duration::weeks(1),
+            LimitPeriod::Monthly => Duration::days(30),
+        }
+    }
+
+    /// Limit for users who did not set their own.
+    pub(crate) fn default_limit(&self) -> f64 {
+        match self {
+            LimitPeriod::Daily => *DEPOSIT_LIMIT_DAILY.get().unwrap(),
+            LimitPeriod::Weekly => *DEPOSIT_LIMIT_WEEKLY.get().unwrap(),
+            LimitPeriod::Monthly => *DEPOSIT_LIMIT_MONTHLY.get().unwrap(),
+        }
+    }
+
+    pub(crate) fn column(&self) -> &'static str {
+        match self {
+            LimitPeriod::Daily => "daily",
+            LimitPeriod::Weekly => "weekly",
+            LimitPeriod::Monthly => "monthly",
+        }
+    }
+}
+
+/// Deposit limits set by the user.
+#[derive(Debug, Default)]
+pub(crate) struct DepositLimits {
+    pub daily: Option<f64>,
+    pub weekly: Option<f64>,
+    pub monthly: Option<f64>,
+}
+
+impl DepositLimits {
+    pub(crate) fn limit(&self, period: LimitPeriod) -> f64 {
+        let limit: Option<f64> = match period {
+            LimitPeriod::Daily => self.daily,
+            LimitPeriod::Weekly => self.weekly,
+            LimitPeriod::Monthly => self.monthly,
+        };
+
+        limit.unwrap_or_else(|| period.default_limit())
+    }
+}
+
+/// Own limit of the user for a period, without amount the default applies again.
+#[derive(Deserialize, Debug)]
+pub(crate) struct DepositLimitParams {
+    #[serde(default)]
+    pub sub: String,
+    pub period: LimitPeriod,
+    pub amount: Option<f64>,
+}
+
+impl Validate for DepositLimitParams {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.sub.is_empty() {
+            return Err(ValidationError::new("sub", "is required"));
+        }
+
+        let amount: f64 = match self.amount {
+            Some(v) => v,
+            None => return Ok(()),
+        };
+
+        if !amount.is_finite() || amount <= 0.0 {
+            return Err(ValidationError::new("amount", "must be positive"));
+        }
+
+        if !is_whole_cents(amount) {
+            return Err(ValidationError::new(
+                "amount",
+                "cannot have more than two decimals",
+            ));
+        }
+
+        // Users can only be stricter than the platform
+        let max: f64 = self.period.default_limit();
+        if amount > max {
+            return Err(ValidationError::new(
+                "amount",
+                &format!("must be at most {max}"),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) struct Deposit {
+    pub id: i32,
+    pub amount: f64,
+    pub currency: Currency,
+    pub status: DepositStatus,
+    pub provider_reference: Option<String>,
+    pub created: DateTime<Local>,
+    pub updated: DateTime<Local>,
+}
+
+#[derive(Debug)]
+pub(crate) struct ParseDepositError;
+
+impl Error for ParseDepositError {}
+
+impl Display for ParseDepositError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ParseDepositError")
+    }
+}
+
+impl FromStr for Currency {
+    type Err = ParseDepositError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "EUR" => Ok(Currency::EUR),
+            _ => Err(ParseDepositError),
+        }
+    }
+}
+
+impl FromStr for DepositStatus {
+    type Err = ParseDepositError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Pending" => Ok(DepositStatus::Pending),
+            "Completed" => Ok(DepositStatus::Completed),
+            "Failed" => Ok(DepositStatus::Failed),
+            _ => Err(ParseDepositError),
+        }
+    }
+}
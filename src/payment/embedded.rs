@@ -0,0 +1,3 @@
+use refinery::embed_migrations;
+
+embed_migrations!("src/payment/migrations");
@@ -0,0 +1,103 @@
+pub(crate) mod db;
+
+use self::db::{delete_kyc_status, upsert_kyc_status};
+
+use bb8_postgres::{bb8::Pool, PostgresConnectionManager};
+use futures::StreamExt;
+use leprecon::{
+    broker::{AccountDeleted, KycStatusChanged},
+    utils::PostgresConn,
+};
+use rabbitmq_stream_client::Consumer;
+use tokio_postgres::NoTls;
+use tracing::{debug, error, info};
+
+/// Keeps track of the verification status of users, published by the account service.
+pub(super) async fn consume_kyc_status(
+    mut consumer: Consumer,
+    postgres_pool: Pool<PostgresConnectionManager<NoTls>>,
+) {
+    while let Some(delivery) = consumer.next().await {
+        let d = match delivery {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Could not receive kyc status: {:?}", e);
+                continue;
+            }
+        };
+
+        let event: KycStatusChanged = match d
+            .message()
+            .data()
+            .map(serde_json::from_slice::<KycStatusChanged>)
+        {
+            Some(Ok(v)) => v,
+            Some(Err(e)) => {
+                debug!("Skipping malformed kyc status at {}: {:?}", d.offset(), e);
+                continue;
+            }
+            None => continue,
+        };
+
+        let postgres_conn: PostgresConn = match postgres_pool.get().await {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Cannot get connection from pool: {:?}", e);
+                continue;
+            }
+        };
+
+        match upsert_kyc_status(&event, &postgres_conn).await {
+            Ok(0) => debug!("Skipped kyc status of deleted account {}", event.sub),
+            Ok(_) => info!("Updated kyc status of {}: {}", event.sub, event.status),
+            Err(e) => error!("Could not update kyc status {:?}: {:?}", event, e),
+        }
+    }
+}
+
+/// Forgets the verification status of deleted accounts, and keeps them from coming back when the
+/// streams are replayed. Deposits are kept as financial records.
+pub(super) async fn consume_account_deleted(
+    mut consumer: Consumer,
+    postgres_pool: Pool<PostgresConnectionManager<NoTls>>,
+) {
+    while let Some(delivery) = consumer.next().await {
+        let d = match delivery {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Could not receive account deletion: {:?}", e);
+                continue;
+            }
+        };
+
+        let event: AccountDeleted = match d
+            .message()
+            .data()
+            .map(serde_json::from_slice::<AccountDeleted>)
+        {
+            Some(Ok(v)) => v,
+            Some(Err(e)) => {
+                debug!(
+                    "Skipping malformed account deletion at {}: {:?}",
+                    d.offset(),
+                    e
+                );
+                continue;
+            }
+            None => continue,
+        };
+
+        let mut postgres_conn: PostgresConn = match postgres_pool.get().await {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Cannot get connection from pool: {:?}", e);
+                continue;
+            }
+        };
+
+        match delete_kyc_status(&event.sub, &mut postgres_conn).await {
+            Ok(_) => info!("Purged kyc status of deleted account {}", event.sub),
+            Err(e) => error!("Could not purge kyc status {:?}: {:?}", event, e),
+        }
+    }
+}
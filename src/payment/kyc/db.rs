@@ -0,0 +1,58 @@
+use chrono::Local;
+use leprecon::{
+    broker::{KycStatus, KycStatusChanged},
+    utils::PostgresConn,
+};
+use std::{error::Error, str::FromStr};
+use tokio_postgres::{GenericClient, Row, Transaction};
+
+/// Stores the status, unless the account was deleted since, returns the rows updated.
+pub(super) async fn upsert_kyc_status(
+    event: &KycStatusChanged,
+    db_client: &PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    db_client
+        .execute(
+            "INSERT INTO kyc_statuses(sub, status, updated) SELECT $1, $2, $3 WHERE NOT EXISTS (SELECT 1 FROM deleted_accounts WHERE sub = $1) ON CONFLICT (sub) DO UPDATE SET status = EXCLUDED.status, updated = EXCLUDED.updated",
+            &[&event.sub, &event.status.to_string(), &Local::now()],
+        )
+        .await
+}
+
+/// Deletes the status and keeps a tombstone, so a replayed status does not bring it back.
+pub(super) async fn delete_kyc_status(
+    sub: &str,
+    db_client: &mut PostgresConn<'_>,
+) -> Result<u64, tokio_postgres::Error> {
+    let transaction: Transaction = db_client.transaction().await?;
+
+    transaction
+        .execute(
+            "INSERT INTO deleted_accounts(sub, deleted) VALUES($1, $2) ON CONFLICT (sub) DO NOTHING",
+            &[&sub, &Local::now()],
+        )
+        .await?;
+
+    let deleted: u64 = transaction
+        .execute("DELETE FROM kyc_statuses WHERE sub = $1", &[&sub])
+        .await?;
+
+    transaction.commit().await?;
+
+    Ok(deleted)
+}
+
+/// Last known verification status of the user, unknown users are unverified.
+pub(crate) async fn get_kyc_status<C: GenericClient>(
+    sub: &str,
+    db_client: &C,
+) -> Result<KycStatus, Box<dyn Error>> {
+    let r: Option<Row> = db_client
+        .query_opt("SELECT status FROM kyc_statuses WHERE sub = $1", &[&sub])
+        .await?;
+
+    match r {
+        Some(v) => Ok(KycStatus::from_str(v.get("status"))?),
+        None => Ok(KycStatus::Unverified),
+    }
+}
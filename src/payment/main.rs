@@ -1,17 +1,51 @@
 mod balance;
+mod deposit;
+mod embedded;
+mod kyc;
 
-use axum::{serve, Router};
+use axum::{middleware, serve, Router};
 use balance::{add_balance, get_balance_page};
-use leprecon::{broker::init_broker, signals::shutdown_signal, utils::configure_tracing};
-use rabbitmq_stream_client::types::ByteCapacity;
-use std::{env, error::Error, sync::OnceLock};
-use tokio::net::TcpListener;
+use bb8_postgres::{bb8::Pool, PostgresConnectionManager};
+use deposit::{consume_balance_applied, set_deposit_limit, user_deposits};
+use kyc::{consume_account_deleted, consume_kyc_status};
+use leprecon::{
+    broker::init_broker,
+    i18n::resolve_locale,
+    signals::shutdown_signal,
+    utils::{configure_tracing, create_conn_pool},
+};
+use rabbitmq_stream_client::types::{ByteCapacity, OffsetSpecification};
+use std::{env, error::Error, ops::DerefMut, sync::OnceLock, time::Duration};
+use tokio::{net::TcpListener, task};
+use tokio_postgres::NoTls;
 use tracing::{error, info};
 
+type StateParams = (
+    rabbitmq_stream_client::Producer<rabbitmq_stream_client::NoDedup>,
+    Pool<PostgresConnectionManager<NoTls>>,
+    reqwest::Client,
+);
+
 // Host variables
 static HOST: OnceLock<String> = OnceLock::new();
 static LOG_LEVEL: OnceLock<String> = OnceLock::new();
 
+// DB variables
+static DB_CONN: OnceLock<String> = OnceLock::new();
+static PAYMENT_CONN: OnceLock<String> = OnceLock::new();
+
+// Service variables
+static ACCOUNT_URL: OnceLock<String> = OnceLock::new();
+static SERVICE_TOKEN: OnceLock<String> = OnceLock::new();
+
+// Deposit variables
+static DEPOSIT_MIN: OnceLock<f64> = OnceLock::new();
+static DEPOSIT_MAX: OnceLock<f64> = OnceLock::new();
+static DEPOSIT_LIMIT_DAILY: OnceLock<f64> = OnceLock::new();
+static DEPOSIT_LIMIT_WEEKLY: OnceLock<f64> = OnceLock::new();
+static DEPOSIT_LIMIT_MONTHLY: OnceLock<f64> = OnceLock::new();
+static KYC_DEPOSIT_THRESHOLD: OnceLock<f64> = OnceLock::new();
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     // Initialize env variables
@@ -33,11 +67,111 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let producer: rabbitmq_stream_client::Producer<rabbitmq_stream_client::NoDedup> =
         environment.producer().build(stream).await?;
 
+    let kyc_stream = "kyc_status";
+    let create_response = environment
+        .stream_creator()
+        .max_length(ByteCapacity::GB(5))
+        .create(kyc_stream)
+        .await;
+
+    if let Err(e) = create_response {
+        error!("Error creating stream: {:?} {:?}", kyc_stream, e);
+    }
+
+    let consumer = environment
+        .consumer()
+        .offset(OffsetSpecification::First)
+        .build(kyc_stream)
+        .await?;
+
+    let deleted_stream = "account_deleted";
+    let create_response = environment
+        .stream_creator()
+        .max_length(ByteCapacity::GB(5))
+        .create(deleted_stream)
+        .await;
+
+    if let Err(e) = create_response {
+        error!("Error creating stream: {:?} {:?}", deleted_stream, e);
+    }
+
+    let deleted_consumer = environment
+        .consumer()
+        .offset(OffsetSpecification::First)
+        .build(deleted_stream)
+        .await?;
+
+    let applied_stream = "balance_applied";
+    let create_response = environment
+        .stream_creator()
+        .max_length(ByteCapacity::GB(5))
+        .create(applied_stream)
+        .await;
+
+    if let Err(e) = create_response {
+        error!("Error creating stream: {:?} {:?}", applied_stream, e);
+    }
+
+    let applied_consumer = environment
+        .consumer()
+        .offset(OffsetSpecification::First)
+        .build(applied_stream)
+        .await?;
+
     // Configure logging
     configure_tracing(LOG_LEVEL.get().unwrap());
 
+    // Create database if not exist
+    let (db_client, connection) = tokio_postgres::connect(DB_CONN.get().unwrap(), NoTls).await?;
+
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            error!("connection error: {}", e);
+        }
+    });
+
+    if let Err(e) = db_client.query("CREATE DATABASE payment", &[]).await {
+        error!("Database already exists: {:?}", e);
+    };
+
+    // Connection pool config
+    let connection_timeout: Duration = Duration::from_secs(10);
+    let max_size: u32 = 20;
+
+    // Postgres connection pool
+    let postgres_manager: PostgresConnectionManager<tokio_postgres::NoTls> =
+        PostgresConnectionManager::new_from_stringlike(
+            PAYMENT_CONN.get().unwrap(),
+            tokio_postgres::NoTls,
+        )?;
+    let postgres_pool: Pool<PostgresConnectionManager<NoTls>> =
+        create_conn_pool(postgres_manager, connection_timeout, max_size).await?;
+
+    // Run migrations
+    embedded::migrations::runner()
+        .run_async(postgres_pool.get().await?.deref_mut())
+        .await?;
+
+    // Track kyc status
+    task::spawn(consume_kyc_status(consumer, postgres_pool.clone()));
+
+    // Purge data of deleted accounts
+    task::spawn(consume_account_deleted(
+        deleted_consumer,
+        postgres_pool.clone(),
+    ));
+
+    // Complete credited deposits
+    task::spawn(consume_balance_applied(
+        applied_consumer,
+        postgres_pool.clone(),
+    ));
+
+    // Http client (holds connection pool internally)
+    let req_client: reqwest::Client = reqwest::Client::new();
+
     // Build application and listen to incoming requests.
-    let app: Router = build_app(producer);
+    let app: Router = build_app(producer, postgres_pool, req_client);
     let listener: TcpListener = TcpListener::bind(HOST.get().unwrap()).await?;
 
     info!("Running application");
@@ -54,16 +188,39 @@ async fn main() -> Result<(), Box<dyn Error>> {
 fn init_env() {
     HOST.get_or_init(|| env::var("PAYMENT_HOST").unwrap());
     LOG_LEVEL.get_or_init(|| env::var("LOG_LEVEL").unwrap());
+
+    DB_CONN.get_or_init(|| env::var("DB_CONN").unwrap());
+    PAYMENT_CONN.get_or_init(|| env::var("PAYMENT_CONN").unwrap());
+
+    ACCOUNT_URL.get_or_init(|| env::var("ACCOUNT_URL").unwrap());
+    SERVICE_TOKEN.get_or_init(|| env::var("SERVICE_TOKEN").unwrap());
+
+    DEPOSIT_MIN.get_or_init(|| env::var("DEPOSIT_MIN").unwrap().parse().unwrap());
+    DEPOSIT_MAX.get_or_init(|| env::var("DEPOSIT_MAX").unwrap().parse().unwrap());
+    DEPOSIT_LIMIT_DAILY.get_or_init(|| env::var("DEPOSIT_LIMIT_DAILY").unwrap().parse().unwrap());
+    DEPOSIT_LIMIT_WEEKLY.get_or_init(|| env::var("DEPOSIT_LIMIT_WEEKLY").unwrap().parse().unwrap());
+    DEPOSIT_LIMIT_MONTHLY
+        .get_or_init(|| env::var("DEPOSIT_LIMIT_MONTHLY").unwrap().parse().unwrap());
+    KYC_DEPOSIT_THRESHOLD
+        .get_or_init(|| env::var("KYC_DEPOSIT_THRESHOLD").unwrap().parse().unwrap());
 }
 
 /// Builds the application.
 fn build_app(
     producer: rabbitmq_stream_client::Producer<rabbitmq_stream_client::NoDedup>,
+    postgres_pool: Pool<PostgresConnectionManager<NoTls>>,
+    req_client: reqwest::Client,
 ) -> Router {
-    Router::new().route(
-        "/payment/balance",
-        axum::routing::post(add_balance)
-            .get(get_balance_page)
-            .with_state(producer),
-    )
+    Router::new()
+        .route(
+            "/payment/balance",
+            axum::routing::post(add_balance).get(get_balance_page),
+        )
+        .route("/payment/deposits", axum::routing::get(user_deposits))
+        .route(
+            "/payment/deposits/limits",
+            axum::routing::put(set_deposit_limit),
+        )
+        .with_state((producer, postgres_pool, req_client))
+        .layer(middleware::from_fn(resolve_locale))
 }
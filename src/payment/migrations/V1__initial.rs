@@ -0,0 +1,20 @@
+use barrel::{backend::Pg, types, Migration};
+
+pub(crate) fn migration() -> String {
+    let mut m: Migration = Migration::new();
+
+    m.create_table_if_not_exists("deposits", |t| {
+        t.add_column("id", types::primary());
+        t.add_column("sub", types::text());
+        t.add_column("amount", types::double());
+        t.add_column("currency", types::text());
+        t.add_column("status", types::text());
+        t.add_column("provider_reference", types::text().nullable(true));
+        t.add_column("created", types::custom("timestamp with time zone"));
+        t.add_column("updated", types::custom("timestamp with time zone"));
+
+        t.add_index("deposits_sub", types::index(vec!["sub"]));
+    });
+
+    m.make::<Pg>()
+}
@@ -0,0 +1,14 @@
+use barrel::{backend::Pg, types, Migration};
+
+pub(crate) fn migration() -> String {
+    let mut m: Migration = Migration::new();
+
+    m.create_table_if_not_exists("kyc_statuses", |t| {
+        t.add_column("id", types::primary());
+        t.add_column("sub", types::text().unique(true));
+        t.add_column("status", types::text());
+        t.add_column("updated", types::custom("timestamp with time zone"));
+    });
+
+    m.make::<Pg>()
+}
@@ -0,0 +1,16 @@
+use barrel::{backend::Pg, types, Migration};
+
+pub(crate) fn migration() -> String {
+    let mut m: Migration = Migration::new();
+
+    // Limits of the user, the configured default applies where empty
+    m.create_table_if_not_exists("deposit_limits", |t| {
+        t.add_column("id", types::primary());
+        t.add_column("sub", types::text().unique(true));
+        t.add_column("daily", types::double().nullable(true));
+        t.add_column("weekly", types::double().nullable(true));
+        t.add_column("monthly", types::double().nullable(true));
+    });
+
+    m.make::<Pg>()
+}
@@ -0,0 +1,14 @@
+use barrel::{backend::Pg, types, Migration};
+
+pub(crate) fn migration() -> String {
+    let mut m: Migration = Migration::new();
+
+    // Kept as long as the kyc_status stream, which is replayed from the start on every restart
+    m.create_table_if_not_exists("deleted_accounts", |t| {
+        t.add_column("id", types::primary());
+        t.add_column("sub", types::text().unique(true));
+        t.add_column("deleted", types::custom("timestamp with time zone"));
+    });
+
+    m.make::<Pg>()
+}
@@ -0,0 +1,313 @@
+use leprecon::{machine::Machine, rng::Rng};
+
+/// Machine with the symbols numbered, so a spin does not compare strings.
+pub(crate) struct Reels {
+    symbols: Vec<String>,
+    reels: Vec<Vec<usize>>,
+    rows: usize,
+    paylines: Vec<Vec<usize>>,
+    /// Best multiplier per symbol for a run of each length, zero when it does not pay.
+    pays: Vec<Vec<f64>>,
+    wild: Option<usize>,
+    scatter: Option<usize>,
+}
+
+/// Winning payline, the multiplier is of the line bet.
+#[derive(Debug, PartialEq)]
+pub(crate) struct LineWin {
+    pub line: usize,
+    pub symbol: String,
+    pub count: usize,
+    pub multiplier: f64,
+}
+
+/// Scatter win, the multiplier is of the total bet.
+#[derive(Debug, PartialEq)]
+pub(crate) struct ScatterWin {
+    pub symbol: String,
+    pub count: usize,
+    pub multiplier: f64,
+}
+
+#[derive(Debug)]
+pub(crate) struct Spin {
+    pub stops: Vec<usize>,
+    /// Symbols in view, row by row.
+    pub window: Vec<Vec<String>>,
+    pub lines: Vec<LineWin>,
+    pub scatter: Option<ScatterWin>,
+    /// Total win as multiple of the bet.
+    pub multiplier: f64,
+}
+
+impl From<&Machine> for Reels {
+    /// Only call on a validated machine.
+    fn from(machine: &Machine) -> Self {
+        let mut symbols: Vec<String> = vec![];
+        let mut index = |symbol: &String| match symbols.iter().position(|s| s == symbol) {
+            Some(v) => v,
+            None => {
+                symbols.push(symbol.to_owned());
+                symbols.len() - 1
+            }
+        };
+
+        let reels: Vec<Vec<usize>> = machine
+            .reels
+            .iter()
+            .map(|reel| reel.iter().map(&mut index).collect())
+            .collect();
+        let wild: Option<usize> = machine.wild.as_ref().map(&mut index);
+        let scatter: Option<usize> = machine.scatter.as_ref().map(&mut index);
+        let paid: Vec<usize> = machine
+            .paytable
+            .iter()
+            .map(|pay| index(&pay.symbol))
+            .collect();
+
+        let mut pays: Vec<Vec<f64>> = vec![vec![0.0; reels.len() + 1]; symbols.len()];
+        for (symbol, pay) in paid.into_iter().zip(&machine.paytable) {
+            for best in pays[symbol].iter_mut().skip(pay.count) {
+                *best = best.max(pay.multiplier);
+            }
+        }
+
+        Reels {
+            symbols,
+            reels,
+            rows: machine.rows,
+            paylines: machine.paylines.clone(),
+            pays,
+            wild,
+            scatter,
+        }
+    }
+}
+
+impl Reels {
+    pub(crate) fn reel_count(&self) -> usize {
+        self.reels.len()
+    }
+
+    pub(crate) fn spin(&self, rng: &mut Rng) -> Spin {
+        let mut stops: Vec<usize> = vec![0; self.reels.len()];
+        self.fill_stops(rng, &mut stops);
+        self.evaluate(stops)
+    }
+
+    /// Stops every reel at random, reuses the buffer so simulations do not allocate.
+    pub(crate) fn fill_stops(&self, rng: &mut Rng, stops: &mut [usize]) {
+        for (stop, reel) in stops.iter_mut().zip(&self.reels) {
+            *stop = rng.below(reel.len() as u64) as usize;
+        }
+    }
+
+    pub(crate) fn evaluate(&self, stops: Vec<usize>) -> Spin {
+        let window: Vec<Vec<String>> = (0..self.rows)
+            .map(|row| {
+                (0..self.reels.len())
+                    .map(|reel| self.symbols[self.symbol_at(&stops, reel, row)].to_owned())
+                    .collect()
+            })
+            .collect();
+
+        let lines: Vec<LineWin> = self
+            .paylines
+            .iter()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                self.line_pay(&stops, line)
+                    .map(|(symbol, count, multiplier)| LineWin {
+                        line: i + 1,
+                        symbol: self.symbols[symbol].to_owned(),
+                        count,
+                        multiplier,
+                    })
+            })
+            .collect();
+
+        let scatter: Option<ScatterWin> =
+            self.scatter_pay(&stops)
+                .map(|(symbol, count, multiplier)| ScatterWin {
+                    symbol: self.symbols[symbol].to_owned(),
+                    count,
+                    multiplier,
+                });
+
+        let multiplier: f64 = self.multiplier(&stops);
+
+        Spin {
+            stops,
+            window,
+            lines,
+            scatter,
+            multiplier,
+        }
+    }
+
+    /// Total win of the stops as multiple of the bet, which is spread evenly over the lines.
+    pub(crate) fn multiplier(&self, stops: &[usize]) -> f64 {
+        let lines: f64 = self
+            .paylines
+            .iter()
+            .filter_map(|line| self.line_pay(stops, line))
+            .map(|(_, _, multiplier)| multiplier)
+            .sum();
+        let scatter: f64 = self
+            .scatter_pay(stops)
+            .map_or(0.0, |(_, _, multiplier)| multiplier);
+
+        lines / self.paylines.len() as f64 + scatter
+    }
+
+    fn symbol_at(&self, stops: &[usize], reel: usize, row: usize) -> usize {
+        let strip: &[usize] = &self.reels[reel];
+        strip[(stops[reel] + row) % strip.len()]
+    }
+
+    /// Pays the run from the leftmost reel, wilds pay on their own when that pays more.
+    fn line_pay(&self, stops: &[usize], line: &[usize]) -> Option<(usize, usize, f64)> {
+        let symbols = line
+            .iter()
+            .enumerate()
+            .map(|(reel, row)| self.symbol_at(stops, reel, *row));
+        let is_wild = |s: usize| Some(s) == self.wild;
+
+        let wilds: usize = symbols.clone().take_while(|s| is_wild(*s)).count();
+        let target: Option<usize> = symbols
+            .clone()
+            .find(|s| !is_wild(*s))
+            .filter(|s| Some(*s) != self.scatter);
+
+        let mut best: Option<(usize, usize, f64)> = self
+            .wild
+            .map(|w| (w, wilds, self.pays[w][wilds]))
+            .filter(|(_, _, m)| *m > 0.0);
+
+        if let Some(target) = target {
+            let count: usize = symbols.take_while(|s| *s == target || is_wild(*s)).count();
+            let multiplier: f64 = self.pays[target][count];
+            if multiplier > best.map_or(0.0, |(_, _, m)| m) {
+                best = Some((target, count, multiplier));
+            }
+        }
+
+        best
+    }
+
+    fn scatter_pay(&self, stops: &[usize]) -> Option<(usize, usize, f64)> {
+        let scatter: usize = self.scatter?;
+        let count: usize = (0..self.reels.len())
+            .flat_map(|reel| (0..self.rows).map(move |row| (reel, row)))
+            .filter(|(reel, row)| self.symbol_at(stops, *reel, *row) == scatter)
+            .count()
+            .min(self.reels.len());
+
+        Some((scatter, count, self.pays[scatter][count])).filter(|(_, _, m)| *m > 0.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use leprecon::{machine::Pay, utils::validate::Validate};
+
+    fn pay(symbol: &str, count: usize, multiplier: f64) -> Pay {
+        Pay {
+            symbol: symbol.to_owned(),
+            count,
+            multiplier,
+        }
+    }
+
+    fn strip(symbols: &[&str]) -> Vec<String> {
+        symbols.iter().map(|s| s.to_string()).collect()
+    }
+
+    /// Three reels of one row, so the stops pick the symbols on the only line.
+    fn machine() -> Machine {
+        let reel: Vec<String> = strip(&["A", "B", "W", "S"]);
+        Machine {
+            reels: vec![reel.clone(), reel.clone(), reel],
+            rows: 1,
+            paylines: vec![vec![0, 0, 0]],
+            paytable: vec![
+                pay("A", 2, 2.0),
+                pay("A", 3, 10.0),
+                pay("B", 3, 5.0),
+                pay("W", 3, 50.0),
+                pay("S", 2, 1.0),
+                pay("S", 3, 20.0),
+            ],
+            wild: Some("W".to_owned()),
+            scatter: Some("S".to_owned()),
+        }
+    }
+
+    #[test]
+    fn test_machine_is_valid() {
+        assert!(machine().validate().is_ok());
+
+        let mut invalid: Machine = machine();
+        invalid.paylines = vec![vec![0, 1, 0]];
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_line_pays_longest_run() {
+        let reels: Reels = Reels::from(&machine());
+
+        let spin: Spin = reels.evaluate(vec![0, 0, 1]);
+        assert_eq!(spin.window, vec![strip(&["A", "A", "B"])]);
+        assert_eq!(
+            spin.lines,
+            vec![LineWin {
+                line: 1,
+                symbol: "A".to_owned(),
+                count: 2,
+                multiplier: 2.0,
+            }]
+        );
+        assert_eq!(spin.multiplier, 2.0);
+
+        assert_eq!(reels.multiplier(&[1, 0, 0]), 0.0);
+    }
+
+    #[test]
+    fn test_wild_substitutes() {
+        let reels: Reels = Reels::from(&machine());
+
+        assert_eq!(reels.multiplier(&[2, 0, 2]), 10.0);
+        assert_eq!(reels.multiplier(&[2, 2, 1]), 5.0);
+        assert_eq!(reels.multiplier(&[2, 2, 2]), 50.0);
+    }
+
+    #[test]
+    fn test_scatter_pays_anywhere() {
+        let reels: Reels = Reels::from(&machine());
+
+        let spin: Spin = reels.evaluate(vec![3, 0, 3]);
+        assert!(spin.lines.is_empty());
+        assert_eq!(
+            spin.scatter,
+            Some(ScatterWin {
+                symbol: "S".to_owned(),
+                count: 2,
+                multiplier: 1.0,
+            })
+        );
+
+        // Wilds do not stand in for the scatter
+        assert_eq!(reels.multiplier(&[3, 2, 3]), 1.0);
+    }
+
+    #[test]
+    fn test_same_seed_same_spin() {
+        let reels: Reels = Reels::from(&machine());
+
+        let first: Spin = reels.spin(&mut Rng::new(7));
+        let second: Spin = reels.spin(&mut Rng::new(7));
+        assert_eq!(first.stops, second.stops);
+    }
+}
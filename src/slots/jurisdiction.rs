@@ -0,0 +1,29 @@
+use crate::{StateParams, ACCOUNT_URL, SERVICE_TOKEN};
+
+use leprecon::jurisdiction::get_jurisdiction;
+use tracing::error;
+
+/// Country of the user.
+///
+/// Unknown for anonymous users and when the account service cannot tell, which only offers
+/// machines without country restrictions.
+pub(crate) async fn country_code(sub: &str, state: &StateParams) -> Option<String> {
+    if sub.is_empty() {
+        return None;
+    }
+
+    match get_jurisdiction(
+        &state.2,
+        ACCOUNT_URL.get().unwrap(),
+        SERVICE_TOKEN.get().unwrap(),
+        sub,
+    )
+    .await
+    {
+        Ok(v) => v.country_code,
+        Err(e) => {
+            error!("Could not get jurisdiction of {}: {:?}", sub, e);
+            None
+        }
+    }
+}
@@ -0,0 +1,114 @@
+mod engine;
+mod jurisdiction;
+mod simulation;
+mod spin;
+
+use axum::{middleware, serve, Router};
+use leprecon::{
+    broker::init_broker, i18n::resolve_locale, signals::shutdown_signal, utils::configure_tracing,
+};
+use mongodb::options::ClientOptions;
+use rabbitmq_stream_client::types::ByteCapacity;
+use simulation::run_simulation;
+use spin::{get_machine, spin};
+use std::{env, error::Error, sync::OnceLock};
+use tokio::net::TcpListener;
+use tracing::{error, info};
+
+type StateParams = (
+    rabbitmq_stream_client::Producer<rabbitmq_stream_client::NoDedup>,
+    mongodb::Database,
+    reqwest::Client,
+);
+
+// Host variables
+static HOST: OnceLock<String> = OnceLock::new();
+static LOG_LEVEL: OnceLock<String> = OnceLock::new();
+
+// DB variables
+static GAME_CATALOG_CONN: OnceLock<String> = OnceLock::new();
+static GAME_CATALOG_DB: OnceLock<String> = OnceLock::new();
+
+// Service variables
+static ACCOUNT_URL: OnceLock<String> = OnceLock::new();
+static SERVICE_TOKEN: OnceLock<String> = OnceLock::new();
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // Checks the RTP of a machine instead of serving, only needs the catalog
+    if args.first().map(String::as_str) == Some("simulate") {
+        init_catalog_env();
+        return run_simulation(&args[1..]).await;
+    }
+
+    // Initialize env variables
+    init_env();
+
+    // Initialize broker environment
+    let environment = init_broker().await;
+    let play_stream = "game_play";
+    let create_response = environment
+        .stream_creator()
+        .max_length(ByteCapacity::GB(5))
+        .create(play_stream)
+        .await;
+
+    if let Err(e) = create_response {
+        error!("Error creating stream: {:?} {:?}", play_stream, e);
+    }
+
+    let play_producer: rabbitmq_stream_client::Producer<rabbitmq_stream_client::NoDedup> =
+        environment.producer().build(play_stream).await?;
+
+    // Configure logging
+    configure_tracing(LOG_LEVEL.get().unwrap());
+
+    // Mongo, the machines are part of the catalog
+    let client_options: ClientOptions =
+        ClientOptions::parse(GAME_CATALOG_CONN.get().unwrap()).await?;
+    let mongo_client: mongodb::Client = mongodb::Client::with_options(client_options).unwrap();
+    let mongo_db: mongodb::Database = mongo_client.database(GAME_CATALOG_DB.get().unwrap());
+
+    // Http client (holds connection pool internally)
+    let req_client: reqwest::Client = reqwest::Client::new();
+
+    // Build application and listen to incoming requests.
+    let app: Router = build_app((play_producer, mongo_db, req_client));
+    let listener: TcpListener = TcpListener::bind(HOST.get().unwrap()).await?;
+
+    info!("Running application");
+
+    // Run the app.
+    serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+
+    Ok(())
+}
+
+// Initialize env variables
+fn init_env() {
+    HOST.get_or_init(|| env::var("SLOTS_HOST").unwrap());
+    LOG_LEVEL.get_or_init(|| env::var("LOG_LEVEL").unwrap());
+
+    init_catalog_env();
+
+    ACCOUNT_URL.get_or_init(|| env::var("ACCOUNT_URL").unwrap());
+    SERVICE_TOKEN.get_or_init(|| env::var("SERVICE_TOKEN").unwrap());
+}
+
+fn init_catalog_env() {
+    GAME_CATALOG_CONN.get_or_init(|| env::var("GAME_CATALOG_CONN").unwrap());
+    GAME_CATALOG_DB.get_or_init(|| env::var("GAME_CATALOG_DB").unwrap());
+}
+
+/// Builds the application.
+fn build_app(state: StateParams) -> Router {
+    Router::new()
+        .route("/game/slots/:slug", axum::routing::get(get_machine))
+        .route("/game/slots/:slug/spin", axum::routing::post(spin))
+        .with_state(state)
+        .layer(middleware::from_fn(resolve_locale))
+}
@@ -0,0 +1,144 @@
+use crate::{
+    engine::Reels,
+    spin::{db::get_slot_game, model::SlotGame},
+    GAME_CATALOG_CONN, GAME_CATALOG_DB,
+};
+
+use leprecon::{rng::Rng, utils::validate::Validate};
+use mongodb::{bson::Document, options::ClientOptions, Client};
+use std::error::Error;
+
+/// Standard errors the simulated RTP may be off before it no longer matches.
+const TOLERANCE: f64 = 3.0;
+
+#[derive(Debug)]
+pub(crate) struct Simulation {
+    pub spins: u64,
+    /// Return to player, in percent.
+    pub rtp: f64,
+    /// Standard error of the RTP, in percent.
+    pub std_error: f64,
+    /// Share of spins which win anything, in percent.
+    pub hit_rate: f64,
+}
+
+impl Simulation {
+    pub(crate) fn matches(&self, advertised: f64) -> bool {
+        (self.rtp - advertised).abs() <= TOLERANCE * self.std_error
+    }
+}
+
+/// Plays the spins with a fixed bet of one, the same seed gives the same result.
+pub(crate) fn simulate(reels: &Reels, spins: u64, seed: u64) -> Simulation {
+    let mut rng: Rng = Rng::new(seed);
+    let mut stops: Vec<usize> = vec![0; reels.reel_count()];
+
+    let (mut sum, mut sum_squares, mut hits): (f64, f64, u64) = (0.0, 0.0, 0);
+    for _ in 0..spins {
+        reels.fill_stops(&mut rng, &mut stops);
+        let multiplier: f64 = reels.multiplier(&stops);
+
+        sum += multiplier;
+        sum_squares += multiplier * multiplier;
+        if multiplier > 0.0 {
+            hits += 1;
+        }
+    }
+
+    let n: f64 = spins as f64;
+    let mean: f64 = sum / n;
+    let variance: f64 = (sum_squares / n - mean * mean).max(0.0);
+
+    Simulation {
+        spins,
+        rtp: mean * 100.0,
+        std_error: (variance / n).sqrt() * 100.0,
+        hit_rate: hits as f64 / n * 100.0,
+    }
+}
+
+/// `slots simulate <slug> [spins] [seed]`, fails when the RTP does not match the catalog.
+pub(crate) async fn run_simulation(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let slug: &str = args
+        .first()
+        .ok_or("usage: slots simulate <slug> [spins] [seed]")?;
+    let spins: u64 = match args.get(1) {
+        Some(v) => v.parse()?,
+        None => 10_000_000,
+    };
+    let seed: u64 = match args.get(2) {
+        Some(v) => v.parse()?,
+        None => leprecon::rng::random_seed(),
+    };
+
+    let client_options: ClientOptions =
+        ClientOptions::parse(GAME_CATALOG_CONN.get().unwrap()).await?;
+    let mongo_db: mongodb::Database =
+        Client::with_options(client_options)?.database(GAME_CATALOG_DB.get().unwrap());
+
+    // Every machine, wherever it is offered
+    let game: SlotGame = get_slot_game(slug, Document::new(), &mongo_db)
+        .await?
+        .ok_or(format!("no slot game with slug {slug}"))?;
+    game.machine.validate()?;
+
+    let reels: Reels = Reels::from(&game.machine);
+    let simulation: Simulation = simulate(&reels, spins, seed);
+
+    println!(
+        "{slug}: {} spins with seed {seed}, RTP {:.3}% ± {:.3}%, hit rate {:.2}%, advertised {:.2}%",
+        simulation.spins, simulation.rtp, simulation.std_error, simulation.hit_rate, game.rtp
+    );
+
+    if !simulation.matches(game.rtp) {
+        return Err(format!(
+            "simulated RTP of {slug} is more than {TOLERANCE} standard errors off the advertised {:.2}%",
+            game.rtp
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use leprecon::machine::{Machine, Pay};
+
+    /// Two A's in a row win 4 times the bet one in four spins, so the RTP is exactly 100%.
+    fn reels() -> Reels {
+        let reel: Vec<String> = vec!["A".to_owned(), "B".to_owned()];
+        Reels::from(&Machine {
+            reels: vec![reel.clone(), reel],
+            rows: 1,
+            paylines: vec![vec![0, 0]],
+            paytable: vec![Pay {
+                symbol: "A".to_owned(),
+                count: 2,
+                multiplier: 4.0,
+            }],
+            wild: None,
+            scatter: None,
+        })
+    }
+
+    #[test]
+    fn test_simulation_matches_rtp() {
+        let simulation: Simulation = simulate(&reels(), 100_000, 42);
+
+        assert_eq!(simulation.spins, 100_000);
+        assert!((simulation.hit_rate - 25.0).abs() < 1.0);
+        assert!(simulation.matches(100.0));
+        assert!(!simulation.matches(90.0));
+    }
+
+    #[test]
+    fn test_simulation_is_reproducible() {
+        let first: Simulation = simulate(&reels(), 1_000, 7);
+        let second: Simulation = simulate(&reels(), 1_000, 7);
+
+        assert_eq!(first.rtp, second.rtp);
+    }
+}
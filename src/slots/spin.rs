@@ -0,0 +1,260 @@
+pub(crate) mod db;
+pub(crate) mod model;
+
+use self::{
+    db::get_slot_game,
+    model::{SlotGame, SpinParams},
+};
+
+use crate::{
+    engine::{Reels, Spin},
+    jurisdiction::country_code,
+    StateParams, ACCOUNT_URL, SERVICE_TOKEN,
+};
+
+use askama::Template;
+use axum::{
+    extract::{Path, State},
+    response::Html,
+    Form,
+};
+use chrono::Utc;
+use leprecon::{
+    auth::AuthParam,
+    broker::GamePlayStarted,
+    i18n::{translate_with, FluentArgs},
+    jurisdiction::jurisdiction_filter,
+    rng::{random_seed, Rng},
+    template::{self, Snackbar},
+    utils::{extract::ValidForm, validate::Validate},
+    wallet::{get_wallet, take_stake, StakeError, WagerParams},
+};
+use rabbitmq_stream_client::{error::ProducerPublishError, types::Message, NoDedup, Producer};
+use reqwest::StatusCode;
+use serde::Serialize;
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+/// Tries to debit a bet while the account service cannot be reached.
+const STAKE_ATTEMPTS: u8 = 3;
+
+/// Machine before the first spin.
+pub(super) async fn get_machine(
+    State(state): State<StateParams>,
+    Path(slug): Path<String>,
+    Form(auth_param): Form<AuthParam>,
+) -> (StatusCode, Html<String>) {
+    let country: Option<String> = country_code(&auth_param.sub, &state).await;
+    let game: SlotGame = match find_game(&slug, country.as_deref(), &state.1).await {
+        Ok(v) => v,
+        Err(e) => return e,
+    };
+
+    let reels: Reels = Reels::from(&game.machine);
+    let spin: Spin = reels.evaluate(vec![0; game.machine.reels.len()]);
+
+    let machine: template::SlotMachine = template::SlotMachine {
+        slug: game.slug,
+        name: game.name,
+        window: spin.window,
+        wins: vec![],
+        payout: None,
+        bet: game.min_bet,
+        min_bet: game.min_bet,
+        max_bet: game.max_bet,
+    };
+
+    (StatusCode::OK, Html(machine.render().unwrap()))
+}
+
+/// Spins the machine, the result is shown once the ledger booked the bet and the win together.
+pub(super) async fn spin(
+    State(state): State<StateParams>,
+    Path(slug): Path<String>,
+    ValidForm(params): ValidForm<SpinParams>,
+) -> (StatusCode, Html<String>) {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    let country: Option<String> = country_code(&params.sub, &state).await;
+    let game: SlotGame = match find_game(&slug, country.as_deref(), &state.1).await {
+        Ok(v) => v,
+        Err(e) => return e,
+    };
+
+    if params.amount < game.min_bet || params.amount > game.max_bet {
+        let mut args: FluentArgs = FluentArgs::new();
+        args.set("min", game.min_bet);
+        args.set("max", game.max_bet);
+        let message: String = translate_with("slots-bet-out-of-range", &args);
+        snackbar.message = &message;
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Html(snackbar.render().unwrap()),
+        );
+    }
+
+    // Only the currency, the balance is checked by the debit
+    let currency: String = match get_wallet(
+        &state.2,
+        ACCOUNT_URL.get().unwrap(),
+        SERVICE_TOKEN.get().unwrap(),
+        &params.sub,
+    )
+    .await
+    {
+        Ok(v) => v.currency,
+        Err(e) => {
+            error!("Could not get wallet: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(snackbar.render().unwrap()),
+            );
+        }
+    };
+
+    if !game.currencies.contains(&currency) {
+        snackbar.message = "slots-currency-unsupported";
+        return (StatusCode::BAD_REQUEST, Html(snackbar.render().unwrap()));
+    }
+
+    let id: Uuid = Uuid::new_v4();
+    let seed: u64 = random_seed();
+    let reels: Reels = Reels::from(&game.machine);
+    let spin: Spin = reels.spin(&mut Rng::new(seed));
+    let payout: f64 = cents(params.amount * spin.multiplier);
+
+    // The seed replays the spin when a player disputes it
+    info!(
+        "Spin {id} of {} on {slug} with seed {seed} stopped at {:?} and pays {payout}",
+        params.sub, spin.stops
+    );
+
+    // Shown only once the bet and the win are booked, which the ledger does together or not at all
+    let wager: WagerParams = WagerParams {
+        sub: params.sub.clone(),
+        amount: params.amount,
+        reference: format!("slots:{id}:wager"),
+        win: payout,
+        win_reference: format!("slots:{id}:win"),
+    };
+    if let Err(e) = stake(&state.2, &wager).await {
+        return e.response();
+    }
+
+    let play: GamePlayStarted = GamePlayStarted {
+        session: format!("slots:{id}"),
+        sub: params.sub,
+        game: game.slug.clone(),
+        started_at: Utc::now(),
+    };
+    if let Err(e) = publish(&state.0, &play).await {
+        error!("Error while publishing message: {:?}", e);
+    }
+
+    let line_bet: f64 = params.amount / game.machine.paylines.len() as f64;
+    let mut wins: Vec<template::SlotWin> = spin
+        .lines
+        .into_iter()
+        .map(|win| template::SlotWin {
+            line: Some(win.line),
+            symbol: win.symbol,
+            count: win.count,
+            payout: cents(line_bet * win.multiplier),
+        })
+        .collect();
+    if let Some(win) = spin.scatter {
+        wins.push(template::SlotWin {
+            line: None,
+            symbol: win.symbol,
+            count: win.count,
+            payout: cents(params.amount * win.multiplier),
+        });
+    }
+
+    let machine: template::SlotMachine = template::SlotMachine {
+        slug: game.slug,
+        name: game.name,
+        window: spin.window,
+        wins,
+        payout: Some(payout),
+        bet: params.amount,
+        min_bet: game.min_bet,
+        max_bet: game.max_bet,
+    };
+
+    (StatusCode::OK, Html(machine.render().unwrap()))
+}
+
+/// Has the account book the bet and the win, retrying with the same reference while the outcome is
+/// unknown.
+async fn stake(req_client: &reqwest::Client, wager: &WagerParams) -> Result<String, StakeError> {
+    let mut result: Result<String, StakeError> = Err(StakeError::Unavailable);
+
+    for _ in 0..STAKE_ATTEMPTS {
+        result = take_stake(
+            req_client,
+            ACCOUNT_URL.get().unwrap(),
+            SERVICE_TOKEN.get().unwrap(),
+            wager,
+        )
+        .await;
+
+        if !matches!(result, Err(StakeError::Unavailable)) {
+            break;
+        }
+    }
+
+    // Booked in full or not at all, the ledger tells by the reference
+    if let Err(StakeError::Unavailable) = result {
+        error!(
+            "Outcome of stake {} of {} is unknown",
+            wager.reference, wager.sub
+        );
+    }
+
+    result
+}
+
+/// Slot game offered in the country.
+async fn find_game(
+    slug: &str,
+    country_code: Option<&str>,
+    db: &mongodb::Database,
+) -> Result<SlotGame, (StatusCode, Html<String>)> {
+    let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+    match get_slot_game(slug, jurisdiction_filter(country_code), db).await {
+        // Admins validate the machine, but the catalog can be edited directly
+        Ok(Some(v)) if v.machine.validate().is_ok() => Ok(v),
+        Ok(Some(_)) => {
+            error!("Machine of {slug} is invalid");
+            Err((StatusCode::BAD_GATEWAY, Html(snackbar.render().unwrap())))
+        }
+        Ok(None) => {
+            snackbar.message = "game-does-not-exist";
+            Err((StatusCode::NOT_FOUND, Html(snackbar.render().unwrap())))
+        }
+        Err(e) => {
+            debug!("Could not get game: {:?}", e);
+            Err((StatusCode::BAD_GATEWAY, Html(snackbar.render().unwrap())))
+        }
+    }
+}
+
+async fn publish<T: Serialize>(
+    producer: &Producer<NoDedup>,
+    message: &T,
+) -> Result<(), ProducerPublishError> {
+    producer
+        .send_with_confirm(
+            Message::builder()
+                .body(serde_json::to_string(message).unwrap())
+                .build(),
+        )
+        .await
+        .map(|_| ())
+}
+
+fn cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
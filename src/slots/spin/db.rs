@@ -0,0 +1,22 @@
+use super::model::SlotGame;
+
+use mongodb::{
+    bson::{doc, Document},
+    options::FindOneOptions,
+};
+
+/// Enabled slot game of the catalog which has a machine, games outside the jurisdiction are not
+/// offered.
+pub(crate) async fn get_slot_game(
+    slug: &str,
+    jurisdiction: Document,
+    conn: &mongodb::Database,
+) -> Result<Option<SlotGame>, mongodb::error::Error> {
+    let collection: mongodb::Collection<SlotGame> = conn.collection::<SlotGame>("catalog");
+
+    let mut filter: Document =
+        doc! { "slug": slug, "category": "Slots", "enabled": true, "machine": { "$exists": true } };
+    filter.extend(jurisdiction);
+
+    collection.find_one(filter, FindOneOptions::default()).await
+}
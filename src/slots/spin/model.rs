@@ -0,0 +1,46 @@
+use leprecon::{
+    machine::Machine,
+    utils::validate::{is_whole_cents, Validate, ValidationError},
+};
+use serde::Deserialize;
+
+/// The fields of a catalog entry the slots service plays by.
+#[derive(Deserialize, Debug)]
+pub(crate) struct SlotGame {
+    pub slug: String,
+    pub name: String,
+    /// Advertised return to player, in percent.
+    pub rtp: f64,
+    pub min_bet: f64,
+    pub max_bet: f64,
+    pub currencies: Vec<String>,
+    pub machine: Machine,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct SpinParams {
+    #[serde(default)]
+    pub sub: String,
+    pub amount: f64,
+}
+
+impl Validate for SpinParams {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.sub.is_empty() {
+            return Err(ValidationError::new("sub", "is required"));
+        }
+
+        if !self.amount.is_finite() || self.amount <= 0.0 {
+            return Err(ValidationError::new("amount", "must be positive"));
+        }
+
+        if !is_whole_cents(self.amount) {
+            return Err(ValidationError::new(
+                "amount",
+                "cannot have more than two decimals",
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -1,11 +1,32 @@
+mod audit;
 mod balance;
+mod blackjack;
 mod catalog;
+mod deposit;
+mod export;
+mod filters;
+mod kyc;
+mod limit;
+mod locale;
+mod mail;
+mod notification;
 mod payment_balance;
+mod slots;
 mod snackbar;
 mod user;
 
+pub use audit::*;
 pub use balance::*;
+pub use blackjack::*;
 pub use catalog::*;
+pub use deposit::*;
+pub use export::*;
+pub use kyc::*;
+pub use limit::*;
+pub use locale::*;
+pub use mail::*;
+pub use notification::*;
 pub use payment_balance::*;
+pub use slots::*;
 pub use snackbar::*;
 pub use user::*;
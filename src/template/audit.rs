@@ -0,0 +1,17 @@
+use askama::Template;
+
+#[derive(Template)]
+#[template(path = "audit_log.html")]
+pub struct AuditLog {
+    pub entries: Vec<AuditLogEntry>,
+}
+
+pub struct AuditLogEntry {
+    pub sub: String,
+    pub actor: String,
+    pub action: String,
+    pub diff: Option<String>,
+    pub request_id: Option<String>,
+    pub ip: Option<String>,
+    pub created: String,
+}
@@ -0,0 +1,38 @@
+use super::filters;
+
+use askama::Template;
+
+#[derive(Template)]
+#[template(path = "blackjack.html")]
+pub struct BlackjackTable {
+    pub round: Option<BlackjackRound>,
+    pub min_bet: f64,
+    pub max_bet: f64,
+}
+
+impl BlackjackTable {
+    /// A new round can be dealt when none is in play.
+    pub fn betting(&self) -> bool {
+        self.round.as_ref().map_or(true, |r| r.finished)
+    }
+}
+
+pub struct BlackjackRound {
+    /// Only the up card while the round is in play.
+    pub dealer: Vec<String>,
+    pub dealer_value: Option<u8>,
+    pub hands: Vec<BlackjackHand>,
+    pub actions: Vec<String>,
+    pub insurance: Option<f64>,
+    pub finished: bool,
+    pub staked: f64,
+    pub payout: f64,
+}
+
+pub struct BlackjackHand {
+    pub cards: Vec<String>,
+    pub value: u8,
+    pub bet: f64,
+    pub active: bool,
+    pub outcome: Option<String>,
+}
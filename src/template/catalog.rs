@@ -1,14 +1,55 @@
+use super::filters;
+
 use askama::Template;
-use serde::{Deserialize, Serialize};
 
 #[derive(Template)]
 #[template(path = "catalog.html")]
 pub struct Catalogs {
+    pub title: String,
+    /// Shows the search form, lists of the user are not searchable.
+    pub search: bool,
+    pub catalogs: Vec<Catalog>,
+    pub next: Option<String>,
+    pub q: String,
+    pub category: String,
+    pub tag: String,
+    pub sort: String,
+}
+
+/// Games following the previous page, for infinite scroll.
+#[derive(Template)]
+#[template(path = "catalog_page.html")]
+pub struct CatalogPage {
     pub catalogs: Vec<Catalog>,
+    pub next: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
 pub struct Catalog {
+    pub slug: String,
+    pub name: String,
+    pub description: String,
+    pub category: String,
+    pub provider: String,
+    pub thumbnail_url: String,
+    /// Plays over the last week.
+    pub plays: i64,
+}
+
+#[derive(Template)]
+#[template(path = "game_detail.html")]
+pub struct GameDetail {
+    pub slug: String,
     pub name: String,
     pub description: String,
+    pub category: String,
+    pub provider: String,
+    pub rtp: f64,
+    pub min_bet: f64,
+    pub max_bet: f64,
+    pub currencies: Vec<String>,
+    pub thumbnail_url: String,
+    pub tags: Vec<String>,
+    pub release_date: String,
+    pub plays_day: i64,
+    pub plays_week: i64,
 }
@@ -0,0 +1,17 @@
+use askama::Template;
+
+#[derive(Template)]
+#[template(path = "deposits.html")]
+pub struct Deposits {
+    pub deposits: Vec<Deposit>,
+}
+
+pub struct Deposit {
+    pub id: i32,
+    pub amount: f64,
+    pub currency: String,
+    pub status: String,
+    pub provider_reference: Option<String>,
+    pub created: String,
+    pub updated: String,
+}
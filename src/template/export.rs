@@ -0,0 +1,8 @@
+use askama::Template;
+
+#[derive(Template)]
+#[template(path = "export.html")]
+pub struct Export {
+    pub token: String,
+    pub expires: String,
+}
@@ -0,0 +1,8 @@
+use crate::i18n::translate;
+
+use std::fmt::Display;
+
+/// Translates the message id in the locale of the request.
+pub fn t<T: Display>(id: T) -> askama::Result<String> {
+    Ok(translate(&id.to_string()))
+}
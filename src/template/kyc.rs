@@ -0,0 +1,15 @@
+use askama::Template;
+
+#[derive(Template)]
+#[template(path = "kyc.html")]
+pub struct Kyc {
+    pub status: String,
+    pub reason: Option<String>,
+    pub documents: Vec<KycDocument>,
+}
+
+pub struct KycDocument {
+    pub document_type: String,
+    pub file_name: String,
+    pub uploaded: String,
+}
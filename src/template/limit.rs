@@ -0,0 +1,21 @@
+use askama::Template;
+
+#[derive(Template)]
+#[template(path = "limits.html")]
+pub struct Limits {
+    pub limits: Vec<Limit>,
+}
+
+pub struct Limit {
+    pub kind: String,
+    pub period: String,
+    pub amount: f64,
+    pub pending: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "self_exclusion.html")]
+pub struct SelfExclusion {
+    pub excluded: bool,
+    pub until: Option<String>,
+}
@@ -0,0 +1,9 @@
+use super::filters;
+
+use askama::Template;
+
+#[derive(Template)]
+#[template(path = "locale.html")]
+pub struct LocalePreference {
+    pub locale: String,
+}
@@ -0,0 +1,76 @@
+use askama::Template;
+
+/// Verification link, as sent to the user.
+pub struct VerificationMail {
+    pub link: String,
+    pub expires: String,
+}
+
+#[derive(Template)]
+#[template(path = "mail/verification.html")]
+pub struct VerificationMailHtml<'a> {
+    pub mail: &'a VerificationMail,
+}
+
+#[derive(Template)]
+#[template(path = "mail/verification.txt")]
+pub struct VerificationMailText<'a> {
+    pub mail: &'a VerificationMail,
+}
+
+pub struct DepositReceiptMail {
+    pub amount: f64,
+    pub currency: String,
+    pub reference: String,
+    pub created: String,
+}
+
+#[derive(Template)]
+#[template(path = "mail/deposit_receipt.html")]
+pub struct DepositReceiptMailHtml<'a> {
+    pub mail: &'a DepositReceiptMail,
+}
+
+#[derive(Template)]
+#[template(path = "mail/deposit_receipt.txt")]
+pub struct DepositReceiptMailText<'a> {
+    pub mail: &'a DepositReceiptMail,
+}
+
+pub struct WithdrawalStatusMail {
+    pub status: String,
+    pub amount: f64,
+    pub currency: String,
+    pub reference: String,
+    pub reason: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "mail/withdrawal_status.html")]
+pub struct WithdrawalStatusMailHtml<'a> {
+    pub mail: &'a WithdrawalStatusMail,
+}
+
+#[derive(Template)]
+#[template(path = "mail/withdrawal_status.txt")]
+pub struct WithdrawalStatusMailText<'a> {
+    pub mail: &'a WithdrawalStatusMail,
+}
+
+pub struct LimitChangeMail {
+    pub kind: String,
+    pub period: String,
+    pub change: String,
+}
+
+#[derive(Template)]
+#[template(path = "mail/limit_change.html")]
+pub struct LimitChangeMailHtml<'a> {
+    pub mail: &'a LimitChangeMail,
+}
+
+#[derive(Template)]
+#[template(path = "mail/limit_change.txt")]
+pub struct LimitChangeMailText<'a> {
+    pub mail: &'a LimitChangeMail,
+}
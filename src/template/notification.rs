@@ -0,0 +1,22 @@
+use askama::Template;
+
+#[derive(Template)]
+#[template(path = "notifications.html")]
+pub struct Notifications {
+    pub unread: usize,
+    pub notifications: Vec<Notification>,
+}
+
+pub struct Notification {
+    pub id: String,
+    pub title: String,
+    pub message: String,
+    pub created: String,
+    pub read: bool,
+}
+
+#[derive(Template)]
+#[template(path = "notification_count.html")]
+pub struct NotificationCount {
+    pub unread: usize,
+}
@@ -0,0 +1,26 @@
+use super::filters;
+
+use askama::Template;
+
+#[derive(Template)]
+#[template(path = "slots.html")]
+pub struct SlotMachine {
+    pub slug: String,
+    pub name: String,
+    /// Symbols in view, row by row.
+    pub window: Vec<Vec<String>>,
+    pub wins: Vec<SlotWin>,
+    /// Only after a spin.
+    pub payout: Option<f64>,
+    pub bet: f64,
+    pub min_bet: f64,
+    pub max_bet: f64,
+}
+
+pub struct SlotWin {
+    /// None for a scatter win.
+    pub line: Option<usize>,
+    pub symbol: String,
+    pub count: usize,
+    pub payout: f64,
+}
@@ -1,5 +1,8 @@
+use super::filters;
+
 use askama::Template;
 
+/// Title and message are message ids, translated when rendered. Other text is shown as it is.
 #[derive(Template)]
 #[template(path = "snackbar.html")]
 pub struct Snackbar<'a> {
@@ -17,8 +20,8 @@ impl<'a> Default for Snackbar<'a> {
 impl<'a> Snackbar<'a> {
     fn new() -> Snackbar<'a> {
         Snackbar {
-            title: "Error",
-            message: "Could not process request",
+            title: "error",
+            message: "could-not-process-request",
             color: "red",
         }
     }
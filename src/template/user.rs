@@ -8,6 +8,7 @@ pub struct UserInformation<'a> {
     pub account_details: AccountDetails,
     pub name_input: NameInput<'a>,
     pub address_input: AddressInput<'a>,
+    pub version_input: VersionInput,
 }
 
 #[derive(Template)]
@@ -22,10 +23,33 @@ pub struct AccountDetails {
 #[template(path = "user_information/name_input.html")]
 pub struct NameInput<'a> {
     pub inputs: IndexMap<&'a str, Option<String>>,
+    pub errors: IndexMap<&'a str, String>,
+    pub oob: bool,
 }
 
 #[derive(Template)]
 #[template(path = "user_information/address_input.html")]
 pub struct AddressInput<'a> {
     pub inputs: IndexMap<&'a str, Option<String>>,
+    pub errors: IndexMap<&'a str, String>,
+    pub oob: bool,
+}
+
+#[derive(Template)]
+#[template(path = "user_information/version_input.html")]
+pub struct VersionInput {
+    pub version: i32,
+    pub oob: bool,
+}
+
+impl<'a> NameInput<'a> {
+    pub fn error(&self, key: &str) -> Option<&String> {
+        self.errors.get(key)
+    }
+}
+
+impl<'a> AddressInput<'a> {
+    pub fn error(&self, key: &str) -> Option<&String> {
+        self.errors.get(key)
+    }
 }
@@ -1,4 +1,5 @@
 pub mod extract;
+pub mod validate;
 
 use bb8_postgres::PostgresConnectionManager;
 use bb8_redis::{
@@ -1,9 +1,17 @@
+use super::validate::Validate;
+
 use crate::template::Snackbar;
 
 use askama::Template;
-use axum::response::Html;
+use axum::{
+    async_trait,
+    extract::{FromRequest, Request},
+    response::Html,
+    Form, Json,
+};
 use bb8_redis::bb8::{ManageConnection, Pool, PooledConnection};
 use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
 use tracing::debug;
 
 pub async fn extract_conn_from_pool<'a, M>(
@@ -24,3 +32,73 @@ where
         }
     }
 }
+
+/// Form extractor which validates the input, and renders a snackbar on rejection.
+pub struct ValidForm<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for ValidForm<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, Html<String>);
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let value: T = match Form::<T>::from_request(req, state).await {
+            Ok(Form(v)) => v,
+            Err(e) => {
+                debug!("Could not deserialize form: {:?}", e);
+                return Err(rejection(&e.body_text()));
+            }
+        };
+
+        if let Err(e) = value.validate() {
+            debug!("Invalid form input: {:?}", e);
+            return Err(rejection(&e.to_string()));
+        }
+
+        Ok(ValidForm(value))
+    }
+}
+
+/// Json extractor which validates the input, for bodies too nested for a form.
+pub struct ValidJson<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for ValidJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, Html<String>);
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let value: T = match Json::<T>::from_request(req, state).await {
+            Ok(Json(v)) => v,
+            Err(e) => {
+                debug!("Could not deserialize json: {:?}", e);
+                return Err(rejection(&e.body_text()));
+            }
+        };
+
+        if let Err(e) = value.validate() {
+            debug!("Invalid json input: {:?}", e);
+            return Err(rejection(&e.to_string()));
+        }
+
+        Ok(ValidJson(value))
+    }
+}
+
+fn rejection(message: &str) -> (StatusCode, Html<String>) {
+    let snackbar: Snackbar<'_> = Snackbar {
+        message,
+        ..Default::default()
+    };
+
+    (
+        StatusCode::UNPROCESSABLE_ENTITY,
+        Html(snackbar.render().unwrap()),
+    )
+}
@@ -0,0 +1,59 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+/// Validation of deserialized request input.
+pub trait Validate {
+    fn validate(&self) -> Result<(), ValidationError>;
+}
+
+/// Whether the amount has at most two decimals.
+///
+/// Compared with a margin, as amounts like 19.99 are not exact in binary.
+pub fn is_whole_cents(amount: f64) -> bool {
+    let cents: f64 = amount * 100.0;
+    (cents - cents.round()).abs() <= 1e-9 * cents.abs().max(1.0)
+}
+
+#[derive(Debug)]
+pub struct ValidationError {
+    pub field: String,
+    pub message: String,
+}
+
+impl ValidationError {
+    pub fn new(field: &str, message: &str) -> ValidationError {
+        ValidationError {
+            field: field.to_owned(),
+            message: message.to_owned(),
+        }
+    }
+}
+
+impl Error for ValidationError {}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_whole_cents() {
+        for amount in [19.99, 0.29, 1.13, 4.35, 0.01, 10.0, 100_000.1] {
+            assert!(is_whole_cents(amount), "{amount}");
+        }
+    }
+
+    #[test]
+    fn test_fractional_cents() {
+        for amount in [0.001, 19.999, 1.125, 4.3501] {
+            assert!(!is_whole_cents(amount), "{amount}");
+        }
+    }
+}
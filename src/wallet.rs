@@ -0,0 +1,7 @@
+mod model;
+mod request;
+mod stake;
+
+pub use model::*;
+pub use request::{debit_wager, get_wallet};
+pub use stake::{take_stake, StakeError};
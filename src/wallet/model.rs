@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+/// Balance of the user, as booked by the account ledger.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct Wallet {
+    pub balance: f64,
+    pub currency: String,
+}
+
+/// Stake a game takes from the balance, a retry with the same reference is not debited twice.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WagerParams {
+    #[serde(default)]
+    pub sub: String,
+    pub amount: f64,
+    #[serde(default)]
+    pub reference: String,
+    /// Credited together with the stake, for games which decide the outcome before taking it.
+    #[serde(default)]
+    pub win: f64,
+    #[serde(default)]
+    pub win_reference: String,
+}
+
+/// Answer of the account service to a wager.
+#[derive(Debug)]
+pub enum WagerOutcome {
+    Debited(Wallet),
+    /// The limits or the balance of the user do not allow the wager, with the reason in the locale
+    /// of the request.
+    Refused(String),
+}
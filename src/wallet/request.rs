@@ -0,0 +1,48 @@
+use super::{WagerOutcome, WagerParams, Wallet};
+use crate::limit::LimitCheck;
+
+use reqwest::StatusCode;
+
+/// Asks the account service for the balance of the user.
+pub async fn get_wallet(
+    req_client: &reqwest::Client,
+    account_url: &str,
+    service_token: &str,
+    sub: &str,
+) -> Result<Wallet, reqwest::Error> {
+    req_client
+        .get(format!("{account_url}/account/user/wallet"))
+        .bearer_auth(service_token)
+        .query(&[("sub", sub)])
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Wallet>()
+        .await
+}
+
+/// Asks the account service to check the limits of the user and debit the wager.
+pub async fn debit_wager(
+    req_client: &reqwest::Client,
+    account_url: &str,
+    service_token: &str,
+    params: &WagerParams,
+) -> Result<WagerOutcome, reqwest::Error> {
+    let response: reqwest::Response = req_client
+        .post(format!("{account_url}/account/user/wager"))
+        .bearer_auth(service_token)
+        .form(params)
+        .send()
+        .await?;
+
+    if response.status() == StatusCode::CONFLICT {
+        let check: LimitCheck = response.json::<LimitCheck>().await?;
+        return Ok(WagerOutcome::Refused(check.message()));
+    }
+
+    response
+        .error_for_status()?
+        .json::<Wallet>()
+        .await
+        .map(WagerOutcome::Debited)
+}
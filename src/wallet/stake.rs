@@ -0,0 +1,60 @@
+use super::{debit_wager, WagerOutcome, WagerParams};
+use crate::template::Snackbar;
+
+use askama::Template;
+use axum::response::Html;
+use reqwest::StatusCode;
+use tracing::error;
+
+/// Why a stake was not taken.
+#[derive(Debug)]
+pub enum StakeError {
+    /// The limits or the balance of the user do not allow the stake, nothing was debited.
+    Refused(String),
+    /// The account service could not be asked, the stake may have been debited.
+    Unavailable,
+}
+
+impl StakeError {
+    /// Snackbar telling the player why the stake was not taken.
+    pub fn response(&self) -> (StatusCode, Html<String>) {
+        let mut snackbar: Snackbar<'_> = Snackbar::default();
+
+        match self {
+            StakeError::Refused(reason) => {
+                snackbar.message = reason;
+                (StatusCode::BAD_REQUEST, Html(snackbar.render().unwrap()))
+            }
+            StakeError::Unavailable => {
+                snackbar.message = "stake-pending";
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Html(snackbar.render().unwrap()),
+                )
+            }
+        }
+    }
+}
+
+/// Has the account service check the limits of the user and debit the stake, returns the currency
+/// of the balance.
+///
+/// Deal only once this succeeded. The reference identifies the stake, when the outcome is unknown
+/// the stake can be taken again with the same reference without debiting it twice.
+pub async fn take_stake(
+    req_client: &reqwest::Client,
+    account_url: &str,
+    service_token: &str,
+    params: &WagerParams,
+) -> Result<String, StakeError> {
+    let reference: &str = &params.reference;
+
+    match debit_wager(req_client, account_url, service_token, params).await {
+        Ok(WagerOutcome::Debited(wallet)) => Ok(wallet.currency),
+        Ok(WagerOutcome::Refused(reason)) => Err(StakeError::Refused(reason)),
+        Err(e) => {
+            error!("Could not debit stake {}: {:?}", reference, e);
+            Err(StakeError::Unavailable)
+        }
+    }
+}